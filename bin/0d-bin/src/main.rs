@@ -13,6 +13,9 @@ use zerod_api::{ApiService, AppState};
 use zerod_db::{init_pool, run_migrations};
 use zerod_indexer::task::IndexerTask;
 use zerod_kpi::KpiTask;
+use zerod_quoting::{PriceService, currencies::CURRENCIES_PRICES};
+use zerod_types::EventBus;
+use zerod_webhooks::WebhookService;
 
 /// The list of all the starknet rpcs that the FallbackProvider may use.
 /// They're sorted by priority (so we sorted them by reliability here).
@@ -45,22 +48,44 @@ async fn main() -> Result<()> {
     )
     .expect("Could not init the starknet provider");
 
+    // On-chain fallback of the prices when Pyth is unavailable
+    CURRENCIES_PRICES.set_starknet_provider(starknet_provider.clone());
+
     let pool = init_pool(app_name, &database_url)?;
     run_migrations(&pool).await?;
 
-    let app_state = AppState { pool: pool.clone() };
+    // Price history used to convert time series at the rate of each point
+    CURRENCIES_PRICES.set_db_pool(pool.clone());
+
+    // Indexed events fanned out to the streaming endpoints
+    let event_bus = EventBus::new();
+
+    let app_state = AppState {
+        pool: pool.clone(),
+        events: event_bus.clone(),
+    };
 
     let api_service = ApiService::new(app_state, "0.0.0.0", api_port);
 
-    let indexer_service =
-        IndexerTask::new(pool.clone(), apibara_api_key, starknet_provider.clone());
+    let indexer_service = IndexerTask::new(
+        pool.clone(),
+        apibara_api_key,
+        starknet_provider.clone(),
+        event_bus,
+    );
 
     let kpi_service = KpiTask::new(pool.clone());
 
+    let price_service = PriceService::new(pool.clone());
+
+    let webhook_service = WebhookService::new(pool.clone());
+
     ServiceGroup::default()
         .with_critical(api_service)
         .with_critical(indexer_service)
         .with_critical(kpi_service)
+        .with_critical(price_service)
+        .with_critical(webhook_service)
         .start_and_drive_to_end()
         .await?;
 
@@ -0,0 +1,106 @@
+use axum::{
+    http::{HeaderValue, header},
+    response::{IntoResponse, Response},
+};
+
+/// Minimal RFC 4180 CSV writer used by the export endpoints
+#[derive(Debug)]
+pub struct CsvWriter {
+    buffer: String,
+    columns: usize,
+}
+
+impl CsvWriter {
+    /// Create a writer and emit the header row
+    pub fn new(headers: &[&str]) -> Self {
+        let mut writer = Self {
+            buffer: String::new(),
+            columns: headers.len(),
+        };
+        writer.push_record(headers.iter());
+        writer
+    }
+
+    /// Append a row. Missing trailing fields are left empty and extra ones are dropped
+    /// so that every row has as many fields as the header.
+    pub fn write_row<I, S>(&mut self, fields: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let fields: Vec<S> = fields.into_iter().take(self.columns).collect();
+        let missing = self.columns - fields.len();
+        self.push_record(
+            fields
+                .iter()
+                .map(AsRef::as_ref)
+                .chain(std::iter::repeat_n("", missing)),
+        );
+    }
+
+    fn push_record<I, S>(&mut self, fields: I)
+    where
+        I: Iterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for (i, field) in fields.enumerate() {
+            if i > 0 {
+                self.buffer.push(',');
+            }
+            Self::push_field(&mut self.buffer, field.as_ref());
+        }
+        self.buffer.push_str("\r\n");
+    }
+
+    fn push_field(buffer: &mut String, field: &str) {
+        if field.contains([',', '"', '\r', '\n']) {
+            buffer.push('"');
+            buffer.push_str(&field.replace('"', "\"\""));
+            buffer.push('"');
+        } else {
+            buffer.push_str(field);
+        }
+    }
+
+    pub fn finish(self) -> String {
+        self.buffer
+    }
+
+    /// Turn the document into a downloadable `text/csv` response
+    pub fn into_response(self, filename: &str) -> Response {
+        let disposition = HeaderValue::from_str(&format!("attachment; filename=\"{filename}\""))
+            .unwrap_or_else(|_| HeaderValue::from_static("attachment"));
+
+        (
+            [
+                (
+                    header::CONTENT_TYPE,
+                    HeaderValue::from_static("text/csv; charset=utf-8"),
+                ),
+                (header::CONTENT_DISPOSITION, disposition),
+            ],
+            self.finish(),
+        )
+            .into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_escapes_fields_with_separators_and_quotes() {
+        let mut writer = CsvWriter::new(&["a", "b"]);
+        writer.write_row(["1,5", "say \"hi\""]);
+        assert_eq!(writer.finish(), "a,b\r\n\"1,5\",\"say \"\"hi\"\"\"\r\n");
+    }
+
+    #[test]
+    fn test_pads_and_truncates_rows_to_header_width() {
+        let mut writer = CsvWriter::new(&["a", "b", "c"]);
+        writer.write_row(["1"]);
+        writer.write_row(["1", "2", "3", "4"]);
+        assert_eq!(writer.finish(), "a,b,c\r\n1,,\r\n1,2,3\r\n");
+    }
+}
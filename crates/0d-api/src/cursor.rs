@@ -0,0 +1,142 @@
+use std::sync::LazyLock;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use sha2::{Digest, Sha256};
+
+use crate::errors::ApiError;
+
+/// Key signing the cursors, read from `CURSOR_SECRET`. Release builds refuse to start without
+/// it (see `ensure_key`). Debug builds generate a random key instead, which invalidates the
+/// cursors handed out before a restart.
+static CURSOR_KEY: LazyLock<Vec<u8>> = LazyLock::new(|| {
+    cursor_secret().unwrap_or_else(|| {
+        tracing::warn!("CURSOR_SECRET not set; pagination cursors will not survive a restart");
+        uuid::Uuid::new_v4().as_bytes().to_vec()
+    })
+});
+
+fn cursor_secret() -> Option<Vec<u8>> {
+    std::env::var("CURSOR_SECRET")
+        .ok()
+        .map(|secret| secret.trim().to_string())
+        .filter(|secret| !secret.is_empty())
+        .map(String::into_bytes)
+}
+
+/// Check at startup that the cursors are signed with a key shared by every instance, which
+/// release builds require
+pub fn ensure_key() -> anyhow::Result<()> {
+    if !cfg!(debug_assertions) && cursor_secret().is_none() {
+        anyhow::bail!("CURSOR_SECRET must be set to sign the pagination cursors");
+    }
+    LazyLock::force(&CURSOR_KEY);
+    Ok(())
+}
+
+/// Encode a position in a listing into an opaque cursor: `<hex payload>.<hex HMAC-SHA256>`
+pub fn encode<T: Serialize>(position: &T) -> String {
+    let payload = serde_json::to_vec(position).expect("cursor positions serialize to JSON");
+    format!(
+        "{}.{}",
+        hex::encode(&payload),
+        hex::encode(sign(&CURSOR_KEY, &payload))
+    )
+}
+
+/// Decode a cursor handed out by `encode`, rejecting the ones that were altered
+pub fn decode<T: DeserializeOwned>(cursor: &str) -> Result<T, ApiError> {
+    decode_with_key(&CURSOR_KEY, cursor)
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid cursor: {cursor}")))
+}
+
+/// Position bound to the filters of the listing it was handed out for
+#[derive(Serialize, Deserialize)]
+struct Filtered<T> {
+    position: T,
+    /// Hex SHA-256 of the filters
+    filters: String,
+}
+
+/// Encode a position into a cursor only valid for a listing with the same filters
+pub fn encode_filtered<T: Serialize, F: Serialize>(position: &T, filters: &F) -> String {
+    encode(&Filtered {
+        position,
+        filters: filters_hash(filters),
+    })
+}
+
+/// Decode a cursor handed out by `encode_filtered`, rejecting the ones handed out for
+/// other filters
+pub fn decode_filtered<T: DeserializeOwned, F: Serialize>(
+    cursor: &str,
+    filters: &F,
+) -> Result<T, ApiError> {
+    let filtered: Filtered<T> = decode(cursor)?;
+    if filtered.filters != filters_hash(filters) {
+        return Err(ApiError::BadRequest(
+            "Cursor was handed out for other filters".to_string(),
+        ));
+    }
+    Ok(filtered.position)
+}
+
+fn filters_hash<F: Serialize>(filters: &F) -> String {
+    let filters = serde_json::to_vec(filters).expect("cursor filters serialize to JSON");
+    hex::encode(Sha256::digest(filters))
+}
+
+fn decode_with_key<T: DeserializeOwned>(key: &[u8], cursor: &str) -> Option<T> {
+    let (payload, signature) = cursor.split_once('.')?;
+    let payload = hex::decode(payload).ok()?;
+    let signature = hex::decode(signature).ok()?;
+
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(&payload);
+    mac.verify_slice(&signature).ok()?;
+
+    serde_json::from_slice(&payload).ok()
+}
+
+fn sign(key: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(payload);
+    mac.finalize().into_bytes().to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor_round_trip() {
+        let cursor = encode(&(42, "0xabc"));
+        assert_eq!(
+            decode::<(i32, String)>(&cursor).unwrap(),
+            (42, "0xabc".to_string())
+        );
+    }
+
+    #[test]
+    fn test_cursor_rejects_tampering() {
+        let cursor = encode(&42);
+        let (_, signature) = cursor.split_once('.').unwrap();
+        let forged = format!("{}.{signature}", hex::encode(b"43"));
+
+        assert!(decode::<i32>(&forged).is_err());
+        assert!(decode::<i32>("42").is_err());
+        assert!(decode_with_key::<i32>(b"another key", &cursor).is_none());
+    }
+
+    #[test]
+    fn test_filtered_cursor_rejects_other_filters() {
+        let cursor = encode_filtered(&42, &("deposit", "desc"));
+        assert_eq!(
+            decode_filtered::<i32, _>(&cursor, &("deposit", "desc")).unwrap(),
+            42
+        );
+        assert!(decode_filtered::<i32, _>(&cursor, &("withdraw", "desc")).is_err());
+        assert!(decode_filtered::<i32, _>(&cursor, &("deposit", "asc")).is_err());
+        assert!(decode_filtered::<i32, _>(&encode(&42), &("deposit", "desc")).is_err());
+    }
+}
@@ -5,6 +5,7 @@ use serde_json::to_string_pretty;
 use std::path::PathBuf;
 use utoipa::Modify;
 use utoipa::OpenApi;
+use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
 use utoipa::openapi::{ServerBuilder, ServerVariableBuilder};
 use utoipauto::utoipauto;
 
@@ -25,14 +26,29 @@ impl Modify for ServerAddon {
     }
 }
 
+pub struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "admin_api_key",
+            SecurityScheme::Http(HttpBuilder::new().scheme(HttpAuthScheme::Bearer).build()),
+        );
+    }
+}
+
 #[utoipauto(paths = "./crates/0d-api/src/")]
 #[derive(OpenApi)]
 #[openapi(
-    modifiers(&ServerAddon),
+    modifiers(&ServerAddon, &SecurityAddon),
     tags(
         (name = "zerod_bin", description = "0d, master api"),
         (name = "User", description = "User profile endpoints"),
-        (name = "Vaults", description = "Vault management endpoints")
+        (name = "Vaults", description = "Vault management endpoints"),
+        (name = "Streaming", description = "Real-time indexer events"),
+        (name = "Partners", description = "Referral analytics of the partners attributed to deposits"),
+        (name = "Admin", description = "Operator endpoints, authenticated with the admin API key")
     )
 )]
 pub struct ApiDoc;
@@ -1,5 +1,7 @@
+use rust_decimal::{Decimal, prelude::ToPrimitive};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
+use zerod_db::types::RiskMetric;
 
 /// Common timeseries data point used across vault and user endpoints
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -41,3 +43,45 @@ pub enum GroupBy {
     Platform,
     Asset,
 }
+
+/// Risk analytics, restricted to the metrics selected with the `metrics` query parameter
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct RiskMetrics {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_drawdown_pct: Option<f64>,
+    #[serde(rename = "sharpe", skip_serializing_if = "Option::is_none")]
+    pub sharpe_ratio: Option<f64>,
+    #[serde(rename = "sortino", skip_serializing_if = "Option::is_none")]
+    pub sortino_ratio: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volatility_pct: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub var_historical_pct: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub var_parametric_pct: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cvar_pct: Option<f64>,
+    #[serde(rename = "calmar", skip_serializing_if = "Option::is_none")]
+    pub calmar_ratio: Option<f64>,
+    #[serde(rename = "omega", skip_serializing_if = "Option::is_none")]
+    pub omega_ratio: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_drawdown_duration_days: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recovery_time_days: Option<f64>,
+}
+
+impl RiskMetrics {
+    /// Build the selected metrics from a lookup function
+    pub fn from_selection(
+        selected: &[RiskMetric],
+        value_of: impl Fn(&RiskMetric) -> Option<Decimal>,
+    ) -> Self {
+        let mut metrics = Self::default();
+        for metric in selected {
+            *zerod_db::risk_metric_field!(&mut metrics, metric) =
+                value_of(metric).and_then(|v| v.to_f64());
+        }
+        metrics
+    }
+}
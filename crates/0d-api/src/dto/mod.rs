@@ -1,11 +1,19 @@
 pub mod common;
+pub mod partner;
+pub mod partner_reward;
 pub mod query;
 pub mod response;
+pub mod stream;
 pub mod user;
 pub mod vault;
+pub mod webhook;
 
 pub use common::*;
+pub use partner::*;
+pub use partner_reward::*;
 pub use query::*;
 pub use response::*;
+pub use stream::*;
 pub use user::*;
 pub use vault::*;
+pub use webhook::*;
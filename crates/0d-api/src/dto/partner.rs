@@ -0,0 +1,128 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use zerod_db::types::{BucketInterval, Timeframe};
+
+use crate::pagination::Page;
+
+/// Fees generated by the referred positions, estimated from the vault fee rates.
+/// Management fees accrue on the current value since the referral, performance fees
+/// on the unrealized gains.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
+pub struct PartnerFees {
+    pub management_fees_usd: String,
+    pub performance_fees_usd: String,
+    pub total_fees_usd: String,
+}
+
+/// Referral activity of a partner in one vault
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartnerVaultStats {
+    pub vault_id: String,
+    pub vault_name: String,
+    pub underlying_currency: String,
+    pub referred_users: i64,
+    pub deposit_count: i64,
+    /// Deposited through the partner, in the underlying currency
+    pub deposit_volume: String,
+    /// Deposit volume at the price of the underlying currency at deposit time
+    pub deposit_volume_usd: String,
+    /// Shares currently held by the referred users
+    pub share_balance: String,
+    pub tvl_usd: String,
+    pub fees: PartnerFees,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartnerSummary {
+    pub partner_id: String,
+    pub referred_users: i64,
+    pub deposit_count: i64,
+    pub deposit_volume_usd: String,
+    /// Current value of the referred positions
+    pub referred_tvl_usd: String,
+    pub first_referral_at: DateTime<Utc>,
+    pub last_referral_at: DateTime<Utc>,
+    pub fees: PartnerFees,
+    pub vaults: Vec<PartnerVaultStats>,
+}
+
+/// User who deposited into a vault through the partner
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReferredUser {
+    pub user_address: String,
+    pub vault_id: String,
+    pub first_referred_at: DateTime<Utc>,
+    pub deposit_count: i64,
+    /// Deposited through the partner, in the underlying currency
+    pub deposit_volume: String,
+    pub share_balance: String,
+    pub value_usd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct ReferredUsersResponse {
+    pub partner_id: String,
+    #[serde(flatten)]
+    pub page: Page<ReferredUser>,
+}
+
+/// Query parameters for the partner flows endpoint
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PartnerFlowsQuery {
+    #[serde(default)]
+    pub timeframe: Timeframe,
+    #[serde(default)]
+    pub interval: BucketInterval,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartnerFlowPoint {
+    /// Start of the bucket
+    pub t: DateTime<Utc>,
+    pub deposits_usd: String,
+    pub redemptions_usd: String,
+    pub net_flow_usd: String,
+    /// Users referred for the first time in the bucket
+    pub new_users: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartnerFlowsResponse {
+    pub partner_id: String,
+    pub interval: BucketInterval,
+    pub points: Vec<PartnerFlowPoint>,
+}
+
+/// Ranking criteria of the partner leaderboard
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PartnerRanking {
+    #[default]
+    Tvl,
+    DepositVolume,
+    ReferredUsers,
+}
+
+/// Query parameters for the partner leaderboard endpoint
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PartnerLeaderboardQuery {
+    #[serde(default)]
+    pub sort_by: PartnerRanking,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartnerLeaderboardEntry {
+    pub rank: usize,
+    pub partner_id: String,
+    pub referred_users: i64,
+    pub deposit_volume_usd: String,
+    pub referred_tvl_usd: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PartnerLeaderboardResponse {
+    pub sort_by: PartnerRanking,
+    pub items: Vec<PartnerLeaderboardEntry>,
+}
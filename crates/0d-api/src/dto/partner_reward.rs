@@ -0,0 +1,147 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+/// Partner to register, identified by the `partner_id` attributed to its deposits
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreatePartnerRequest {
+    pub id: String,
+    pub display_name: String,
+    pub payout_address: String,
+    /// Share of the fees generated by the referred positions paid to the partner
+    pub reward_share_bps: i32,
+    /// Start of the active window, now when omitted
+    pub active_from: Option<DateTime<Utc>>,
+    pub active_until: Option<DateTime<Utc>>,
+}
+
+/// Fields of a partner to update
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdatePartnerRequest {
+    pub display_name: Option<String>,
+    pub payout_address: Option<String>,
+    pub reward_share_bps: Option<i32>,
+    pub active_from: Option<DateTime<Utc>>,
+    pub active_until: Option<DateTime<Utc>>,
+    /// Remove the end of the active window
+    #[serde(default)]
+    pub clear_active_until: bool,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct Partner {
+    pub id: String,
+    pub display_name: String,
+    pub payout_address: String,
+    pub reward_share_bps: i32,
+    pub active_from: DateTime<Utc>,
+    pub active_until: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Rewards accrued by a partner in a day on the positions it referred in a vault
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PartnerRewardAccrual {
+    pub id: i32,
+    pub partner_id: String,
+    pub vault_id: String,
+    pub accrual_date: NaiveDate,
+    pub referred_positions: i32,
+    pub referred_tvl_usd: String,
+    pub yield_usd: String,
+    pub management_fees_usd: String,
+    pub performance_fees_usd: String,
+    pub reward_share_bps: i32,
+    pub reward_usd: String,
+    pub payout_batch_id: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PartnerPayoutBatch {
+    pub id: i32,
+    pub partner_id: String,
+    pub payout_address: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub accruals_count: i32,
+    pub total_reward_usd: String,
+    /// `pending` or `paid`
+    pub status: String,
+    pub tx_hash: Option<String>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Payout batch along with the accruals it pays out
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct PartnerPayoutBatchDetails {
+    #[serde(flatten)]
+    pub batch: PartnerPayoutBatch,
+    pub accruals: Vec<PartnerRewardAccrual>,
+}
+
+/// Batch the accruals not paid out yet, up to a day included
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct GeneratePayoutBatchesRequest {
+    /// Last accrual day to pay out, yesterday when omitted
+    pub until: Option<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct MarkPayoutBatchPaidRequest {
+    /// Hash of the payout transaction
+    pub tx_hash: String,
+}
+
+impl From<zerod_db::models::Partner> for Partner {
+    fn from(partner: zerod_db::models::Partner) -> Self {
+        Self {
+            id: partner.id,
+            display_name: partner.display_name,
+            payout_address: partner.payout_address,
+            reward_share_bps: partner.reward_share_bps,
+            active_from: partner.active_from,
+            active_until: partner.active_until,
+            created_at: partner.created_at,
+            updated_at: partner.updated_at,
+        }
+    }
+}
+
+impl From<zerod_db::models::PartnerRewardAccrual> for PartnerRewardAccrual {
+    fn from(accrual: zerod_db::models::PartnerRewardAccrual) -> Self {
+        Self {
+            id: accrual.id,
+            partner_id: accrual.partner_id,
+            vault_id: accrual.vault_id,
+            accrual_date: accrual.accrual_date,
+            referred_positions: accrual.referred_positions,
+            referred_tvl_usd: accrual.referred_tvl_usd.round_dp(6).to_string(),
+            yield_usd: accrual.yield_usd.round_dp(6).to_string(),
+            management_fees_usd: accrual.management_fees_usd.round_dp(6).to_string(),
+            performance_fees_usd: accrual.performance_fees_usd.round_dp(6).to_string(),
+            reward_share_bps: accrual.reward_share_bps,
+            reward_usd: accrual.reward_usd.round_dp(6).to_string(),
+            payout_batch_id: accrual.payout_batch_id,
+        }
+    }
+}
+
+impl From<zerod_db::models::PartnerPayoutBatch> for PartnerPayoutBatch {
+    fn from(batch: zerod_db::models::PartnerPayoutBatch) -> Self {
+        Self {
+            id: batch.id,
+            partner_id: batch.partner_id,
+            payout_address: batch.payout_address,
+            period_start: batch.period_start,
+            period_end: batch.period_end,
+            accruals_count: batch.accruals_count,
+            total_reward_usd: batch.total_reward_usd.round_dp(6).to_string(),
+            status: batch.status,
+            tx_hash: batch.tx_hash,
+            paid_at: batch.paid_at,
+            created_at: batch.created_at,
+        }
+    }
+}
@@ -1,6 +1,11 @@
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 use utoipa::ToSchema;
-use zerod_db::types::{AprBasis, Currency, GroupBy, Metric, Timeframe};
+use zerod_db::models::{TransactionStatus, TransactionType};
+use zerod_db::types::{
+    AprBasis, BucketInterval, CostBasisMethod, Currency, GroupBy, HolderSort, Metric, SortOrder,
+    Timeframe,
+};
 
 /// Common query parameters for endpoints that accept timeframe
 #[derive(Debug, Deserialize, ToSchema)]
@@ -9,6 +14,51 @@ pub struct TimeframeQuery {
     pub timeframe: Timeframe,
 }
 
+/// Query parameters for user KPIs endpoint
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UserKpisQuery {
+    /// Comma-separated list of risk metrics to return (defaults to all)
+    pub metrics: Option<String>,
+    #[serde(default)]
+    pub cost_basis_method: CostBasisMethod,
+}
+
+/// Query parameters for vault KPIs endpoint
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VaultKpisQuery {
+    #[serde(default)]
+    pub timeframe: Timeframe,
+    /// Comma-separated list of risk metrics to return (defaults to all)
+    pub metrics: Option<String>,
+}
+
+/// Query parameters for vault flows endpoint
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VaultFlowsQuery {
+    #[serde(default)]
+    pub timeframe: Timeframe,
+    #[serde(default)]
+    pub interval: BucketInterval,
+}
+
+/// Query parameters for vault holders endpoint
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VaultHoldersQuery {
+    #[serde(default)]
+    pub sort_by: HolderSort,
+    #[serde(default)]
+    pub order: SortOrder,
+}
+
+/// Query parameters for vault comparison endpoint
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct VaultCompareQuery {
+    /// Comma-separated vault ids, a page of the live vaults when omitted
+    pub ids: Option<String>,
+    #[serde(default)]
+    pub timeframe: Timeframe,
+}
+
 /// Query parameters for APR summary endpoint
 #[derive(Debug, Deserialize, ToSchema)]
 pub struct AprSummaryQuery {
@@ -48,3 +98,50 @@ pub struct CompositionSeriesQuery {
     #[serde(default)]
     pub group_by: GroupBy,
 }
+
+/// Output format of export endpoints
+#[derive(Debug, Clone, Copy, Default, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ReportFormat {
+    #[default]
+    Json,
+    Csv,
+}
+
+/// Query parameters for tax report endpoint
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct TaxReportQuery {
+    /// Calendar year of the report (defaults to the current year)
+    pub year: Option<i32>,
+    #[serde(default)]
+    pub format: ReportFormat,
+    #[serde(default)]
+    pub cost_basis_method: CostBasisMethod,
+}
+
+/// Query parameters for user portfolio endpoint
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct PortfolioQuery {
+    #[serde(default)]
+    pub currency: Currency,
+    #[serde(default)]
+    pub timeframe: Timeframe,
+    #[serde(default)]
+    pub cost_basis_method: CostBasisMethod,
+}
+
+/// Query parameters for user transactions endpoint
+#[derive(Debug, Deserialize, ToSchema)]
+pub struct UserTransactionsQuery {
+    /// Comma-separated vault ids, all the vaults of the user when omitted
+    pub vault_ids: Option<String>,
+    #[serde(rename = "type")]
+    pub transaction_type: Option<TransactionType>,
+    pub status: Option<TransactionStatus>,
+    /// Inclusive start of the period
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive end of the period
+    pub to: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub order: SortOrder,
+}
@@ -0,0 +1,44 @@
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use zerod_types::IndexerEvent;
+
+/// Message sent by a streaming client
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum StreamClientMessage {
+    /// Start receiving the events of the given topics
+    Subscribe {
+        topics: Vec<String>,
+    },
+    /// Stop receiving the events of the given topics
+    Unsubscribe {
+        topics: Vec<String>,
+    },
+    Ping,
+}
+
+/// Message sent to a streaming client
+#[derive(Debug, Clone, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum StreamServerMessage {
+    /// Topics the client is now subscribed to
+    Subscribed {
+        topics: Vec<String>,
+    },
+    Unsubscribed {
+        topics: Vec<String>,
+    },
+    /// An indexed event published on a subscribed topic
+    Event {
+        topic: String,
+        data: Box<IndexerEvent>,
+    },
+    /// The client was too slow and missed some events
+    Lagged {
+        missed: u64,
+    },
+    Pong,
+    Error {
+        message: String,
+    },
+}
@@ -5,7 +5,7 @@ use utoipa::ToSchema;
 use zerod_db::models::user_transaction::{TransactionStatus, TransactionType};
 use zerod_types::Currency;
 
-pub use zerod_db::types::{PerformanceMetric, Timeframe};
+pub use zerod_db::types::{CostBasisMethod, PerformanceMetric, Timeframe};
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct UserProfile {
@@ -24,6 +24,13 @@ pub struct UserPositionSummary {
     pub first_deposit_at: Option<DateTime<Utc>>,
     pub total_deposits: String,
     pub all_time_earned: String,
+    pub unrealized_pnl: String,
+    pub realized_pnl: String,
+    /// Cost basis of the shares still held
+    pub cost_basis: String,
+    /// Current value of the redeem requests not yet claimed, in USD
+    pub pending_redeems: String,
+    pub pending_redeems_count: usize,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -38,10 +45,19 @@ pub struct UserTransaction {
     pub timestamp: DateTime<Utc>,
 }
 
+/// Transaction of a user along with its value at the time of the transaction
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct UserTransactionHistory {
-    pub items: Vec<UserTransaction>,
-    pub next_cursor: Option<String>,
+pub struct UserTransactionDetails {
+    #[serde(flatten)]
+    pub transaction: UserTransaction,
+    /// Currency of the amount, the underlying currency of the vault
+    pub underlying_currency: String,
+    pub shares: Option<String>,
+    pub share_price: Option<String>,
+    /// Amount in USD at the time of the transaction, `None` when the currency can't be priced
+    pub amount_usd: Option<String>,
+    pub block_number: i64,
+    pub explorer_url: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -53,6 +69,144 @@ pub struct UserKpi {
     pub max_drawdown_pct: f64,
     pub sharpe: f64,
     pub sortino: f64,
+    /// Annualized risk-free rate in effect at the latest point, the Sharpe and Sortino ratios
+    /// use the rate in effect over each period
+    pub risk_free_rate: Option<f64>,
+    /// Annualization factor derived from the sampling frequency of the portfolio history
+    pub periods_per_year: Option<f64>,
+    pub volatility_pct: Option<f64>,
+    pub var_historical_pct: Option<f64>,
+    pub var_parametric_pct: Option<f64>,
+    pub cvar_pct: Option<f64>,
+    pub calmar: Option<f64>,
+    pub omega: Option<f64>,
+    pub max_drawdown_duration_days: Option<f64>,
+    pub recovery_time_days: Option<f64>,
+}
+
+/// Gain realized on the portion of a deposit lot sold by a withdrawal
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RealizedLot {
+    pub acquired_at: DateTime<Utc>,
+    pub disposed_at: DateTime<Utc>,
+    pub acquisition_tx_hash: String,
+    pub disposal_tx_hash: String,
+    pub shares: String,
+    pub cost_basis_usd: String,
+    pub proceeds_usd: String,
+    pub realized_gain_usd: String,
+    pub holding_period_days: i64,
+    pub long_term: bool,
+}
+
+impl From<&zerod_kpi::RealizedLot> for RealizedLot {
+    fn from(lot: &zerod_kpi::RealizedLot) -> Self {
+        Self {
+            acquired_at: lot.acquired_at,
+            disposed_at: lot.disposed_at,
+            acquisition_tx_hash: lot.acquisition_tx_hash.clone(),
+            disposal_tx_hash: lot.disposal_tx_hash.clone(),
+            shares: lot.shares.to_string(),
+            cost_basis_usd: lot.cost_basis.to_string(),
+            proceeds_usd: lot.proceeds.to_string(),
+            realized_gain_usd: lot.realized_gain.to_string(),
+            holding_period_days: lot.holding_period_days,
+            long_term: lot.is_long_term(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserKpisResponse {
+    #[serde(flatten)]
+    #[schema(value_type = UserKpi)]
+    pub kpis: zerod_db::models::UserKpi,
+    /// Lot selection method used for the cost basis and realized gains
+    pub cost_basis_method: CostBasisMethod,
+    pub realized_lots: Vec<RealizedLot>,
+}
+
+/// Position of a user in a vault, valued in the requested currency
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct PortfolioPosition {
+    pub vault_id: String,
+    pub vault_name: String,
+    pub share_balance: String,
+    pub share_price_usd: String,
+    pub value_usd: String,
+    pub value: String,
+    /// Share of the total portfolio value, in percent
+    pub allocation_pct: f64,
+    pub realized_pnl: String,
+    pub unrealized_pnl: String,
+    pub all_time_pnl: String,
+    /// Whether the indexer of the vault is syncing or failing, in which case the position
+    /// may be out of date
+    pub indexer_stale: bool,
+}
+
+/// Positions of a user across all vaults, with aggregate gains and value history
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct UserPortfolio {
+    pub address: String,
+    pub as_of: DateTime<Utc>,
+    pub currency: Currency,
+    pub total_value_usd: String,
+    pub total_value: String,
+    /// Gains realized across every position ever held, closed ones included
+    pub realized_pnl: String,
+    pub unrealized_pnl: String,
+    pub all_time_pnl: String,
+    /// Positions currently held
+    pub positions: Vec<PortfolioPosition>,
+    pub history: Vec<HistoricalDataPoint>,
+}
+
+/// Deposit, redemption or claim booked in a tax report
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TaxLedgerEntry {
+    pub timestamp: DateTime<Utc>,
+    pub tx_hash: String,
+    #[serde(rename = "type")]
+    pub transaction_type: TransactionType,
+    pub shares: Option<String>,
+    pub share_price: Option<String>,
+    /// Amount in the underlying currency of the vault
+    pub amount: String,
+    /// USD value at transaction time, `None` if the underlying currency can't be priced
+    pub amount_usd: Option<String>,
+}
+
+/// Position held at the end of the reporting period
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct YearEndHolding {
+    pub shares: String,
+    pub cost_basis_usd: String,
+    /// Share price at the end of the period, if one was recorded
+    pub share_price: Option<String>,
+    pub value_usd: Option<String>,
+    pub unrealized_gain_usd: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VaultTaxReport {
+    pub vault_id: String,
+    pub entries: Vec<TaxLedgerEntry>,
+    pub realized_lots: Vec<RealizedLot>,
+    pub realized_gain_usd: String,
+    pub year_end_holding: YearEndHolding,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct TaxReport {
+    pub address: String,
+    pub year: i32,
+    pub cost_basis_method: CostBasisMethod,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub generated_at: DateTime<Utc>,
+    pub vaults: Vec<VaultTaxReport>,
+    pub total_realized_gain_usd: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
@@ -71,19 +225,39 @@ pub struct HistoricalUserPerformance {
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PendingRedeem {
     pub vault_id: String,
+    pub redeem_id: String,
+    pub epoch: i64,
+    pub shares: String,
+    /// Assets of the shares at the share price of the request
     pub amount: String,
+    /// Value of the shares at the current share price, in the requested currency
+    pub current_value: String,
     pub transaction_type: TransactionType,
     pub tx_hash: String,
     pub submitted_at: DateTime<Utc>,
+    /// Whether the epoch of the request has been processed
+    pub claimable: bool,
+    /// When the redeem is expected to be claimable: 24h after the request while the assets
+    /// queued stay within `redeem_24h_threshold_pct_of_aum` of the AUM, 48h above it when the
+    /// vault commits to it, and otherwise at the end of its epoch on the epoch schedule of the
+    /// vault. `None` when the vault has too few epochs to estimate it.
+    pub expected_claimable_at: Option<DateTime<Utc>>,
+    /// Whether the request is past its expected time without being processed, in which case
+    /// `expected_claimable_at` is the current time
+    pub overdue: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct PendingRedeemsResponse {
     pub address: String,
     pub as_of: DateTime<Utc>,
+    pub currency: Currency,
     pub pending_redeems: Vec<PendingRedeem>,
+    /// Current value of the pending redeems, in the requested currency
     pub total_pending: String,
     pub average_redeem_delay: Option<i64>,
+    /// Cursor of the next page of pending redeems
+    pub next_cursor: Option<String>,
 }
 
 impl From<zerod_db::models::User> for UserProfile {
@@ -121,21 +295,3 @@ impl From<zerod_db::models::UserTransaction> for UserTransaction {
         }
     }
 }
-
-impl From<zerod_db::models::UserTransaction> for PendingRedeem {
-    fn from(tx: zerod_db::models::UserTransaction) -> Self {
-        Self {
-            vault_id: tx.vault_id,
-            amount: tx.amount.to_string(),
-            transaction_type: match tx.type_.as_str() {
-                "deposit" => TransactionType::Deposit,
-                "withdraw" => TransactionType::Withdraw,
-                "transfer" => TransactionType::Transfer,
-                "claim" => TransactionType::Claim,
-                _ => unreachable!("Invalid transaction type: {}", tx.type_),
-            },
-            tx_hash: tx.tx_hash,
-            submitted_at: tx.block_timestamp,
-        }
-    }
-}
@@ -1,5 +1,11 @@
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
+use zerod_db::types::{BucketInterval, Timeframe};
+use zerod_master::KpisDTO;
+
+use crate::dto::{RiskMetrics, TimeseriesPoint};
+use crate::pagination::Page;
 
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct VaultListItem {
@@ -16,11 +22,6 @@ pub struct VaultListItem {
     pub last_reported: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
-pub struct VaultListResponse {
-    pub items: Vec<VaultListItem>,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 pub struct Vault {
     pub id: String,
@@ -134,3 +135,170 @@ impl From<zerod_db::models::Vault> for Vault {
         }
     }
 }
+
+#[derive(Debug, Serialize, Deserialize, ToSchema)]
+pub struct VaultKpisResponse {
+    #[serde(flatten)]
+    pub kpis: KpisDTO,
+    /// Risk analytics computed on the vault share price history, if available
+    pub risk_metrics: Option<RiskMetrics>,
+    /// Annualized risk-free rate in effect at the latest share price, the risk-adjusted ratios
+    /// use the rate in effect over each period
+    pub risk_free_rate: Option<f64>,
+    /// Annualization factor derived from the sampling frequency of the share price history
+    pub periods_per_year: Option<f64>,
+    pub risk_calculated_at: Option<DateTime<Utc>>,
+}
+
+/// Deposits and redemptions of a bucket, in the underlying currency of the vault
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VaultFlowPoint {
+    /// Start of the bucket
+    pub t: DateTime<Utc>,
+    pub deposits: String,
+    /// Redemptions requested in the bucket
+    pub redemptions: String,
+    pub net_flows: String,
+    pub unique_depositors: i64,
+    /// Depositors making their first deposit in the vault
+    pub new_users: i64,
+    /// Depositors who had already deposited in the vault before the bucket
+    pub returning_users: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VaultFlowsResponse {
+    pub vault_id: String,
+    pub underlying_currency: String,
+    pub timeframe: Timeframe,
+    pub interval: BucketInterval,
+    pub points: Vec<VaultFlowPoint>,
+    /// Cursor of the next page of points
+    pub next_cursor: Option<String>,
+}
+
+/// Daily share price of a vault
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct SharePriceSeriesResponse {
+    pub metric: String,
+    pub timeframe: String,
+    pub points: Vec<TimeseriesPoint>,
+    /// Cursor of the next page of points
+    pub next_cursor: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VaultHolder {
+    pub user_address: String,
+    pub share_balance: String,
+    pub value_usd: String,
+    /// Share of the indexed supply held, in percent
+    pub share_of_supply_pct: f64,
+    pub first_deposit_at: Option<DateTime<Utc>>,
+    pub last_activity_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VaultHoldersResponse {
+    pub vault_id: String,
+    pub total_holders: i64,
+    /// Shares held by all the holders
+    pub total_shares: String,
+    pub share_price_usd: String,
+    #[serde(flatten)]
+    pub page: Page<VaultHolder>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct HolderCountPoint {
+    pub t: DateTime<Utc>,
+    pub holders: i64,
+    pub herfindahl_index: f64,
+}
+
+/// How concentrated the shares of a vault are among its holders
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VaultHolderConcentration {
+    pub vault_id: String,
+    pub holders: i64,
+    pub total_shares: String,
+    /// Share of the supply held by the 10 largest holders, in percent
+    pub top_10_share_pct: f64,
+    /// Sum of the squared supply shares of the holders, from 1/holders (even) to 1 (single holder)
+    pub herfindahl_index: f64,
+    /// Number of equally sized holders with the same concentration (1 / Herfindahl index)
+    pub effective_holders: Option<f64>,
+    pub timeframe: Timeframe,
+    /// Daily holder count and Herfindahl index, from the daily portfolio snapshots
+    pub series: Vec<HolderCountPoint>,
+}
+
+/// Pending redemptions requested during an epoch
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RedeemQueueEpoch {
+    pub epoch: i64,
+    pub requests: i64,
+    pub users: i64,
+    pub shares: String,
+    /// Assets at the share price of the requests
+    pub assets: String,
+    pub oldest_requested_at: DateTime<Utc>,
+}
+
+/// Time between the request and the claim of the redemptions, in seconds
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct RedeemLatencyStats {
+    pub timeframe: Timeframe,
+    /// Number of claimed redemptions in the sample
+    pub claimed: i64,
+    pub p50_seconds: Option<f64>,
+    pub p90_seconds: Option<f64>,
+}
+
+/// Redemption queue of a vault, amounts in its underlying currency
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VaultRedeemsResponse {
+    pub vault_id: String,
+    pub underlying_currency: String,
+    pub current_epoch: i64,
+    pub total_pending_shares: String,
+    pub total_pending_assets: String,
+    /// Pending assets of the epochs up to the current one, due when the next epoch is processed
+    pub next_epoch_liquidity_need: String,
+    pub buffer: String,
+    /// Part of the next epoch liquidity need not covered by the buffer
+    pub next_epoch_shortfall: String,
+    pub epochs: Vec<RedeemQueueEpoch>,
+    pub latency: RedeemLatencyStats,
+}
+
+/// Performance of a vault side by side with other vaults
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VaultComparison {
+    pub vault_id: String,
+    pub name: String,
+    pub symbol: String,
+    pub underlying_currency: String,
+    /// `None` when the vault backend couldn't provide its stats
+    pub tvl: Option<String>,
+    pub tvl_usd: Option<String>,
+    /// Average APR over the timeframe, in percent, `None` when the vault backend couldn't
+    /// provide its APR series
+    pub apr_pct: Option<f64>,
+    /// Over the timeframe, `None` when the vault backend couldn't provide its KPIs
+    pub max_drawdown_pct: Option<f64>,
+    pub sharpe: Option<f64>,
+    /// Change of the share price over the timeframe, in percent
+    pub return_pct: Option<f64>,
+    /// Share price from the vault backend, rebased to 100 at the start of the timeframe. Empty
+    /// when the backend can't provide it.
+    pub share_price_series: Vec<TimeseriesPoint>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct VaultCompareResponse {
+    pub timeframe: Timeframe,
+    pub vaults: Vec<VaultComparison>,
+    /// Cursor of the next page of the leaderboard, `None` when comparing given vaults
+    pub next_cursor: Option<String>,
+}
@@ -0,0 +1,100 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use zerod_types::events::TransactionEventKind;
+
+/// Webhook subscription to create. Events are only sent when they match every filter set.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct CreateWebhookSubscriptionRequest {
+    /// HTTPS endpoint receiving the events
+    pub url: String,
+    pub event_types: Vec<TransactionEventKind>,
+    pub description: Option<String>,
+    pub vault_id: Option<String>,
+    pub partner_id: Option<String>,
+    pub user_address: Option<String>,
+    /// Signing secret, generated when omitted
+    pub secret: Option<String>,
+}
+
+/// Fields of a webhook subscription to update. Filters can't be changed.
+#[derive(Debug, Clone, Deserialize, ToSchema)]
+pub struct UpdateWebhookSubscriptionRequest {
+    pub url: Option<String>,
+    pub event_types: Option<Vec<TransactionEventKind>>,
+    pub description: Option<String>,
+    pub active: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookSubscription {
+    pub id: i32,
+    pub url: String,
+    pub description: Option<String>,
+    pub event_types: Vec<String>,
+    pub vault_id: Option<String>,
+    pub partner_id: Option<String>,
+    pub user_address: Option<String>,
+    pub active: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+/// Created subscription, the only response including its signing secret
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct CreatedWebhookSubscription {
+    #[serde(flatten)]
+    pub subscription: WebhookSubscription,
+    pub secret: String,
+}
+
+#[derive(Debug, Clone, Serialize, ToSchema)]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub subscription_id: i32,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    /// `pending`, `delivered` or `dead_letter`
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl From<zerod_db::models::WebhookSubscription> for WebhookSubscription {
+    fn from(subscription: zerod_db::models::WebhookSubscription) -> Self {
+        Self {
+            id: subscription.id,
+            url: subscription.url,
+            description: subscription.description,
+            event_types: subscription.event_types,
+            vault_id: subscription.vault_id,
+            partner_id: subscription.partner_id,
+            user_address: subscription.user_address,
+            active: subscription.active,
+            created_at: subscription.created_at,
+            updated_at: subscription.updated_at,
+        }
+    }
+}
+
+impl From<zerod_db::models::WebhookDelivery> for WebhookDelivery {
+    fn from(delivery: zerod_db::models::WebhookDelivery) -> Self {
+        Self {
+            id: delivery.id,
+            subscription_id: delivery.subscription_id,
+            event_type: delivery.event_type,
+            payload: delivery.payload,
+            status: delivery.status,
+            attempts: delivery.attempts,
+            next_attempt_at: delivery.next_attempt_at,
+            last_response_status: delivery.last_response_status,
+            last_error: delivery.last_error,
+            delivered_at: delivery.delivered_at,
+            created_at: delivery.created_at,
+        }
+    }
+}
@@ -0,0 +1,12 @@
+pub mod partners;
+pub mod webhooks;
+
+pub use partners::{
+    create_partner, generate_payout_batches, get_partner, get_payout_batch, list_partner_accruals,
+    list_partners, list_payout_batches, mark_payout_batch_paid, update_partner,
+};
+pub use webhooks::{
+    create_webhook_subscription, delete_webhook_subscription, get_webhook_subscription,
+    list_webhook_deliveries, list_webhook_subscriptions, retry_webhook_delivery,
+    update_webhook_subscription,
+};
@@ -0,0 +1,456 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::{DateTime, Days, Utc};
+use serde::Deserialize;
+use zerod_db::{
+    DatabaseError, ZerodPool,
+    models::{
+        NewPartner, Partner, PartnerPayoutBatch, PartnerRewardAccrual, PartnerUpdate,
+        PayoutBatchStatus,
+    },
+};
+
+use crate::{
+    AppState,
+    dto::{
+        self, ApiResponse, CreatePartnerRequest, GeneratePayoutBatchesRequest,
+        MarkPayoutBatchPaidRequest, PartnerPayoutBatchDetails, UpdatePartnerRequest,
+    },
+    errors::{ApiError, DatabaseErrorExt},
+    helpers::normalize_address,
+};
+
+const DEFAULT_LIST_LIMIT: i64 = 50;
+const MAX_LIST_LIMIT: i64 = 500;
+const MAX_REWARD_SHARE_BPS: i32 = 10_000;
+
+#[derive(Debug, Deserialize)]
+pub struct PartnerAccrualsQuery {
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PayoutBatchesQuery {
+    pub partner_id: Option<String>,
+    pub status: Option<PayoutBatchStatus>,
+    pub limit: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/partners",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    responses(
+        (status = 200, description = "Registered partners", body = [dto::Partner]),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_partners(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+    let partners = state
+        .pool
+        .interact_with_context("fetch partners".to_string(), Partner::find_all)
+        .await?;
+
+    let partners: Vec<dto::Partner> = partners.into_iter().map(dto::Partner::from).collect();
+
+    Ok(Json(ApiResponse::ok(partners)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/partners",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    request_body = CreatePartnerRequest,
+    responses(
+        (status = 201, description = "Partner registered", body = dto::Partner),
+        (status = 400, description = "Invalid or already registered partner"),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn create_partner(
+    State(state): State<AppState>,
+    Json(request): Json<CreatePartnerRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    validate_display_name(&request.display_name)?;
+    validate_reward_share(request.reward_share_bps)?;
+    let active_from = request.active_from.unwrap_or_else(Utc::now);
+    validate_active_window(active_from, request.active_until)?;
+
+    let partner_id = normalize_address(&request.id);
+    let new_partner = NewPartner {
+        id: partner_id.clone(),
+        display_name: request.display_name,
+        payout_address: normalize_address(&request.payout_address),
+        reward_share_bps: request.reward_share_bps,
+        active_from,
+        active_until: request.active_until,
+    };
+
+    let partner = state
+        .pool
+        .interact_with_context(format!("create partner {partner_id}"), move |conn| {
+            Partner::create(&new_partner, conn)
+        })
+        .await
+        .map_err(|e| match e {
+            DatabaseError::UniqueViolation { .. } => {
+                ApiError::BadRequest(format!("Partner {partner_id} is already registered"))
+            }
+            e => e.into(),
+        })?;
+
+    Ok((
+        StatusCode::CREATED,
+        Json(ApiResponse::ok(dto::Partner::from(partner))),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/partners/{partner_id}",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    params(("partner_id" = String, Path, description = "Partner id attributed to deposits")),
+    responses(
+        (status = 200, description = "Registered partner", body = dto::Partner),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 404, description = "Partner not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_partner(
+    State(state): State<AppState>,
+    Path(partner_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let partner = find_partner(&state, normalize_address(&partner_id)).await?;
+    Ok(Json(ApiResponse::ok(dto::Partner::from(partner))))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/admin/partners/{partner_id}",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    params(("partner_id" = String, Path, description = "Partner id attributed to deposits")),
+    request_body = UpdatePartnerRequest,
+    responses(
+        (status = 200, description = "Partner updated", body = dto::Partner),
+        (status = 400, description = "Invalid update"),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 404, description = "Partner not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn update_partner(
+    State(state): State<AppState>,
+    Path(partner_id): Path<String>,
+    Json(request): Json<UpdatePartnerRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if let Some(display_name) = &request.display_name {
+        validate_display_name(display_name)?;
+    }
+    if let Some(reward_share_bps) = request.reward_share_bps {
+        validate_reward_share(reward_share_bps)?;
+    }
+
+    let partner = find_partner(&state, normalize_address(&partner_id)).await?;
+    let active_until = if request.clear_active_until {
+        Some(None)
+    } else {
+        request.active_until.map(Some)
+    };
+    validate_active_window(
+        request.active_from.unwrap_or(partner.active_from),
+        active_until.unwrap_or(partner.active_until),
+    )?;
+
+    let updates = PartnerUpdate {
+        display_name: request.display_name,
+        payout_address: request.payout_address.as_deref().map(normalize_address),
+        reward_share_bps: request.reward_share_bps,
+        active_from: request.active_from,
+        active_until,
+        updated_at: Some(Utc::now()),
+    };
+
+    let partner = state
+        .pool
+        .interact_with_context(format!("update partner {}", partner.id), move |conn| {
+            partner.update(&updates, conn)
+        })
+        .await?;
+
+    Ok(Json(ApiResponse::ok(dto::Partner::from(partner))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/partners/{partner_id}/accruals",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    params(
+        ("partner_id" = String, Path, description = "Partner id attributed to deposits"),
+        ("limit" = Option<i64>, Query, description = "Number of accruals to return (default 50, max 500)")
+    ),
+    responses(
+        (status = 200, description = "Most recent reward accruals of the partner", body = [dto::PartnerRewardAccrual]),
+        (status = 400, description = "Invalid limit"),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 404, description = "Partner not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_partner_accruals(
+    State(state): State<AppState>,
+    Path(partner_id): Path<String>,
+    Query(query): Query<PartnerAccrualsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let limit = validate_limit(query.limit)?;
+    let partner = find_partner(&state, normalize_address(&partner_id)).await?;
+
+    let accruals = state
+        .pool
+        .interact_with_context(
+            format!("fetch reward accruals of partner {}", partner.id),
+            move |conn| PartnerRewardAccrual::find_by_partner(&partner.id, limit, conn),
+        )
+        .await?;
+
+    let accruals: Vec<dto::PartnerRewardAccrual> = accruals
+        .into_iter()
+        .map(dto::PartnerRewardAccrual::from)
+        .collect();
+
+    Ok(Json(ApiResponse::ok(accruals)))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/payouts",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    params(
+        ("partner_id" = Option<String>, Query, description = "Filter by partner"),
+        ("status" = Option<PayoutBatchStatus>, Query, description = "Filter by batch status"),
+        ("limit" = Option<i64>, Query, description = "Number of batches to return (default 50, max 500)")
+    ),
+    responses(
+        (status = 200, description = "Most recent payout batches", body = [dto::PartnerPayoutBatch]),
+        (status = 400, description = "Invalid limit"),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_payout_batches(
+    State(state): State<AppState>,
+    Query(query): Query<PayoutBatchesQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let limit = validate_limit(query.limit)?;
+    let partner_id = query.partner_id.as_deref().map(normalize_address);
+
+    let batches = state
+        .pool
+        .interact_with_context("fetch payout batches".to_string(), move |conn| {
+            PartnerPayoutBatch::find_recent(
+                partner_id.as_deref(),
+                query.status.as_ref().map(PayoutBatchStatus::as_str),
+                limit,
+                conn,
+            )
+        })
+        .await?;
+
+    let batches: Vec<dto::PartnerPayoutBatch> = batches
+        .into_iter()
+        .map(dto::PartnerPayoutBatch::from)
+        .collect();
+
+    Ok(Json(ApiResponse::ok(batches)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/payouts",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    request_body = GeneratePayoutBatchesRequest,
+    responses(
+        (status = 201, description = "One payout batch per partner with rewards to pay out", body = [dto::PartnerPayoutBatch]),
+        (status = 400, description = "Accruals of today can't be paid out yet"),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn generate_payout_batches(
+    State(state): State<AppState>,
+    Json(request): Json<GeneratePayoutBatchesRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    let yesterday = Utc::now()
+        .date_naive()
+        .checked_sub_days(Days::new(1))
+        .ok_or(ApiError::InternalServerError)?;
+    let until = request.until.unwrap_or(yesterday);
+    if until > yesterday {
+        return Err(ApiError::BadRequest(format!(
+            "until must be {yesterday} or earlier"
+        )));
+    }
+
+    let batches = state
+        .pool
+        .interact_with_context(
+            format!("generate payout batches until {until}"),
+            move |conn| PartnerPayoutBatch::generate(until, conn),
+        )
+        .await?;
+
+    let batches: Vec<dto::PartnerPayoutBatch> = batches
+        .into_iter()
+        .map(dto::PartnerPayoutBatch::from)
+        .collect();
+
+    Ok((StatusCode::CREATED, Json(ApiResponse::ok(batches))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/payouts/{batch_id}",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    params(("batch_id" = i32, Path, description = "Payout batch id")),
+    responses(
+        (status = 200, description = "Payout batch and its accruals", body = PartnerPayoutBatchDetails),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 404, description = "Payout batch not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_payout_batch(
+    State(state): State<AppState>,
+    Path(batch_id): Path<i32>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (batch, accruals) = state
+        .pool
+        .interact_with_context(format!("find payout batch {batch_id}"), move |conn| {
+            let batch = PartnerPayoutBatch::find_by_id(batch_id, conn)?;
+            let accruals = PartnerRewardAccrual::find_by_batch(batch_id, conn)?;
+            Ok::<_, diesel::result::Error>((batch, accruals))
+        })
+        .await
+        .map_err(|e| e.or_not_found(format!("Payout batch {batch_id} not found")))?;
+
+    Ok(Json(ApiResponse::ok(PartnerPayoutBatchDetails {
+        batch: batch.into(),
+        accruals: accruals
+            .into_iter()
+            .map(dto::PartnerRewardAccrual::from)
+            .collect(),
+    })))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/payouts/{batch_id}/paid",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    params(("batch_id" = i32, Path, description = "Payout batch id")),
+    request_body = MarkPayoutBatchPaidRequest,
+    responses(
+        (status = 200, description = "Payout batch marked as paid", body = dto::PartnerPayoutBatch),
+        (status = 400, description = "Payout batch already paid"),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 404, description = "Payout batch not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn mark_payout_batch_paid(
+    State(state): State<AppState>,
+    Path(batch_id): Path<i32>,
+    Json(request): Json<MarkPayoutBatchPaidRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if request.tx_hash.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "tx_hash must not be empty".to_string(),
+        ));
+    }
+    let tx_hash = normalize_address(&request.tx_hash);
+
+    let paid = state
+        .pool
+        .interact_with_context(
+            format!("mark payout batch {batch_id} as paid"),
+            move |conn| {
+                PartnerPayoutBatch::find_by_id(batch_id, conn)?;
+                PartnerPayoutBatch::mark_paid(batch_id, &tx_hash, conn)
+            },
+        )
+        .await
+        .map_err(|e| e.or_not_found(format!("Payout batch {batch_id} not found")))?;
+
+    let Some(batch) = paid else {
+        return Err(ApiError::BadRequest(format!(
+            "Payout batch {batch_id} was already paid"
+        )));
+    };
+
+    Ok(Json(ApiResponse::ok(dto::PartnerPayoutBatch::from(batch))))
+}
+
+async fn find_partner(state: &AppState, partner_id: String) -> Result<Partner, ApiError> {
+    let partner_id_clone = partner_id.clone();
+    state
+        .pool
+        .interact_with_context(format!("find partner {partner_id}"), move |conn| {
+            Partner::find_by_id(&partner_id_clone, conn)
+        })
+        .await
+        .map_err(|e| e.or_not_found(format!("Partner {partner_id} not found")))
+}
+
+fn validate_limit(limit: Option<i64>) -> Result<i64, ApiError> {
+    let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT);
+    if !(1..=MAX_LIST_LIMIT).contains(&limit) {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {MAX_LIST_LIMIT}"
+        )));
+    }
+    Ok(limit)
+}
+
+fn validate_display_name(display_name: &str) -> Result<(), ApiError> {
+    if display_name.trim().is_empty() {
+        return Err(ApiError::BadRequest(
+            "display_name must not be empty".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn validate_reward_share(reward_share_bps: i32) -> Result<(), ApiError> {
+    if !(0..=MAX_REWARD_SHARE_BPS).contains(&reward_share_bps) {
+        return Err(ApiError::BadRequest(format!(
+            "reward_share_bps must be between 0 and {MAX_REWARD_SHARE_BPS}"
+        )));
+    }
+    Ok(())
+}
+
+fn validate_active_window(
+    active_from: DateTime<Utc>,
+    active_until: Option<DateTime<Utc>>,
+) -> Result<(), ApiError> {
+    if active_until.is_some_and(|until| until <= active_from) {
+        return Err(ApiError::BadRequest(
+            "active_until must be after active_from".to_string(),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,372 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    http::StatusCode,
+    response::IntoResponse,
+};
+use chrono::Utc;
+use serde::Deserialize;
+use zerod_db::{
+    ZerodPool,
+    models::{
+        NewWebhookSubscription, Vault, WebhookDelivery, WebhookDeliveryStatus, WebhookSubscription,
+        WebhookSubscriptionUpdate,
+    },
+};
+use zerod_types::events::TransactionEventKind;
+use zerod_webhooks::signature::generate_secret;
+
+use crate::{
+    AppState,
+    dto::{
+        self, ApiResponse, CreateWebhookSubscriptionRequest, CreatedWebhookSubscription,
+        UpdateWebhookSubscriptionRequest,
+    },
+    errors::{ApiError, DatabaseErrorExt},
+    helpers::normalize_address,
+};
+
+const DEFAULT_DELIVERIES_LIMIT: i64 = 50;
+const MAX_DELIVERIES_LIMIT: i64 = 500;
+const MIN_SECRET_LENGTH: usize = 16;
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveriesQuery {
+    pub status: Option<WebhookDeliveryStatus>,
+    pub limit: Option<i64>,
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/webhooks",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    responses(
+        (status = 200, description = "Webhook subscriptions", body = [dto::WebhookSubscription]),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_webhook_subscriptions(
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiError> {
+    let subscriptions = state
+        .pool
+        .interact_with_context(
+            "fetch webhook subscriptions".to_string(),
+            WebhookSubscription::find_all,
+        )
+        .await?;
+
+    let subscriptions: Vec<dto::WebhookSubscription> = subscriptions
+        .into_iter()
+        .map(dto::WebhookSubscription::from)
+        .collect();
+
+    Ok(Json(ApiResponse::ok(subscriptions)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/webhooks",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    request_body = CreateWebhookSubscriptionRequest,
+    responses(
+        (status = 201, description = "Webhook subscription created", body = CreatedWebhookSubscription),
+        (status = 400, description = "Invalid subscription"),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 404, description = "Vault not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn create_webhook_subscription(
+    State(state): State<AppState>,
+    Json(request): Json<CreateWebhookSubscriptionRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    validate_url(&request.url)?;
+    let event_types = event_types(&request.event_types)?;
+    let secret = match request.secret {
+        Some(secret) if secret.len() < MIN_SECRET_LENGTH => {
+            return Err(ApiError::BadRequest(format!(
+                "secret must be at least {MIN_SECRET_LENGTH} characters long"
+            )));
+        }
+        Some(secret) => secret,
+        None => generate_secret(),
+    };
+
+    if let Some(vault_id) = request.vault_id.clone() {
+        let vault_id_clone = vault_id.clone();
+        state
+            .pool
+            .interact_with_context(format!("find vault by id: {vault_id}"), move |conn| {
+                Vault::find_by_id(&vault_id_clone, conn)
+            })
+            .await
+            .map_err(|e| e.or_not_found(format!("Vault {vault_id} not found")))?;
+    }
+
+    let new_subscription = NewWebhookSubscription {
+        url: request.url,
+        secret,
+        description: request.description,
+        event_types,
+        vault_id: request.vault_id,
+        partner_id: request.partner_id.as_deref().map(normalize_address),
+        user_address: request.user_address.as_deref().map(normalize_address),
+    };
+
+    let subscription = state
+        .pool
+        .interact_with_context("create webhook subscription".to_string(), move |conn| {
+            WebhookSubscription::create(&new_subscription, conn)
+        })
+        .await?;
+
+    let created = CreatedWebhookSubscription {
+        secret: subscription.secret.clone(),
+        subscription: subscription.into(),
+    };
+
+    Ok((StatusCode::CREATED, Json(ApiResponse::ok(created))))
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/webhooks/{id}",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    params(("id" = i32, Path, description = "Webhook subscription id")),
+    responses(
+        (status = 200, description = "Webhook subscription", body = dto::WebhookSubscription),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 404, description = "Webhook subscription not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_webhook_subscription(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, ApiError> {
+    let subscription = find_subscription(&state, id).await?;
+    Ok(Json(ApiResponse::ok(dto::WebhookSubscription::from(
+        subscription,
+    ))))
+}
+
+#[utoipa::path(
+    patch,
+    path = "/admin/webhooks/{id}",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    params(("id" = i32, Path, description = "Webhook subscription id")),
+    request_body = UpdateWebhookSubscriptionRequest,
+    responses(
+        (status = 200, description = "Webhook subscription updated", body = dto::WebhookSubscription),
+        (status = 400, description = "Invalid update"),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 404, description = "Webhook subscription not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn update_webhook_subscription(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Json(request): Json<UpdateWebhookSubscriptionRequest>,
+) -> Result<impl IntoResponse, ApiError> {
+    if let Some(url) = &request.url {
+        validate_url(url)?;
+    }
+    let event_types = request
+        .event_types
+        .as_deref()
+        .map(event_types)
+        .transpose()?;
+
+    let subscription = find_subscription(&state, id).await?;
+    let updates = WebhookSubscriptionUpdate {
+        url: request.url,
+        description: request.description,
+        event_types,
+        active: request.active,
+        updated_at: Some(Utc::now()),
+    };
+
+    let subscription = state
+        .pool
+        .interact_with_context(format!("update webhook subscription {id}"), move |conn| {
+            subscription.update(&updates, conn)
+        })
+        .await?;
+
+    Ok(Json(ApiResponse::ok(dto::WebhookSubscription::from(
+        subscription,
+    ))))
+}
+
+#[utoipa::path(
+    delete,
+    path = "/admin/webhooks/{id}",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    params(("id" = i32, Path, description = "Webhook subscription id")),
+    responses(
+        (status = 204, description = "Webhook subscription and its deliveries deleted"),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 404, description = "Webhook subscription not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn delete_webhook_subscription(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+) -> Result<impl IntoResponse, ApiError> {
+    let deleted = state
+        .pool
+        .interact_with_context(format!("delete webhook subscription {id}"), move |conn| {
+            WebhookSubscription::delete(id, conn)
+        })
+        .await?;
+
+    if deleted == 0 {
+        return Err(ApiError::NotFound(format!(
+            "Webhook subscription {id} not found"
+        )));
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[utoipa::path(
+    get,
+    path = "/admin/webhooks/{id}/deliveries",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    params(
+        ("id" = i32, Path, description = "Webhook subscription id"),
+        ("status" = Option<WebhookDeliveryStatus>, Query, description = "Filter by delivery status"),
+        ("limit" = Option<i64>, Query, description = "Number of deliveries to return (default 50, max 500)")
+    ),
+    responses(
+        (status = 200, description = "Most recent deliveries of the subscription", body = [dto::WebhookDelivery]),
+        (status = 400, description = "Invalid limit"),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 404, description = "Webhook subscription not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    Path(id): Path<i32>,
+    Query(query): Query<WebhookDeliveriesQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_DELIVERIES_LIMIT);
+    if !(1..=MAX_DELIVERIES_LIMIT).contains(&limit) {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {MAX_DELIVERIES_LIMIT}"
+        )));
+    }
+
+    find_subscription(&state, id).await?;
+
+    let deliveries = state
+        .pool
+        .interact_with_context(
+            format!("fetch deliveries of webhook subscription {id}"),
+            move |conn| {
+                WebhookDelivery::find_by_subscription(
+                    id,
+                    query.status.as_ref().map(WebhookDeliveryStatus::as_str),
+                    limit,
+                    conn,
+                )
+            },
+        )
+        .await?;
+
+    let deliveries: Vec<dto::WebhookDelivery> = deliveries
+        .into_iter()
+        .map(dto::WebhookDelivery::from)
+        .collect();
+
+    Ok(Json(ApiResponse::ok(deliveries)))
+}
+
+#[utoipa::path(
+    post,
+    path = "/admin/webhooks/deliveries/{delivery_id}/retry",
+    tag = "Admin",
+    security(("admin_api_key" = [])),
+    params(("delivery_id" = i32, Path, description = "Webhook delivery id")),
+    responses(
+        (status = 200, description = "Delivery queued again", body = dto::WebhookDelivery),
+        (status = 400, description = "Delivery already delivered"),
+        (status = 401, description = "Missing or invalid admin API key"),
+        (status = 404, description = "Webhook delivery not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn retry_webhook_delivery(
+    State(state): State<AppState>,
+    Path(delivery_id): Path<i32>,
+) -> Result<impl IntoResponse, ApiError> {
+    let delivery = state
+        .pool
+        .interact_with_context(
+            format!("find webhook delivery {delivery_id}"),
+            move |conn| WebhookDelivery::find_by_id(delivery_id, conn),
+        )
+        .await
+        .map_err(|e| e.or_not_found(format!("Webhook delivery {delivery_id} not found")))?;
+
+    if delivery.status == WebhookDeliveryStatus::Delivered.as_str() {
+        return Err(ApiError::BadRequest(format!(
+            "Webhook delivery {delivery_id} was already delivered"
+        )));
+    }
+
+    let delivery = state
+        .pool
+        .interact_with_context(
+            format!("requeue webhook delivery {delivery_id}"),
+            move |conn| WebhookDelivery::requeue(delivery_id, conn),
+        )
+        .await?;
+
+    Ok(Json(ApiResponse::ok(dto::WebhookDelivery::from(delivery))))
+}
+
+async fn find_subscription(state: &AppState, id: i32) -> Result<WebhookSubscription, ApiError> {
+    state
+        .pool
+        .interact_with_context(format!("find webhook subscription {id}"), move |conn| {
+            WebhookSubscription::find_by_id(id, conn)
+        })
+        .await
+        .map_err(|e| e.or_not_found(format!("Webhook subscription {id} not found")))
+}
+
+fn validate_url(url: &str) -> Result<(), ApiError> {
+    let parsed = reqwest::Url::parse(url)
+        .map_err(|e| ApiError::BadRequest(format!("Invalid webhook url: {e}")))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host().is_none() {
+        return Err(ApiError::BadRequest(
+            "Webhook url must be an http(s) url".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn event_types(kinds: &[TransactionEventKind]) -> Result<Vec<String>, ApiError> {
+    if kinds.is_empty() {
+        return Err(ApiError::BadRequest(
+            "event_types must not be empty".to_string(),
+        ));
+    }
+
+    let mut event_types: Vec<String> = kinds.iter().map(|k| k.as_str().to_string()).collect();
+    event_types.sort_unstable();
+    event_types.dedup();
+    Ok(event_types)
+}
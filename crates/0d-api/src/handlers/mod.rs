@@ -1,14 +1,34 @@
+pub mod admin;
+pub mod partners;
+pub mod stream;
 pub mod users;
 pub mod vaults;
 
+pub use admin::{
+    create_partner, create_webhook_subscription, delete_webhook_subscription,
+    generate_payout_batches, get_partner, get_payout_batch, get_webhook_subscription,
+    list_partner_accruals, list_partners, list_payout_batches, list_webhook_deliveries,
+    list_webhook_subscriptions, mark_payout_batch_paid, retry_webhook_delivery, update_partner,
+    update_webhook_subscription,
+};
+
+pub use partners::{
+    get_partner_flows, get_partner_leaderboard, get_partner_referred_users, get_partner_summary,
+};
+
+pub use stream::{stream_events, stream_user_events, stream_vault_events};
+
 pub use users::{
-    get_historical_user_performance, get_user_kpis, get_user_pending_redeems,
-    get_user_position_summary, get_user_profile, get_user_transaction_history,
+    get_historical_user_performance, get_user_kpis, get_user_pending_redeems, get_user_portfolio,
+    get_user_position_summary, get_user_profile, get_user_tax_report, get_user_transaction_history,
+    get_user_transactions,
 };
 
 pub use vaults::{
-    get_vault, get_vault_apr_series, get_vault_apr_summary, get_vault_caps, get_vault_composition,
-    get_vault_composition_series, get_vault_info, get_vault_kpis, get_vault_liquidity,
-    get_vault_nav_latest, get_vault_share_price_series, get_vault_slippage_curve, get_vault_stats,
-    get_vault_timeseries, list_vaults, simulate_vault_liquidity,
+    compare_vaults, get_vault, get_vault_apr_series, get_vault_apr_summary, get_vault_caps,
+    get_vault_composition, get_vault_composition_series, get_vault_flows,
+    get_vault_holder_concentration, get_vault_holders, get_vault_info, get_vault_kpis,
+    get_vault_liquidity, get_vault_nav_latest, get_vault_redeems, get_vault_share_price_series,
+    get_vault_slippage_curve, get_vault_stats, get_vault_timeseries, list_vaults,
+    simulate_vault_liquidity,
 };
@@ -0,0 +1,218 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    str::FromStr,
+};
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Duration, Utc};
+use rust_decimal::Decimal;
+use zerod_db::{
+    ZerodPool,
+    models::{ReferredPosition, UserTransaction, Vault},
+};
+use zerod_quoting::currencies::CURRENCIES_PRICES;
+use zerod_types::Currency;
+
+use crate::{
+    AppState,
+    dto::{ApiResponse, PartnerFlowPoint, PartnerFlowsQuery, PartnerFlowsResponse},
+    errors::ApiError,
+    helpers::normalize_address,
+};
+
+/// Deposits and redemptions of a bucket, in the underlying currency of a vault
+#[derive(Debug, Clone, Copy, Default)]
+struct BucketFlows {
+    deposits: Decimal,
+    redemptions: Decimal,
+}
+
+#[utoipa::path(
+    get,
+    path = "/partners/{partner_id}/flows",
+    tag = "Partners",
+    params(
+        ("partner_id" = String, Path, description = "Partner id attributed to deposits"),
+        ("timeframe" = Option<String>, Query, description = "Time period (7d, 30d, 1y, all)"),
+        ("interval" = Option<String>, Query, description = "Bucket width (1d, 1w)")
+    ),
+    responses(
+        (status = 200, description = "Deposits through the partner and redemptions of the referred positions over time", body = PartnerFlowsResponse),
+        (status = 404, description = "No deposit attributed to the partner"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_partner_flows(
+    State(state): State<AppState>,
+    Path(partner_id): Path<String>,
+    Query(query): Query<PartnerFlowsQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let partner_id = normalize_address(&partner_id);
+    let partner = partner_id.clone();
+    let (deposits, positions) = state
+        .pool
+        .interact_with_context(
+            format!("fetch referrals of partner {partner_id}"),
+            move |conn| {
+                let deposits = UserTransaction::find_partner_deposits(Some(&partner), conn)?;
+                let positions = ReferredPosition::find_by_partner(Some(&partner), conn)?;
+                Ok::<_, diesel::result::Error>((deposits, positions))
+            },
+        )
+        .await?;
+
+    let referred_at: HashMap<(String, String), DateTime<Utc>> = positions
+        .into_iter()
+        .map(|position| {
+            (
+                (position.user_address, position.vault_id),
+                position.referred_at,
+            )
+        })
+        .collect();
+    // Deposits through the partner into positions first referred by another one are not
+    // attributed to it
+    let deposits: Vec<UserTransaction> = deposits
+        .into_iter()
+        .filter(|deposit| {
+            referred_at.contains_key(&(deposit.user_address.clone(), deposit.vault_id.clone()))
+        })
+        .collect();
+    let Some(first_deposit) = deposits.first() else {
+        return Err(ApiError::NotFound(format!(
+            "No deposit attributed to partner {partner_id}"
+        )));
+    };
+
+    let now = Utc::now();
+    let interval = query.interval;
+    let since = query
+        .timeframe
+        .to_days()
+        .map_or(first_deposit.block_timestamp, |days| {
+            now - Duration::days(days)
+        });
+
+    let mut users: Vec<String> = referred_at.keys().map(|(user, _)| user.clone()).collect();
+    users.sort_unstable();
+    users.dedup();
+    let (withdrawals, vaults) = tokio::join!(
+        state.pool.interact_with_context(
+            format!("fetch withdrawals of the users referred by {partner_id}"),
+            move |conn| UserTransaction::find_withdrawals_by_users(&users, conn),
+        ),
+        state
+            .pool
+            .interact_with_context("fetch all vaults".to_string(), Vault::find_all),
+    );
+    let vaults: HashMap<String, Vault> = vaults?
+        .into_iter()
+        .map(|vault| (vault.id.clone(), vault))
+        .collect();
+
+    // Redemptions of the referred positions, once referred
+    let redemptions = withdrawals?.into_iter().filter(|tx| {
+        referred_at
+            .get(&(tx.user_address.clone(), tx.vault_id.clone()))
+            .is_some_and(|referred_at| tx.block_timestamp >= *referred_at)
+    });
+
+    let mut flows: HashMap<String, BTreeMap<DateTime<Utc>, BucketFlows>> = HashMap::new();
+    for deposit in &deposits {
+        if deposit.block_timestamp >= since {
+            let bucket = interval.bucket_start(deposit.block_timestamp);
+            flows
+                .entry(deposit.vault_id.clone())
+                .or_default()
+                .entry(bucket)
+                .or_default()
+                .deposits += deposit.amount;
+        }
+    }
+    for redemption in redemptions {
+        if redemption.block_timestamp >= since {
+            let bucket = interval.bucket_start(redemption.block_timestamp);
+            flows
+                .entry(redemption.vault_id.clone())
+                .or_default()
+                .entry(bucket)
+                .or_default()
+                .redemptions += redemption.amount;
+        }
+    }
+
+    // Amounts are converted to USD at the price of the start of their bucket
+    let mut usd_flows: BTreeMap<DateTime<Utc>, (Decimal, Decimal)> = BTreeMap::new();
+    for (vault_id, buckets) in flows {
+        let Some(currency) = vaults
+            .get(&vault_id)
+            .and_then(|vault| Currency::from_str(&vault.base_asset).ok())
+        else {
+            tracing::warn!("No USD price for the underlying currency of vault {vault_id}");
+            continue;
+        };
+
+        let (deposits, redemptions): (Vec<_>, Vec<_>) = buckets
+            .iter()
+            .map(|(t, f)| ((*t, f.deposits), (*t, f.redemptions)))
+            .unzip();
+        let (deposits_usd, redemptions_usd) = tokio::try_join!(
+            CURRENCIES_PRICES.convert_series(&deposits, currency, Currency::USD),
+            CURRENCIES_PRICES.convert_series(&redemptions, currency, Currency::USD),
+        )
+        .map_err(|e| {
+            tracing::error!("Failed to convert the flows of vault {vault_id} to USD: {e}");
+            ApiError::InternalServerError
+        })?;
+
+        for ((t, _), (deposit_usd, redemption_usd)) in buckets
+            .iter()
+            .zip(deposits_usd.into_iter().zip(redemptions_usd))
+        {
+            let totals = usd_flows.entry(*t).or_default();
+            totals.0 += deposit_usd;
+            totals.1 += redemption_usd;
+        }
+    }
+
+    // A user is new in the bucket of their first referral, whatever the vault
+    let mut first_referrals: HashMap<&str, DateTime<Utc>> = HashMap::new();
+    for ((user, _), referred_at) in &referred_at {
+        first_referrals
+            .entry(user.as_str())
+            .and_modify(|at| *at = (*at).min(*referred_at))
+            .or_insert(*referred_at);
+    }
+    let mut new_users: HashMap<DateTime<Utc>, usize> = HashMap::new();
+    for referred_at in first_referrals.into_values() {
+        if referred_at >= since {
+            *new_users
+                .entry(interval.bucket_start(referred_at))
+                .or_default() += 1;
+        }
+    }
+
+    let mut points = Vec::new();
+    let mut t = interval.bucket_start(since);
+    while t <= now {
+        let (deposits_usd, redemptions_usd) = usd_flows.get(&t).copied().unwrap_or_default();
+        points.push(PartnerFlowPoint {
+            t,
+            deposits_usd: deposits_usd.to_string(),
+            redemptions_usd: redemptions_usd.to_string(),
+            net_flow_usd: (deposits_usd - redemptions_usd).to_string(),
+            new_users: new_users.get(&t).copied().unwrap_or_default(),
+        });
+        t += interval.duration();
+    }
+
+    Ok(Json(ApiResponse::ok(PartnerFlowsResponse {
+        partner_id,
+        interval,
+        points,
+    })))
+}
@@ -0,0 +1,111 @@
+use std::collections::{BTreeMap, HashMap};
+
+use axum::{
+    Json,
+    extract::{Query, State},
+    response::IntoResponse,
+};
+use rust_decimal::Decimal;
+
+use crate::{
+    AppState,
+    dto::{
+        ApiResponse, PartnerLeaderboardEntry, PartnerLeaderboardQuery, PartnerLeaderboardResponse,
+        PartnerRanking,
+    },
+    errors::ApiError,
+    handlers::partners::referrals::{
+        VaultReferralStats, fetch_vault_pricing, fetch_vault_referrals,
+    },
+};
+
+const DEFAULT_LEADERBOARD_SIZE: usize = 20;
+const MAX_LEADERBOARD_SIZE: usize = 100;
+
+#[utoipa::path(
+    get,
+    path = "/partners/leaderboard",
+    tag = "Partners",
+    params(
+        ("sort_by" = Option<PartnerRanking>, Query, description = "Ranking criteria (defaults to tvl)"),
+        ("limit" = Option<usize>, Query, description = "Number of partners to return (default 20, max 100)")
+    ),
+    responses(
+        (status = 200, description = "Partners ranked by referral activity", body = PartnerLeaderboardResponse),
+        (status = 400, description = "Invalid limit"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_partner_leaderboard(
+    State(state): State<AppState>,
+    Query(query): Query<PartnerLeaderboardQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let limit = query.limit.unwrap_or(DEFAULT_LEADERBOARD_SIZE);
+    if !(1..=MAX_LEADERBOARD_SIZE).contains(&limit) {
+        return Err(ApiError::BadRequest(format!(
+            "limit must be between 1 and {MAX_LEADERBOARD_SIZE}"
+        )));
+    }
+
+    let (vault_referrals, referred_users) = fetch_vault_referrals(&state, None).await?;
+    let pricing = fetch_vault_pricing(
+        &state,
+        vault_referrals.iter().map(|r| r.vault_id.clone()).collect(),
+    )
+    .await?;
+
+    // Totals per partner, in USD: (deposit volume, referred TVL)
+    let mut totals: BTreeMap<String, (Decimal, Decimal)> = BTreeMap::new();
+    for referrals in vault_referrals {
+        let partner_totals = totals.entry(referrals.partner_id.clone()).or_default();
+        let pricing = pricing.get(&referrals.vault_id);
+        let vault_stats = VaultReferralStats::new(referrals, pricing);
+        partner_totals.0 += vault_stats.referrals.deposit_volume_usd;
+        partner_totals.1 += vault_stats.tvl_usd;
+    }
+    let referred_users: HashMap<String, i64> = referred_users
+        .into_iter()
+        .map(|users| (users.partner_id, users.referred_users))
+        .collect();
+
+    let mut entries: Vec<(Decimal, Decimal, PartnerLeaderboardEntry)> = totals
+        .into_iter()
+        .map(|(partner_id, (deposit_volume_usd, referred_tvl_usd))| {
+            (
+                deposit_volume_usd,
+                referred_tvl_usd,
+                PartnerLeaderboardEntry {
+                    rank: 0,
+                    referred_users: referred_users.get(&partner_id).copied().unwrap_or_default(),
+                    partner_id,
+                    deposit_volume_usd: deposit_volume_usd.to_string(),
+                    referred_tvl_usd: referred_tvl_usd.to_string(),
+                },
+            )
+        })
+        .collect();
+
+    entries.sort_by(|(a_volume, a_tvl, a), (b_volume, b_tvl, b)| {
+        let ordering = match query.sort_by {
+            PartnerRanking::Tvl => b_tvl.cmp(a_tvl),
+            PartnerRanking::DepositVolume => b_volume.cmp(a_volume),
+            PartnerRanking::ReferredUsers => b.referred_users.cmp(&a.referred_users),
+        };
+        ordering.then_with(|| a.partner_id.cmp(&b.partner_id))
+    });
+
+    let items = entries
+        .into_iter()
+        .take(limit)
+        .enumerate()
+        .map(|(i, (_, _, entry))| PartnerLeaderboardEntry {
+            rank: i + 1,
+            ..entry
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::ok(PartnerLeaderboardResponse {
+        sort_by: query.sort_by,
+        items,
+    })))
+}
@@ -0,0 +1,10 @@
+pub mod flows;
+pub mod leaderboard;
+pub(crate) mod referrals;
+pub mod summary;
+pub mod users;
+
+pub use flows::get_partner_flows;
+pub use leaderboard::get_partner_leaderboard;
+pub use summary::get_partner_summary;
+pub use users::get_partner_referred_users;
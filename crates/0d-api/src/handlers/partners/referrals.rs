@@ -0,0 +1,175 @@
+use std::collections::{HashMap, HashSet};
+
+use chrono::Utc;
+use futures::future::join_all;
+use rust_decimal::{Decimal, dec};
+use zerod_db::{
+    ZerodPool,
+    models::{PartnerReferredUsers, PartnerVaultReferrals, Vault},
+};
+use zerod_kpi::SECONDS_PER_YEAR;
+use zerod_quoting::currencies::CURRENCIES_PRICES;
+
+use crate::{
+    AppState,
+    dto::{PartnerFees, PartnerVaultStats},
+    errors::ApiError,
+    helpers::{VaultBackendClient, call_vault_backend},
+};
+
+const BPS_DENOMINATOR: Decimal = dec!(10_000);
+
+/// Current prices used to value the referred positions of a vault
+pub(crate) struct VaultPricing {
+    pub vault: Vault,
+    /// Current share price, `None` when the vault backend could not be reached
+    pub share_price_usd: Option<Decimal>,
+    /// USD price of the underlying currency, when it is supported
+    pub asset_price_usd: Option<Decimal>,
+}
+
+/// Referral totals of a partner in one vault, valued at the current prices
+#[derive(Debug, Clone)]
+pub(crate) struct VaultReferralStats {
+    pub referrals: PartnerVaultReferrals,
+    pub tvl_usd: Decimal,
+    pub management_fees_usd: Decimal,
+    pub performance_fees_usd: Decimal,
+}
+
+impl VaultReferralStats {
+    /// Value the referrals of a vault. The management fees are charged on the current value
+    /// of the shares over the time they were held since referred, the performance fees on
+    /// the unrealized gains.
+    pub(crate) fn new(referrals: PartnerVaultReferrals, pricing: Option<&VaultPricing>) -> Self {
+        let mut stats = Self {
+            referrals,
+            tvl_usd: Decimal::ZERO,
+            management_fees_usd: Decimal::ZERO,
+            performance_fees_usd: Decimal::ZERO,
+        };
+        let Some(pricing) = pricing else {
+            return stats;
+        };
+
+        if let Some(share_price_usd) = pricing.share_price_usd {
+            let mgmt_fee_rate =
+                Decimal::from(pricing.vault.mgmt_fee_bps.unwrap_or_default()) / BPS_DENOMINATOR;
+            stats.tvl_usd = stats.referrals.share_balance * share_price_usd;
+            stats.management_fees_usd =
+                stats.referrals.share_seconds * share_price_usd * mgmt_fee_rate / SECONDS_PER_YEAR;
+        }
+        if let Some(asset_price_usd) = pricing.asset_price_usd {
+            let perf_fee_rate = Decimal::from(pricing.vault.perf_fee_bps) / BPS_DENOMINATOR;
+            stats.performance_fees_usd =
+                stats.referrals.unrealized_gain * asset_price_usd * perf_fee_rate;
+        }
+        stats
+    }
+
+    pub(crate) fn into_dto(self, pricing: &VaultPricing) -> PartnerVaultStats {
+        PartnerVaultStats {
+            vault_id: self.referrals.vault_id,
+            vault_name: pricing.vault.name.clone(),
+            underlying_currency: pricing.vault.base_asset.clone(),
+            referred_users: self.referrals.referred_users,
+            deposit_count: self.referrals.deposit_count,
+            deposit_volume: self.referrals.deposit_volume.to_string(),
+            deposit_volume_usd: self.referrals.deposit_volume_usd.to_string(),
+            share_balance: self.referrals.share_balance.to_string(),
+            tvl_usd: self.tvl_usd.to_string(),
+            fees: fees_dto(self.management_fees_usd, self.performance_fees_usd),
+        }
+    }
+}
+
+pub(crate) fn fees_dto(management_fees_usd: Decimal, performance_fees_usd: Decimal) -> PartnerFees {
+    PartnerFees {
+        management_fees_usd: management_fees_usd.round_dp(6).to_string(),
+        performance_fees_usd: performance_fees_usd.round_dp(6).to_string(),
+        total_fees_usd: (management_fees_usd + performance_fees_usd)
+            .round_dp(6)
+            .to_string(),
+    }
+}
+
+/// Fetch the per vault referral totals of a partner, or of every partner, along with the
+/// number of distinct users each partner referred
+pub(crate) async fn fetch_vault_referrals(
+    state: &AppState,
+    partner_id: Option<String>,
+) -> Result<(Vec<PartnerVaultReferrals>, Vec<PartnerReferredUsers>), ApiError> {
+    let context = partner_id.as_deref().map_or_else(
+        || "fetch partner referrals".to_string(),
+        |partner| format!("fetch referrals of partner {partner}"),
+    );
+    let referrals = state
+        .pool
+        .interact_with_context(context, move |conn| {
+            let vaults =
+                PartnerVaultReferrals::find_by_partner(partner_id.as_deref(), Utc::now(), conn)?;
+            let users = PartnerReferredUsers::find_by_partner(partner_id.as_deref(), conn)?;
+            Ok::<_, diesel::result::Error>((vaults, users))
+        })
+        .await?;
+    Ok(referrals)
+}
+
+/// Fetch the current share price of each vault from its backend, concurrently.
+///
+/// A vault whose backend fails is still returned, without share price, so that one vault
+/// can't fail every referral listing.
+pub(crate) async fn fetch_vault_pricing(
+    state: &AppState,
+    vault_ids: HashSet<String>,
+) -> Result<HashMap<String, VaultPricing>, ApiError> {
+    let vaults = state
+        .pool
+        .interact_with_context("fetch all vaults".to_string(), Vault::find_all)
+        .await?;
+
+    let fetch_futures = vaults
+        .into_iter()
+        .filter(|vault| vault_ids.contains(&vault.id))
+        .map(|vault| async move {
+            let share_price_usd = fetch_share_price_usd(&vault)
+                .await
+                .inspect_err(|e| {
+                    tracing::warn!("No share price for vault {}: {e:?}", vault.id);
+                })
+                .ok();
+            let asset_price_usd = CURRENCIES_PRICES
+                .of_ticker(&vault.base_asset)
+                .await
+                .inspect_err(|e| {
+                    tracing::warn!("No USD price for {}: {e:#}", vault.base_asset);
+                })
+                .ok();
+
+            (
+                vault.id.clone(),
+                VaultPricing {
+                    vault,
+                    share_price_usd,
+                    asset_price_usd,
+                },
+            )
+        });
+
+    Ok(join_all(fetch_futures).await.into_iter().collect())
+}
+
+async fn fetch_share_price_usd(vault: &Vault) -> Result<Decimal, ApiError> {
+    let client = VaultBackendClient::new(vault)?;
+    let info = call_vault_backend(&client, vault, "fetch vault info", |backend| async move {
+        backend.get_vault_info().await
+    })
+    .await?;
+    info.share_price_in_usd.parse::<Decimal>().map_err(|e| {
+        tracing::error!(
+            "Failed to parse share price '{}': {e}",
+            info.share_price_in_usd
+        );
+        ApiError::InternalServerError
+    })
+}
@@ -0,0 +1,88 @@
+use axum::{
+    Json,
+    extract::{Path, State},
+    response::IntoResponse,
+};
+use rust_decimal::Decimal;
+
+use crate::{
+    AppState,
+    dto::{ApiResponse, PartnerSummary},
+    errors::ApiError,
+    handlers::partners::referrals::{
+        VaultReferralStats, fees_dto, fetch_vault_pricing, fetch_vault_referrals,
+    },
+    helpers::normalize_address,
+};
+
+#[utoipa::path(
+    get,
+    path = "/partners/{partner_id}",
+    tag = "Partners",
+    params(("partner_id" = String, Path, description = "Partner id attributed to deposits")),
+    responses(
+        (status = 200, description = "Referral totals of the partner", body = PartnerSummary),
+        (status = 404, description = "No deposit attributed to the partner"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_partner_summary(
+    State(state): State<AppState>,
+    Path(partner_id): Path<String>,
+) -> Result<impl IntoResponse, ApiError> {
+    let partner_id = normalize_address(&partner_id);
+    let (vault_referrals, referred_users) =
+        fetch_vault_referrals(&state, Some(partner_id.clone())).await?;
+    let (Some(first_referral_at), Some(last_referral_at)) = (
+        vault_referrals.iter().map(|r| r.first_referral_at).min(),
+        vault_referrals.iter().map(|r| r.last_deposit_at).max(),
+    ) else {
+        return Err(ApiError::NotFound(format!(
+            "No deposit attributed to partner {partner_id}"
+        )));
+    };
+
+    let pricing = fetch_vault_pricing(
+        &state,
+        vault_referrals.iter().map(|r| r.vault_id.clone()).collect(),
+    )
+    .await?;
+    let vault_stats: Vec<VaultReferralStats> = vault_referrals
+        .into_iter()
+        .map(|referrals| {
+            let pricing = pricing.get(&referrals.vault_id);
+            VaultReferralStats::new(referrals, pricing)
+        })
+        .collect();
+
+    let deposit_count: i64 = vault_stats.iter().map(|s| s.referrals.deposit_count).sum();
+    let deposit_volume_usd: Decimal = vault_stats
+        .iter()
+        .map(|s| s.referrals.deposit_volume_usd)
+        .sum();
+    let referred_tvl_usd: Decimal = vault_stats.iter().map(|s| s.tvl_usd).sum();
+    let management_fees_usd: Decimal = vault_stats.iter().map(|s| s.management_fees_usd).sum();
+    let performance_fees_usd: Decimal = vault_stats.iter().map(|s| s.performance_fees_usd).sum();
+
+    let vaults = vault_stats
+        .into_iter()
+        .filter_map(|s| {
+            let pricing = pricing.get(&s.referrals.vault_id)?;
+            Some(s.into_dto(pricing))
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::ok(PartnerSummary {
+        partner_id,
+        referred_users: referred_users
+            .first()
+            .map_or(0, |users| users.referred_users),
+        deposit_count,
+        deposit_volume_usd: deposit_volume_usd.to_string(),
+        referred_tvl_usd: referred_tvl_usd.to_string(),
+        first_referral_at,
+        last_referral_at,
+        fees: fees_dto(management_fees_usd, performance_fees_usd),
+        vaults,
+    })))
+}
@@ -0,0 +1,109 @@
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    response::Response,
+};
+use rust_decimal::Decimal;
+use zerod_db::{
+    ZerodPool,
+    models::{UserTransaction, ValuedReferredPosition},
+};
+
+use crate::{
+    AppState,
+    dto::{ReferredUser, ReferredUsersResponse},
+    errors::ApiError,
+    handlers::partners::referrals::fetch_vault_pricing,
+    helpers::normalize_address,
+    pagination::{Page, PaginationQuery, paginated},
+};
+
+#[utoipa::path(
+    get,
+    path = "/partners/{partner_id}/users",
+    tag = "Partners",
+    params(
+        ("partner_id" = String, Path, description = "Partner id attributed to deposits"),
+        PaginationQuery
+    ),
+    responses(
+        (status = 200, description = "Users referred by the partner, by decreasing position value", body = ReferredUsersResponse),
+        (status = 400, description = "Invalid cursor"),
+        (status = 404, description = "No deposit attributed to the partner"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_partner_referred_users(
+    State(state): State<AppState>,
+    Path(partner_id): Path<String>,
+    Query(pagination): Query<PaginationQuery>,
+    OriginalUri(uri): OriginalUri,
+) -> Result<Response, ApiError> {
+    let partner_id = normalize_address(&partner_id);
+    let partner = partner_id.clone();
+    let vault_ids = state
+        .pool
+        .interact_with_context(
+            format!("fetch vaults referred by partner {partner_id}"),
+            move |conn| UserTransaction::find_partner_vault_ids(&partner, conn),
+        )
+        .await?;
+    if vault_ids.is_empty() {
+        return Err(ApiError::NotFound(format!(
+            "No deposit attributed to partner {partner_id}"
+        )));
+    }
+
+    let share_prices_usd: Vec<(String, Decimal)> =
+        fetch_vault_pricing(&state, vault_ids.into_iter().collect())
+            .await?
+            .into_iter()
+            .filter_map(|(vault_id, pricing)| {
+                pricing
+                    .share_price_usd
+                    .map(|share_price_usd| (vault_id, share_price_usd))
+            })
+            .collect();
+
+    let limit = pagination.limit();
+    let after: Option<(Decimal, String, String)> = pagination.after()?;
+    let partner = partner_id.clone();
+    let positions = state
+        .pool
+        .interact_with_context(
+            format!("fetch positions referred by partner {partner_id}"),
+            move |conn| {
+                ValuedReferredPosition::find_page_by_partner(
+                    &partner,
+                    &share_prices_usd,
+                    after,
+                    limit + 1, // Get one extra to determine if there's a next page
+                    conn,
+                )
+            },
+        )
+        .await?;
+
+    let page = Page::from_fetched(positions, limit, |valued| {
+        (
+            valued.value_usd,
+            valued.position.user_address.clone(),
+            valued.position.vault_id.clone(),
+        )
+    })
+    .map(|valued| ReferredUser {
+        user_address: valued.position.user_address,
+        vault_id: valued.position.vault_id,
+        first_referred_at: valued.position.referred_at,
+        deposit_count: valued.position.deposit_count,
+        deposit_volume: valued.position.deposit_volume.to_string(),
+        share_balance: valued.position.share_balance.to_string(),
+        value_usd: valued.value_usd.to_string(),
+    });
+    let next_cursor = page.next_cursor.clone();
+
+    Ok(paginated(
+        ReferredUsersResponse { partner_id, page },
+        &uri,
+        next_cursor.as_deref(),
+    ))
+}
@@ -0,0 +1,56 @@
+pub mod sse;
+pub mod ws;
+
+pub use sse::{stream_user_events, stream_vault_events};
+pub use ws::stream_events;
+
+use crate::helpers::normalize_address;
+
+/// Most topics a single connection can subscribe to
+pub const MAX_TOPICS_PER_CONNECTION: usize = 50;
+
+/// Validate a streaming topic and return its canonical form.
+///
+/// Supported topics are `vault:{id}:transactions`, `vault:{id}:share_price`,
+/// `vault:{id}:status`, `user:{address}:transactions` and `user:{address}:positions`.
+pub fn parse_topic(topic: &str) -> Option<String> {
+    let mut parts = topic.split(':');
+    let (Some(scope), Some(key), Some(channel), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    if key.is_empty() {
+        return None;
+    }
+
+    match (scope, channel) {
+        ("vault", "transactions" | "share_price" | "status") => {
+            Some(format!("vault:{key}:{channel}"))
+        }
+        ("user", "transactions" | "positions") => {
+            Some(format!("user:{}:{channel}", normalize_address(key)))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parses_and_normalizes_topics() {
+        assert_eq!(
+            parse_topic("vault:vault-1:share_price").as_deref(),
+            Some("vault:vault-1:share_price")
+        );
+        assert_eq!(
+            parse_topic("user:0x00ABC:positions").as_deref(),
+            Some("user:0xabc:positions")
+        );
+        assert_eq!(parse_topic("vault:vault-1:positions"), None);
+        assert_eq!(parse_topic("vault::transactions"), None);
+        assert_eq!(parse_topic("user:0xabc:positions:extra"), None);
+    }
+}
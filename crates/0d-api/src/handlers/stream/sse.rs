@@ -0,0 +1,277 @@
+use std::{convert::Infallible, future::ready, time::Duration};
+
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    response::sse::{Event, KeepAlive, Sse},
+};
+use futures::{
+    Stream, StreamExt,
+    stream::{self, BoxStream},
+};
+use zerod_db::{
+    ZerodPool,
+    models::{TransactionEventRecord, Vault},
+};
+use zerod_types::IndexerEvent;
+
+use crate::{
+    AppState,
+    errors::{ApiError, DatabaseErrorExt},
+    helpers::normalize_address,
+};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(15);
+/// Most transaction events replayed on resume. Larger backlogs are sent over several connections.
+const REPLAY_LIMIT: i64 = 500;
+
+#[utoipa::path(
+    get,
+    path = "/users/{address}/events",
+    tag = "Streaming",
+    params(
+        ("address" = String, Path, description = "User wallet address"),
+        ("Last-Event-ID" = Option<i64>, Header, description = "Resume after this transaction event id, the `id` of the last event received. Event ids follow the transaction event log, a claim gets its own id while keeping the transaction id of its request")
+    ),
+    responses(
+        (status = 200, description = "Server-Sent Events stream of deposit, redeem_requested, redeem_claimed and indexer_status events", content_type = "text/event-stream", body = IndexerEvent),
+        (status = 400, description = "Invalid Last-Event-ID"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn stream_user_events(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let filter = EventFilter::User(normalize_address(&address));
+    event_stream(&state, filter, &headers).await
+}
+
+#[utoipa::path(
+    get,
+    path = "/vaults/{vault_id}/events",
+    tag = "Streaming",
+    params(
+        ("vault_id" = String, Path, description = "Vault identifier"),
+        ("Last-Event-ID" = Option<i64>, Header, description = "Resume after this transaction event id, the `id` of the last event received. Event ids follow the transaction event log, a claim gets its own id while keeping the transaction id of its request")
+    ),
+    responses(
+        (status = 200, description = "Server-Sent Events stream of deposit, redeem_requested, redeem_claimed and indexer_status events", content_type = "text/event-stream", body = IndexerEvent),
+        (status = 400, description = "Invalid Last-Event-ID"),
+        (status = 404, description = "Vault not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn stream_vault_events(
+    State(state): State<AppState>,
+    Path(vault_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, ApiError> {
+    let vault_id_clone = vault_id.clone();
+    state
+        .pool
+        .interact_with_context(format!("find vault by id: {vault_id}"), move |conn| {
+            Vault::find_by_id(&vault_id_clone, conn)
+        })
+        .await
+        .map_err(|e| e.or_not_found(format!("Vault {vault_id} not found")))?;
+
+    event_stream(&state, EventFilter::Vault(vault_id), &headers).await
+}
+
+#[derive(Debug, Clone)]
+enum EventFilter {
+    User(String),
+    Vault(String),
+}
+
+impl EventFilter {
+    /// Transactions of the user or vault, and the status of the indexers
+    fn matches(&self, event: &IndexerEvent) -> bool {
+        match (self, event) {
+            (Self::User(address), IndexerEvent::Transaction(tx)) => tx.user_address == *address,
+            (
+                Self::Vault(vault_id),
+                IndexerEvent::Transaction(_) | IndexerEvent::IndexerStatus(_),
+            ) => event.vault_id() == vault_id,
+            (Self::User(_), IndexerEvent::IndexerStatus(_)) => true,
+            _ => false,
+        }
+    }
+}
+
+/// Replay the transaction events after `Last-Event-ID`, then follow the live events
+async fn event_stream(
+    state: &AppState,
+    filter: EventFilter,
+    headers: &HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>> + use<>>, ApiError> {
+    let last_event_id = last_event_id(headers)?;
+
+    // Subscribe before reading the backlog so that no event falls in between
+    let receiver = state.events.subscribe();
+
+    let backlog = match last_event_id {
+        Some(after_id) => {
+            let filter = filter.clone();
+            state
+                .pool
+                .interact_with_context(
+                    format!("fetch transaction events after id {after_id}"),
+                    move |conn| {
+                        let (user, vault) = match &filter {
+                            EventFilter::User(address) => (Some(address.as_str()), None),
+                            EventFilter::Vault(vault_id) => (None, Some(vault_id.as_str())),
+                        };
+                        TransactionEventRecord::find_after_id(
+                            user,
+                            vault,
+                            after_id,
+                            REPLAY_LIMIT,
+                            conn,
+                        )
+                    },
+                )
+                .await?
+        }
+        None => Vec::new(),
+    };
+
+    // Once the backlog is sent, close a truncated stream so the client resumes from there
+    let truncated = i64::try_from(backlog.len()).unwrap_or(i64::MAX) >= REPLAY_LIMIT;
+    let resume_after = backlog.last().map(|record| record.id).or(last_event_id);
+    let backlog = stream::iter(
+        backlog
+            .into_iter()
+            .filter_map(|record| record.to_event())
+            .map(IndexerEvent::Transaction),
+    );
+
+    let events: BoxStream<'static, IndexerEvent> = if truncated {
+        backlog.boxed()
+    } else {
+        let live = stream::unfold(receiver, |mut receiver| async move {
+            // On lag, closing the stream makes the client resume from its Last-Event-ID
+            receiver.recv().await.ok().map(|event| (event, receiver))
+        })
+        .filter(move |event| ready(filter.matches(event) && !already_sent(event, resume_after)));
+        backlog.chain(live).boxed()
+    };
+
+    let stream = events.filter_map(|event| ready(sse_event(&event).map(Ok)));
+    Ok(Sse::new(stream).keep_alive(
+        KeepAlive::new()
+            .interval(HEARTBEAT_INTERVAL)
+            .text("heartbeat"),
+    ))
+}
+
+fn last_event_id(headers: &HeaderMap) -> Result<Option<i64>, ApiError> {
+    headers
+        .get("last-event-id")
+        .map(|value| {
+            value
+                .to_str()
+                .ok()
+                .and_then(|v| v.trim().parse::<i64>().ok())
+                .ok_or_else(|| ApiError::BadRequest("Invalid Last-Event-ID header".to_string()))
+        })
+        .transpose()
+}
+
+/// Live transaction events already replayed from the backlog
+const fn already_sent(event: &IndexerEvent, resume_after: Option<i64>) -> bool {
+    match (event, resume_after) {
+        (IndexerEvent::Transaction(tx), Some(after_id)) => tx.event_id <= after_id,
+        _ => false,
+    }
+}
+
+fn sse_event(event: &IndexerEvent) -> Option<Event> {
+    let sse = match event {
+        IndexerEvent::Transaction(tx) => Event::default()
+            .event(tx.kind.as_str())
+            .id(tx.event_id.to_string()),
+        IndexerEvent::IndexerStatus(_) => Event::default().event("indexer_status"),
+        IndexerEvent::Position(_) | IndexerEvent::SharePrice(_) => return None,
+    };
+
+    sse.json_data(event)
+        .inspect_err(|e| tracing::error!("Failed to serialize event: {e}"))
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::http::HeaderValue;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use zerod_types::events::{IndexerStatusEvent, TransactionEventKind};
+
+    use super::*;
+
+    fn record(id: i64, kind: &str) -> TransactionEventRecord {
+        TransactionEventRecord {
+            id,
+            transaction_id: 7,
+            kind: kind.to_string(),
+            vault_id: "1".to_string(),
+            user_address: "0xabc".to_string(),
+            partner_id: None,
+            tx_hash: "0x123".to_string(),
+            status: "confirmed".to_string(),
+            amount: Decimal::ONE_HUNDRED,
+            shares_amount: Some(Decimal::TEN),
+            block_number: 42,
+            block_timestamp: Utc::now(),
+            created_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_parses_last_event_id() {
+        let mut headers = HeaderMap::new();
+        assert_eq!(last_event_id(&headers).unwrap(), None);
+
+        headers.insert("last-event-id", HeaderValue::from_static(" 12 "));
+        assert_eq!(last_event_id(&headers).unwrap(), Some(12));
+
+        headers.insert("last-event-id", HeaderValue::from_static("abc"));
+        assert!(last_event_id(&headers).is_err());
+    }
+
+    #[test]
+    fn test_skips_live_events_already_replayed() {
+        let claim = IndexerEvent::Transaction(
+            record(5, TransactionEventKind::RedeemClaimed.as_str())
+                .to_event()
+                .unwrap(),
+        );
+        let status = IndexerEvent::IndexerStatus(IndexerStatusEvent {
+            vault_id: "1".to_string(),
+            status: "synced".to_string(),
+            last_processed_block: 42,
+        });
+
+        assert!(already_sent(&claim, Some(5)));
+        assert!(!already_sent(&claim, Some(4)));
+        assert!(!already_sent(&claim, None));
+        assert!(!already_sent(&status, Some(5)));
+    }
+
+    #[test]
+    fn test_rebuilds_transaction_events_from_the_log() {
+        // A claim keeps the transaction id of its request but gets its own event id
+        let event = record(9, TransactionEventKind::RedeemClaimed.as_str())
+            .to_event()
+            .unwrap();
+        assert_eq!(event.event_id, 9);
+        assert_eq!(event.id, 7);
+        assert_eq!(event.kind, TransactionEventKind::RedeemClaimed);
+        assert_eq!(event.amount, "100");
+        assert_eq!(event.shares.as_deref(), Some("10"));
+
+        assert!(record(10, "transfer").to_event().is_none());
+    }
+}
@@ -0,0 +1,127 @@
+use std::{collections::HashSet, time::Duration};
+
+use axum::{
+    body::Bytes,
+    extract::{
+        State,
+        ws::{Message, WebSocket, WebSocketUpgrade},
+    },
+    response::Response,
+};
+use tokio::sync::broadcast::{Receiver, error::RecvError};
+use zerod_types::IndexerEvent;
+
+use super::{MAX_TOPICS_PER_CONNECTION, parse_topic};
+use crate::{
+    AppState,
+    dto::{StreamClientMessage, StreamServerMessage},
+};
+
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(30);
+
+#[utoipa::path(
+    get,
+    path = "/ws",
+    tag = "Streaming",
+    responses(
+        (status = 101, description = "Switching to a WebSocket stream. Clients send `StreamClientMessage` and receive `StreamServerMessage` frames", body = StreamServerMessage)
+    )
+)]
+pub async fn stream_events(ws: WebSocketUpgrade, State(state): State<AppState>) -> Response {
+    let events = state.events.subscribe();
+    ws.on_upgrade(move |socket| handle_socket(socket, events))
+}
+
+async fn handle_socket(mut socket: WebSocket, mut events: Receiver<IndexerEvent>) {
+    let mut topics: HashSet<String> = HashSet::new();
+    let mut heartbeat = tokio::time::interval(HEARTBEAT_INTERVAL);
+
+    loop {
+        tokio::select! {
+            message = socket.recv() => {
+                let Some(Ok(message)) = message else {
+                    break;
+                };
+                let reply = match message {
+                    Message::Text(text) => handle_client_message(text.as_str(), &mut topics),
+                    Message::Close(_) => break,
+                    _ => continue,
+                };
+                if send(&mut socket, &reply).await.is_err() {
+                    break;
+                }
+            }
+            event = events.recv() => {
+                let reply = match event {
+                    Ok(event) => {
+                        let Some(topic) = event.topics().into_iter().find(|t| topics.contains(t)) else {
+                            continue;
+                        };
+                        StreamServerMessage::Event { topic, data: Box::new(event) }
+                    }
+                    Err(RecvError::Lagged(missed)) => StreamServerMessage::Lagged { missed },
+                    Err(RecvError::Closed) => break,
+                };
+                if send(&mut socket, &reply).await.is_err() {
+                    break;
+                }
+            }
+            _ = heartbeat.tick() => {
+                if socket.send(Message::Ping(Bytes::new())).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+}
+
+fn handle_client_message(text: &str, topics: &mut HashSet<String>) -> StreamServerMessage {
+    let message = match serde_json::from_str::<StreamClientMessage>(text) {
+        Ok(message) => message,
+        Err(e) => {
+            return StreamServerMessage::Error {
+                message: format!("Invalid message: {e}"),
+            };
+        }
+    };
+
+    match message {
+        StreamClientMessage::Subscribe { topics: requested } => {
+            let mut parsed = Vec::with_capacity(requested.len());
+            for topic in &requested {
+                let Some(topic) = parse_topic(topic) else {
+                    return StreamServerMessage::Error {
+                        message: format!("Unknown topic: {topic}"),
+                    };
+                };
+                parsed.push(topic);
+            }
+
+            let added = parsed.iter().filter(|t| !topics.contains(*t)).count();
+            if topics.len() + added > MAX_TOPICS_PER_CONNECTION {
+                return StreamServerMessage::Error {
+                    message: format!(
+                        "Cannot subscribe to more than {MAX_TOPICS_PER_CONNECTION} topics"
+                    ),
+                };
+            }
+
+            topics.extend(parsed.iter().cloned());
+            StreamServerMessage::Subscribed { topics: parsed }
+        }
+        StreamClientMessage::Unsubscribe { topics: requested } => {
+            let removed = requested
+                .iter()
+                .filter_map(|topic| parse_topic(topic))
+                .filter(|topic| topics.remove(topic))
+                .collect();
+            StreamServerMessage::Unsubscribed { topics: removed }
+        }
+        StreamClientMessage::Ping => StreamServerMessage::Pong,
+    }
+}
+
+async fn send(socket: &mut WebSocket, message: &StreamServerMessage) -> Result<(), axum::Error> {
+    let text = serde_json::to_string(message).unwrap_or_default();
+    socket.send(Message::Text(text.into())).await
+}
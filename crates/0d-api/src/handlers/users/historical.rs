@@ -8,9 +8,9 @@ use serde::Deserialize;
 use crate::{
     AppState,
     dto::ApiResponse,
-    dto::{HistoricalDataPoint, HistoricalUserPerformance, PerformanceMetric, Timeframe},
+    dto::{HistoricalUserPerformance, PerformanceMetric, Timeframe},
     errors::{ApiError, DatabaseErrorExt},
-    helpers::{normalize_address, quote_to_currency, validate_indexer_status},
+    helpers::{normalize_address, quote_series_to_currency, validate_indexer_status},
 };
 use zerod_db::{ZerodPool, models::UserKpi};
 use zerod_types::Currency;
@@ -93,15 +93,8 @@ pub async fn get_historical_user_performance(
         )
         .await?;
 
-    // Convert to API format with currency conversion
-    let mut points: Vec<HistoricalDataPoint> = Vec::new();
-    for (timestamp, value) in historical_data {
-        let converted_value = quote_to_currency(value, query.currency).await?;
-        points.push(HistoricalDataPoint {
-            t: timestamp,
-            v: converted_value.to_string(),
-        });
-    }
+    // Convert to API format, each point at the exchange rate of its own day
+    let points = quote_series_to_currency(historical_data, query.currency).await?;
 
     let response = HistoricalUserPerformance {
         metric: query.metric,
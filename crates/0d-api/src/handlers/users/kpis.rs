@@ -1,21 +1,22 @@
 use axum::{
     Json,
-    extract::{Path, State},
+    extract::{Path, Query, State},
     response::IntoResponse,
 };
 use rust_decimal::Decimal;
 
 use crate::{
     AppState,
-    dto::{ApiResponse, UserKpi},
+    dto::{ApiResponse, RealizedLot, UserKpisQuery, UserKpisResponse},
     errors::{ApiError, DatabaseErrorExt},
-    helpers::{normalize_address, validate_indexer_status},
+    helpers::{normalize_address, parse_risk_metrics, validate_indexer_status},
 };
 use zerod_db::{
     ZerodPool,
     models::{UserPosition, UserTransaction, Vault},
+    types::CostBasisMethod,
 };
-use zerod_kpi::calculate_user_pnl;
+use zerod_kpi::{LotLedger, calculate_ledger_pnl};
 use zerod_master::JaffarClient;
 use zerod_master::VaultMasterClient;
 
@@ -25,10 +26,13 @@ use zerod_master::VaultMasterClient;
     tag = "User",
     params(
         ("address" = String, Path, description = "User wallet address"),
-        ("vault_id" = String, Path, description = "Vault identifier")
+        ("vault_id" = String, Path, description = "Vault identifier"),
+        ("metrics" = Option<String>, Query, description = "Comma-separated risk metrics to return (defaults to all)", example = "sharpe,sortino,volatility"),
+        ("cost_basis_method" = Option<CostBasisMethod>, Query, description = "Lot selection method for cost basis and realized PnL (defaults to fifo)")
     ),
     responses(
-        (status = 200, description = "User performance KPIs", body = UserKpi),
+        (status = 200, description = "User performance KPIs", body = UserKpisResponse),
+        (status = 400, description = "Invalid risk metrics selector or cost basis method"),
         (status = 404, description = "User KPIs not found"),
         (status = 503, description = "Indexer not synced or experiencing issues"),
         (status = 500, description = "Internal server error")
@@ -37,8 +41,10 @@ use zerod_master::VaultMasterClient;
 pub async fn get_user_kpis(
     State(state): State<AppState>,
     Path((address, vault_id)): Path<(String, String)>,
+    Query(params): Query<UserKpisQuery>,
 ) -> Result<impl IntoResponse, ApiError> {
     let address = normalize_address(&address);
+    let selected_metrics = parse_risk_metrics(params.metrics.as_deref())?;
 
     // Validate that the indexer is synced before serving user data
     validate_indexer_status(&vault_id, &state.pool).await?;
@@ -106,8 +112,13 @@ pub async fn get_user_kpis(
         .await?;
 
     // Calculate PnL metrics real-time for current accuracy
+    let ledger =
+        LotLedger::from_transactions(&transactions, params.cost_basis_method).map_err(|e| {
+            tracing::error!("Failed to build tax lots: {e}");
+            ApiError::InternalServerError
+        })?;
     let pnl_result =
-        calculate_user_pnl(&position, &transactions, current_share_price).map_err(|e| {
+        calculate_ledger_pnl(&position, &ledger, current_share_price).map_err(|e| {
             tracing::error!("Failed to calculate real-time PnL: {e}");
             ApiError::InternalServerError
         })?;
@@ -115,6 +126,15 @@ pub async fn get_user_kpis(
     cached_kpis.all_time_pnl = Some(pnl_result.all_time_pnl);
     cached_kpis.unrealized_pnl = Some(pnl_result.unrealized_pnl);
     cached_kpis.realized_pnl = Some(pnl_result.realized_pnl);
+    cached_kpis.retain_risk_metrics(&selected_metrics);
 
-    Ok(Json(ApiResponse::ok(cached_kpis)))
+    Ok(Json(ApiResponse::ok(UserKpisResponse {
+        kpis: cached_kpis,
+        cost_basis_method: params.cost_basis_method,
+        realized_lots: ledger
+            .realized_lots()
+            .iter()
+            .map(RealizedLot::from)
+            .collect(),
+    })))
 }
@@ -1,13 +1,17 @@
 pub mod historical;
 pub mod kpis;
+pub mod portfolio;
 pub mod profile;
 pub mod redeems;
+pub mod reports;
 pub mod summary;
 pub mod transactions;
 
 pub use historical::get_historical_user_performance;
 pub use kpis::get_user_kpis;
+pub use portfolio::get_user_portfolio;
 pub use profile::get_user_profile;
 pub use redeems::get_user_pending_redeems;
+pub use reports::get_user_tax_report;
 pub use summary::get_user_position_summary;
-pub use transactions::get_user_transaction_history;
+pub use transactions::{get_user_transaction_history, get_user_transactions};
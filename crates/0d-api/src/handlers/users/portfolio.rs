@@ -0,0 +1,266 @@
+use std::collections::HashMap;
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use chrono::{DateTime, Duration, Utc};
+use futures::future::try_join_all;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+
+use crate::{
+    AppState,
+    dto::{ApiResponse, PortfolioPosition, PortfolioQuery, Timeframe, UserPortfolio},
+    errors::ApiError,
+    helpers::{
+        call_vault_backend, fetch_vault_with_client, normalize_address, quote_series_to_currency,
+        quote_to_currency, validate_indexer_status, value_at_transaction_time,
+    },
+};
+use zerod_db::{
+    ZerodPool,
+    models::{UserPortfolioHistory, UserPosition, UserTransaction, Vault},
+    types::CostBasisMethod,
+};
+use zerod_kpi::{KpiError, LotLedger, calculate_ledger_pnl};
+use zerod_types::Currency;
+
+#[utoipa::path(
+    get,
+    path = "/users/{address}/portfolio",
+    tag = "User",
+    params(
+        ("address" = String, Path, description = "User wallet address"),
+        ("currency" = Option<Currency>, Query, description = "Display currency (defaults to USD)"),
+        ("timeframe" = Option<Timeframe>, Query, description = "Time period of the value history"),
+        ("cost_basis_method" = Option<CostBasisMethod>, Query, description = "Lot selection method for realized PnL (defaults to fifo)")
+    ),
+    responses(
+        (status = 200, description = "User positions across all vaults", body = UserPortfolio),
+        (status = 400, description = "Invalid parameters"),
+        (status = 503, description = "USD prices unavailable"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_user_portfolio(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<PortfolioQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let address = normalize_address(&address);
+    let now = Utc::now();
+    let since = query
+        .timeframe
+        .to_days()
+        .map(|days| now - Duration::days(days));
+
+    // Run parallel database queries for better performance
+    let (positions_result, transactions_result) = tokio::join!(
+        state
+            .pool
+            .interact_with_context(format!("find positions for user {address}"), {
+                let address = address.clone();
+                move |conn| UserPosition::find_by_user(&address, conn)
+            }),
+        state
+            .pool
+            .interact_with_context(format!("fetch transactions for user {address}"), {
+                let address = address.clone();
+                move |conn| UserTransaction::find_by_user_chronological(&address, conn)
+            }),
+    );
+    let (positions, closed_positions): (Vec<UserPosition>, Vec<UserPosition>) = positions_result?
+        .into_iter()
+        .partition(|position| position.share_balance > Decimal::ZERO);
+
+    // Closed positions stop counting in the value history from their last activity
+    let closed_at: Vec<(String, DateTime<Utc>)> = closed_positions
+        .iter()
+        .filter_map(|position| Some((position.vault_id.clone(), position.last_activity_at?)))
+        .collect();
+    let address_clone = address.clone();
+    let history = state
+        .pool
+        .interact_with_context(
+            format!("fetch aggregate portfolio history for user {address}"),
+            move |conn| {
+                UserPortfolioHistory::get_user_aggregate_time_series(
+                    &address_clone,
+                    since,
+                    &closed_at,
+                    conn,
+                )
+            },
+        )
+        .await?;
+
+    let mut transactions_by_vault: HashMap<String, Vec<UserTransaction>> = HashMap::new();
+    for tx in transactions_result? {
+        transactions_by_vault
+            .entry(tx.vault_id.clone())
+            .or_default()
+            .push(tx);
+    }
+
+    // Lots are booked at their USD value at transaction time, like the share prices
+    let all_vaults = state
+        .pool
+        .interact_with_context("fetch all vaults".to_string(), Vault::find_all)
+        .await?;
+    let mut ledgers = value_ledgers(
+        &address,
+        &transactions_by_vault,
+        &all_vaults,
+        query.cost_basis_method,
+    )
+    .await?;
+
+    // Fetch the current share price of every vault the user is in
+    let vault_futures = positions
+        .iter()
+        .map(|position| fetch_position_vault(&state, position));
+    let vaults = try_join_all(vault_futures).await?;
+
+    let total_value_usd: Decimal = positions
+        .iter()
+        .zip(&vaults)
+        .map(|(position, (_, share_price, _))| position.share_balance * share_price)
+        .sum();
+
+    // Closed positions only add the gains realized when exiting them
+    let mut realized_pnl: Decimal = closed_positions
+        .iter()
+        .filter_map(|position| ledgers.get(&position.vault_id))
+        .map(LotLedger::realized_pnl)
+        .sum();
+    let mut unrealized_pnl = Decimal::ZERO;
+    let mut items = Vec::with_capacity(positions.len());
+    for (position, (vault, share_price, indexer_stale)) in positions.iter().zip(vaults) {
+        let ledger = ledgers
+            .remove(&position.vault_id)
+            .unwrap_or_else(|| LotLedger::new(query.cost_basis_method));
+        let pnl = calculate_ledger_pnl(position, &ledger, share_price)
+            .map_err(|e| ledger_error(&address, &position.vault_id, &e))?;
+        realized_pnl += pnl.realized_pnl;
+        unrealized_pnl += pnl.unrealized_pnl;
+
+        let value_usd = position.share_balance * share_price;
+        let allocation_pct = if total_value_usd > Decimal::ZERO {
+            (value_usd / total_value_usd * Decimal::ONE_HUNDRED)
+                .to_f64()
+                .unwrap_or_default()
+        } else {
+            0.0
+        };
+
+        items.push(PortfolioPosition {
+            vault_id: position.vault_id.clone(),
+            vault_name: vault.name,
+            share_balance: position.share_balance.to_string(),
+            share_price_usd: share_price.to_string(),
+            value_usd: value_usd.to_string(),
+            value: quote_to_currency(value_usd, query.currency)
+                .await?
+                .to_string(),
+            allocation_pct,
+            realized_pnl: quote_to_currency(pnl.realized_pnl, query.currency)
+                .await?
+                .to_string(),
+            unrealized_pnl: quote_to_currency(pnl.unrealized_pnl, query.currency)
+                .await?
+                .to_string(),
+            all_time_pnl: quote_to_currency(pnl.all_time_pnl, query.currency)
+                .await?
+                .to_string(),
+            indexer_stale,
+        });
+    }
+
+    let points = quote_series_to_currency(history, query.currency).await?;
+
+    let portfolio = UserPortfolio {
+        address,
+        as_of: now,
+        currency: query.currency,
+        total_value_usd: total_value_usd.to_string(),
+        total_value: quote_to_currency(total_value_usd, query.currency)
+            .await?
+            .to_string(),
+        realized_pnl: quote_to_currency(realized_pnl, query.currency)
+            .await?
+            .to_string(),
+        unrealized_pnl: quote_to_currency(unrealized_pnl, query.currency)
+            .await?
+            .to_string(),
+        all_time_pnl: quote_to_currency(realized_pnl + unrealized_pnl, query.currency)
+            .await?
+            .to_string(),
+        positions: items,
+        history: points,
+    };
+
+    Ok(Json(ApiResponse::ok(portfolio)))
+}
+
+/// Replay the lots of every vault of the user, valued in USD at transaction time
+async fn value_ledgers(
+    address: &str,
+    transactions_by_vault: &HashMap<String, Vec<UserTransaction>>,
+    vaults: &[Vault],
+    method: CostBasisMethod,
+) -> Result<HashMap<String, LotLedger>, ApiError> {
+    let mut ledgers = HashMap::with_capacity(transactions_by_vault.len());
+    for (vault_id, transactions) in transactions_by_vault {
+        let amounts_usd = value_at_transaction_time(transactions, vaults).await;
+        let ledger = LotLedger::from_valued_transactions(transactions, &amounts_usd, method)
+            .map_err(|e| ledger_error(address, vault_id, &e))?;
+        ledgers.insert(vault_id.clone(), ledger);
+    }
+    Ok(ledgers)
+}
+
+/// Fetch the vault of a position with its current share price in USD, and whether its
+/// indexer is lagging
+async fn fetch_position_vault(
+    state: &AppState,
+    position: &UserPosition,
+) -> Result<(Vault, Decimal, bool), ApiError> {
+    // A lagging indexer only flags its position, the others are still served
+    let indexer_stale = match validate_indexer_status(&position.vault_id, &state.pool).await {
+        Ok(()) => false,
+        Err(ApiError::ServiceUnavailable(msg)) => {
+            tracing::warn!(
+                "Position in vault {} may be stale: {msg}",
+                position.vault_id
+            );
+            true
+        }
+        Err(e) => return Err(e),
+    };
+
+    let (vault, client) = fetch_vault_with_client(state, &position.vault_id).await?;
+    let info = call_vault_backend(&client, &vault, "fetch vault info", |backend| async move {
+        backend.get_vault_info().await
+    })
+    .await?;
+    let share_price = info.share_price_in_usd.parse::<Decimal>().map_err(|e| {
+        tracing::error!(
+            "Failed to parse share price '{}': {e}",
+            info.share_price_in_usd
+        );
+        ApiError::InternalServerError
+    })?;
+
+    Ok((vault, share_price, indexer_stale))
+}
+
+fn ledger_error(address: &str, vault_id: &str, e: &KpiError) -> ApiError {
+    if let KpiError::MissingValue(msg) = e {
+        return ApiError::ServiceUnavailable(format!(
+            "USD prices are unavailable for vault {vault_id}: {msg}"
+        ));
+    }
+    tracing::error!("Failed to calculate PnL for user {address} in vault {vault_id}: {e}");
+    ApiError::InternalServerError
+}
@@ -1,27 +1,134 @@
+use std::{
+    collections::{BTreeSet, HashMap},
+    str::FromStr,
+};
+
 use axum::{
-    Json,
-    extract::{Path, Query, State},
-    response::IntoResponse,
+    extract::{OriginalUri, Path, Query, State},
+    response::Response,
 };
-use chrono::Utc;
+use chrono::{DateTime, Duration, Utc};
+use futures::future::try_join_all;
 use rust_decimal::Decimal;
 use serde::Deserialize;
 
 use crate::{
     AppState,
-    dto::{ApiResponse, PendingRedeem, user::PendingRedeemsResponse},
+    dto::{PendingRedeem, user::PendingRedeemsResponse},
     errors::{ApiError, DatabaseErrorExt},
-    helpers::normalize_address,
+    helpers::{call_vault_backend, fetch_vault_with_client, normalize_address, quote_to_currency},
+    pagination::{Page, PaginationQuery, paginated},
 };
 use zerod_db::{
     ZerodPool,
-    models::{User, user_transaction::UserTransaction},
+    models::{RedeemEpochStart, RedeemRequest, TransactionType, User, Vault},
 };
+use zerod_types::Currency;
 
 #[derive(Debug, Deserialize)]
 pub struct PendingRedeemsQuery {
     pub vault_id: Option<String>,
     pub asset_type: Option<String>,
+    #[serde(default)]
+    pub currency: Currency,
+}
+
+/// Epoch cadence of a vault, estimated from the first redeem requested during each epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct EpochSchedule {
+    /// Estimated start of the current epoch
+    current_start: DateTime<Utc>,
+    /// Median length of an epoch
+    duration: Duration,
+}
+
+impl EpochSchedule {
+    /// `None` when fewer than two epochs had redeems requested
+    fn from_epoch_starts(starts: &[RedeemEpochStart], current_epoch: i64) -> Option<Self> {
+        let mut durations: Vec<Duration> = starts
+            .windows(2)
+            .filter_map(|pair| {
+                let epochs = i32::try_from(pair[1].epoch - pair[0].epoch).ok()?;
+                (epochs > 0)
+                    .then(|| (pair[1].first_requested_at - pair[0].first_requested_at) / epochs)
+            })
+            .collect();
+        durations.sort_unstable();
+        let duration = *durations.get(durations.len() / 2)?;
+        if duration <= Duration::zero() {
+            return None;
+        }
+
+        let anchor = starts.iter().rev().find(|s| s.epoch <= current_epoch)?;
+        let current_start = anchor.first_requested_at
+            + duration * i32::try_from(current_epoch - anchor.epoch).ok()?;
+        Some(Self {
+            current_start,
+            duration,
+        })
+    }
+
+    /// Estimated end of an epoch, when its redeems are processed
+    fn epoch_end(self, epoch: i64, current_epoch: i64) -> Option<DateTime<Utc>> {
+        let epochs_left = i32::try_from(epoch - current_epoch + 1).ok()?;
+        Some(self.current_start + self.duration * epochs_left)
+    }
+}
+
+/// Time within which the redeems of a vault are processed: 24h while the assets queued for
+/// redemption stay within `threshold_pct` of the AUM, 48h above it when the vault commits to it.
+/// `None` when the vault makes no commitment.
+fn processing_window(
+    threshold_pct: Option<Decimal>,
+    above_threshold_48h: bool,
+    queued_assets: Decimal,
+    aum: Decimal,
+) -> Option<Duration> {
+    let threshold_pct = threshold_pct?;
+    if queued_assets <= aum * threshold_pct / Decimal::ONE_HUNDRED {
+        Some(Duration::hours(24))
+    } else if above_threshold_48h {
+        Some(Duration::hours(48))
+    } else {
+        None
+    }
+}
+
+/// State of a vault needed to value its pending redeems and estimate when they are claimable
+struct VaultRedeemState {
+    vault: Vault,
+    current_epoch: i64,
+    share_price_usd: Decimal,
+    processing_window: Option<Duration>,
+    schedule: Option<EpochSchedule>,
+}
+
+impl VaultRedeemState {
+    /// Estimate when a redeem is claimable: right away once its epoch is processed, otherwise
+    /// within the processing window of the vault from its request, or at the end of its epoch
+    /// when the vault makes no commitment. A redeem past its expected time is overdue and
+    /// expected now. Returns the time and whether the redeem is overdue.
+    fn expected_claimable_at(
+        &self,
+        request: &RedeemRequest,
+        now: DateTime<Utc>,
+    ) -> (Option<DateTime<Utc>>, bool) {
+        if request.epoch < self.current_epoch {
+            return (Some(now), false);
+        }
+
+        let expected = self.processing_window.map_or_else(
+            || {
+                self.schedule
+                    .and_then(|schedule| schedule.epoch_end(request.epoch, self.current_epoch))
+            },
+            |window| Some(request.requested_at + window),
+        );
+        match expected {
+            Some(expected) if expected < now => (Some(now), true),
+            expected => (expected, false),
+        }
+    }
 }
 
 #[utoipa::path(
@@ -31,10 +138,13 @@ pub struct PendingRedeemsQuery {
     params(
         ("address" = String, Path, description = "User wallet address"),
         ("vault_id" = Option<String>, Query, description = "Filter by vault ID"),
-        ("asset_type" = Option<String>, Query, description = "Filter by asset type")
+        ("asset_type" = Option<String>, Query, description = "Filter by asset type"),
+        ("currency" = Option<Currency>, Query, description = "Currency of the current values (defaults to USD)"),
+        PaginationQuery
     ),
     responses(
-        (status = 200, description = "Pending redeems for user", body = PendingRedeemsResponse),
+        (status = 200, description = "Pending redeems for user, newest first", body = PendingRedeemsResponse),
+        (status = 400, description = "Invalid cursor"),
         (status = 404, description = "User not found"),
         (status = 500, description = "Internal server error")
     )
@@ -43,7 +153,9 @@ pub async fn get_user_pending_redeems(
     State(state): State<AppState>,
     Path(address): Path<String>,
     Query(query): Query<PendingRedeemsQuery>,
-) -> Result<impl IntoResponse, ApiError> {
+    Query(pagination): Query<PaginationQuery>,
+    OriginalUri(uri): OriginalUri,
+) -> Result<Response, ApiError> {
     let address = normalize_address(&address);
 
     // First verify the user exists
@@ -56,34 +168,92 @@ pub async fn get_user_pending_redeems(
         .await
         .map_err(|e| e.or_not_found(format!("User {address} not found")))?;
 
-    // Get pending transactions for the user
+    // Get a page of the pending redeem requests of the user, along with the shares pending
+    // in each vault for the total
+    let limit = pagination.limit();
+    let after: Option<(DateTime<Utc>, i32)> = pagination.after()?;
     let address_clone = address.clone();
     let vault_id_filter = query.vault_id.clone();
-    let pending_user_txs: Vec<UserTransaction> = state
+    let (pending_requests, pending_shares) = state
         .pool
         .interact_with_context(
-            format!("fetch pending transactions for user: {address}"),
+            format!("fetch pending redeem requests for user: {address}"),
             move |conn| {
-                UserTransaction::find_pending_by_user(
+                let requests = RedeemRequest::find_pending_by_user(
                     &address_clone,
                     vault_id_filter.as_deref(),
+                    after,
+                    limit + 1, // Get one extra to determine if there's a next page
                     conn,
-                )
+                )?;
+                let shares = RedeemRequest::pending_shares_by_vault(
+                    &address_clone,
+                    vault_id_filter.as_deref(),
+                    conn,
+                )?;
+                Ok::<_, diesel::result::Error>((requests, shares))
             },
         )
         .await?;
+    let Page {
+        items: pending_requests,
+        next_cursor,
+    } = Page::from_fetched(pending_requests, limit, |request| {
+        (request.requested_at, request.id)
+    });
 
-    // Convert to PendingAsset DTOs
-    let pending_redeems: Vec<PendingRedeem> = pending_user_txs
+    // Fetch the current state of every vault with a pending redeem
+    let vault_ids: BTreeSet<&str> = pending_shares
+        .iter()
+        .map(|pending| pending.vault_id.as_str())
+        .chain(
+            pending_requests
+                .iter()
+                .map(|request| request.vault_id.as_str()),
+        )
+        .collect();
+    let vault_futures = vault_ids
         .into_iter()
-        .map(PendingRedeem::from)
+        .map(|vault_id| fetch_vault_redeem_state(&state, vault_id));
+    let vaults: HashMap<String, VaultRedeemState> = try_join_all(vault_futures)
+        .await?
+        .into_iter()
+        .map(|vault_state| (vault_state.vault.id.clone(), vault_state))
         .collect();
 
-    // Calculate total pending USD
-    let total_pending: Decimal = pending_redeems
+    let total_pending_usd: Decimal = pending_shares
         .iter()
-        .map(|asset| asset.amount.parse::<Decimal>().unwrap_or_default())
+        .map(|pending| pending.shares * vaults[&pending.vault_id].share_price_usd)
         .sum();
+    let total_pending = quote_to_currency(total_pending_usd, query.currency).await?;
+
+    let now = Utc::now();
+    let mut pending_redeems = Vec::with_capacity(pending_requests.len());
+    for request in pending_requests {
+        let vault_state = &vaults[&request.vault_id];
+        let current_value = quote_to_currency(
+            request.requested_shares * vault_state.share_price_usd,
+            query.currency,
+        )
+        .await?;
+
+        let (expected_claimable_at, overdue) = vault_state.expected_claimable_at(&request, now);
+
+        pending_redeems.push(PendingRedeem {
+            claimable: request.epoch < vault_state.current_epoch,
+            expected_claimable_at,
+            overdue,
+            vault_id: request.vault_id,
+            redeem_id: request.redeem_id,
+            epoch: request.epoch,
+            shares: request.requested_shares.to_string(),
+            amount: request.nominal_assets.to_string(),
+            current_value: current_value.to_string(),
+            transaction_type: TransactionType::Withdraw,
+            tx_hash: request.request_tx_hash,
+            submitted_at: request.requested_at,
+        });
+    }
 
     // Calculate average redeem delay
     let address_clone = address.clone();
@@ -93,7 +263,7 @@ pub async fn get_user_pending_redeems(
         .interact_with_context(
             format!("calculate average redeem delay for user: {address}"),
             move |conn| {
-                UserTransaction::calculate_average_redeem_delay(
+                RedeemRequest::average_claim_delay(
                     &address_clone,
                     vault_id_for_delay.as_deref(),
                     conn,
@@ -104,11 +274,138 @@ pub async fn get_user_pending_redeems(
 
     let response = PendingRedeemsResponse {
         address: address.clone(),
-        as_of: Utc::now(),
+        as_of: now,
+        currency: query.currency,
         pending_redeems,
         total_pending: total_pending.to_string(),
         average_redeem_delay,
+        next_cursor: next_cursor.clone(),
     };
 
-    Ok(Json(ApiResponse::ok(response)))
+    Ok(paginated(response, &uri, next_cursor.as_deref()))
+}
+
+async fn fetch_vault_redeem_state(
+    state: &AppState,
+    vault_id: &str,
+) -> Result<VaultRedeemState, ApiError> {
+    let (vault, client) = fetch_vault_with_client(state, vault_id).await?;
+    let info = call_vault_backend(&client, &vault, "fetch vault info", |backend| async move {
+        backend.get_vault_info().await
+    })
+    .await?;
+    let share_price_usd = info.share_price_in_usd.parse::<Decimal>().map_err(|e| {
+        tracing::error!(
+            "Failed to parse share price '{}': {e}",
+            info.share_price_in_usd
+        );
+        ApiError::InternalServerError
+    })?;
+    let current_epoch = info.current_epoch.parse::<i64>().map_err(|e| {
+        tracing::error!(
+            "Failed to parse current epoch '{}': {e}",
+            info.current_epoch
+        );
+        ApiError::InternalServerError
+    })?;
+    let (queued_assets, aum) = match (
+        info.pending_withdrawals_assets.parse::<Decimal>(),
+        info.aum.parse::<Decimal>(),
+    ) {
+        (Ok(queued_assets), Ok(aum)) => (queued_assets, aum),
+        (Err(e), _) | (_, Err(e)) => {
+            tracing::error!(
+                "Failed to parse the pending withdrawals or AUM of vault {vault_id}: {e}"
+            );
+            return Err(ApiError::InternalServerError);
+        }
+    };
+    let threshold_pct = vault
+        .redeem_24h_threshold_pct_of_aum
+        .as_ref()
+        .and_then(|pct| Decimal::from_str(&pct.to_string()).ok());
+    let processing_window = processing_window(
+        threshold_pct,
+        vault.redeem_48h_above_threshold.unwrap_or_default(),
+        queued_assets,
+        aum,
+    );
+
+    let vault_id_clone = vault_id.to_string();
+    let epoch_starts = state
+        .pool
+        .interact_with_context(
+            format!("fetch epoch starts of vault: {vault_id}"),
+            move |conn| RedeemRequest::epoch_starts(&vault_id_clone, conn),
+        )
+        .await?;
+
+    Ok(VaultRedeemState {
+        vault,
+        current_epoch,
+        share_price_usd,
+        processing_window,
+        schedule: EpochSchedule::from_epoch_starts(&epoch_starts, current_epoch),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+
+    fn epoch_start(epoch: i64, first_requested_at: DateTime<Utc>) -> RedeemEpochStart {
+        RedeemEpochStart {
+            epoch,
+            first_requested_at,
+        }
+    }
+
+    #[test]
+    fn test_epoch_schedule_from_daily_epochs() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        // Epoch 3 had no redeem, its length is spread over epochs 2 and 3
+        let starts = [
+            epoch_start(1, start),
+            epoch_start(2, start + Duration::days(1)),
+            epoch_start(4, start + Duration::days(3)),
+        ];
+
+        let schedule = EpochSchedule::from_epoch_starts(&starts, 5).unwrap();
+        assert_eq!(schedule.duration, Duration::days(1));
+        assert_eq!(schedule.current_start, start + Duration::days(4));
+        assert_eq!(schedule.epoch_end(5, 5), Some(start + Duration::days(5)));
+        assert_eq!(schedule.epoch_end(6, 5), Some(start + Duration::days(6)));
+    }
+
+    #[test]
+    fn test_processing_window_above_threshold() {
+        let aum = Decimal::from(1_000);
+        let threshold = Some(Decimal::TEN);
+
+        assert_eq!(
+            processing_window(threshold, false, Decimal::ONE_HUNDRED, aum),
+            Some(Duration::hours(24))
+        );
+        assert_eq!(
+            processing_window(threshold, true, Decimal::from(101), aum),
+            Some(Duration::hours(48))
+        );
+        assert_eq!(
+            processing_window(threshold, false, Decimal::from(101), aum),
+            None
+        );
+        assert_eq!(processing_window(None, true, Decimal::ZERO, aum), None);
+    }
+
+    #[test]
+    fn test_epoch_schedule_needs_two_epochs() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        assert_eq!(
+            EpochSchedule::from_epoch_starts(&[epoch_start(1, start)], 1),
+            None
+        );
+        assert_eq!(EpochSchedule::from_epoch_starts(&[], 1), None);
+    }
 }
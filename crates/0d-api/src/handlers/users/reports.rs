@@ -0,0 +1,387 @@
+use std::{collections::BTreeMap, str::FromStr};
+
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::{IntoResponse, Response},
+};
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use rust_decimal::Decimal;
+
+use crate::{
+    AppState,
+    csv::CsvWriter,
+    dto::{
+        ApiResponse, RealizedLot, ReportFormat, TaxLedgerEntry, TaxReport, TaxReportQuery,
+        VaultTaxReport, YearEndHolding,
+    },
+    errors::ApiError,
+    helpers::{normalize_address, validate_indexer_status, value_at_transaction_time},
+};
+use zerod_db::{
+    ZerodPool,
+    models::{
+        RedeemRequest, UserPortfolioHistory, UserTransaction, Vault,
+        user_transaction::{TransactionStatus, TransactionType},
+    },
+    types::CostBasisMethod,
+};
+use zerod_kpi::{KpiError, LotLedger};
+use zerod_quoting::currencies::{CURRENCIES_PRICES, Currency};
+
+const CSV_HEADERS: [&str; 15] = [
+    "record",
+    "vault_id",
+    "date",
+    "tx_hash",
+    "type",
+    "shares",
+    "share_price",
+    "amount",
+    "amount_usd",
+    "acquired_at",
+    "holding_period_days",
+    "term",
+    "cost_basis_usd",
+    "proceeds_usd",
+    "gain_usd",
+];
+
+#[utoipa::path(
+    get,
+    path = "/users/{address}/reports/tax",
+    tag = "User",
+    params(
+        ("address" = String, Path, description = "User wallet address"),
+        ("year" = Option<i32>, Query, description = "Calendar year of the report (defaults to the current year)", example = 2025),
+        ("format" = Option<ReportFormat>, Query, description = "Output format (defaults to json)"),
+        ("cost_basis_method" = Option<CostBasisMethod>, Query, description = "Lot selection method for realized gains (defaults to fifo)")
+    ),
+    responses(
+        (status = 200, description = "Per-vault ledger, realized gains and year-end holdings", body = TaxReport),
+        (status = 400, description = "Invalid year"),
+        (status = 503, description = "Indexer not synced or experiencing issues, or USD prices unavailable"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_user_tax_report(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(params): Query<TaxReportQuery>,
+) -> Result<Response, ApiError> {
+    let address = normalize_address(&address);
+    let now = Utc::now();
+    let year = params.year.unwrap_or_else(|| now.year());
+    if year < 2000 || year > now.year() {
+        return Err(ApiError::BadRequest(format!(
+            "Year must be between 2000 and {}",
+            now.year()
+        )));
+    }
+
+    let period_start = start_of_year(year)?;
+    let period_end = start_of_year(year + 1)?.min(now);
+
+    let address_clone = address.clone();
+    let transactions = state
+        .pool
+        .interact_with_context(
+            format!("fetch transactions for user {address}"),
+            move |conn| UserTransaction::find_by_user_chronological(&address_clone, conn),
+        )
+        .await?;
+
+    let address_clone = address.clone();
+    let claims = state
+        .pool
+        .interact_with_context(
+            format!("fetch claimed redemptions for user {address}"),
+            move |conn| RedeemRequest::find_claimed_by_user(&address_clone, period_end, conn),
+        )
+        .await?;
+    let mut claims_by_vault: BTreeMap<String, Vec<RedeemRequest>> = BTreeMap::new();
+    for claim in claims {
+        claims_by_vault
+            .entry(claim.vault_id.clone())
+            .or_default()
+            .push(claim);
+    }
+
+    let mut transactions_by_vault: BTreeMap<String, Vec<UserTransaction>> = BTreeMap::new();
+    for tx in transactions {
+        if tx.block_timestamp < period_end {
+            transactions_by_vault
+                .entry(tx.vault_id.clone())
+                .or_default()
+                .push(tx);
+        }
+    }
+
+    let all_vaults = state
+        .pool
+        .interact_with_context("fetch all vaults".to_string(), Vault::find_all)
+        .await?;
+
+    let mut vaults = Vec::with_capacity(transactions_by_vault.len());
+    let mut total_realized_gain = Decimal::ZERO;
+    for (vault_id, transactions) in transactions_by_vault {
+        // Validate that the indexer is synced before serving user data
+        validate_indexer_status(&vault_id, &state.pool).await?;
+
+        let vault_id_clone = vault_id.clone();
+        let share_price = state
+            .pool
+            .interact_with_context(
+                format!("fetch share price of vault {vault_id} before {period_end}"),
+                move |conn| {
+                    UserPortfolioHistory::find_share_price_before(&vault_id_clone, period_end, conn)
+                },
+            )
+            .await?;
+        let amounts_usd = value_at_transaction_time(&transactions, &all_vaults).await;
+        let claims = claims_by_vault.remove(&vault_id).unwrap_or_default();
+        let claim_entries = claim_entries(&vault_id, &claims, period_start, &all_vaults).await;
+        let share_price_usd = match share_price {
+            Some(price) => value_in_usd(&vault_id, price, period_end, &all_vaults).await,
+            None => None,
+        };
+
+        let mut report = build_vault_report(
+            vault_id,
+            &transactions,
+            &amounts_usd,
+            params.cost_basis_method,
+            period_start,
+            share_price,
+            share_price_usd,
+        )?;
+        // The claims of the redemptions are listed alongside the transactions
+        report.0.entries.extend(claim_entries);
+        report.0.entries.sort_by_key(|entry| entry.timestamp);
+        total_realized_gain += report.1;
+        vaults.push(report.0);
+    }
+
+    let report = TaxReport {
+        address,
+        year,
+        cost_basis_method: params.cost_basis_method,
+        period_start,
+        period_end,
+        generated_at: now,
+        vaults,
+        total_realized_gain_usd: total_realized_gain.to_string(),
+    };
+
+    Ok(match params.format {
+        ReportFormat::Json => Json(ApiResponse::ok(report)).into_response(),
+        ReportFormat::Csv => {
+            let filename = format!("tax-report-{}-{year}.csv", report.address);
+            write_csv(&report).into_response(&filename)
+        }
+    })
+}
+
+fn start_of_year(year: i32) -> Result<DateTime<Utc>, ApiError> {
+    Utc.with_ymd_and_hms(year, 1, 1, 0, 0, 0)
+        .single()
+        .ok_or_else(|| ApiError::BadRequest(format!("Invalid year: {year}")))
+}
+
+/// Value an amount of the underlying currency of a vault in USD at a point in time
+async fn value_in_usd(
+    vault_id: &str,
+    amount: Decimal,
+    at: DateTime<Utc>,
+    vaults: &[Vault],
+) -> Option<Decimal> {
+    let currency = vaults
+        .iter()
+        .find(|vault| vault.id == vault_id)
+        .and_then(|vault| Currency::from_str(&vault.base_asset).ok())?;
+
+    match CURRENCIES_PRICES
+        .convert_series(&[(at, amount)], currency, Currency::USD)
+        .await
+    {
+        Ok(values) => values.into_iter().next(),
+        Err(e) => {
+            tracing::warn!("Failed to value vault {vault_id} in USD at {at}: {e}");
+            None
+        }
+    }
+}
+
+/// Ledger entries of the redemptions of a vault claimed over the period, valued in USD at
+/// claim time
+async fn claim_entries(
+    vault_id: &str,
+    claims: &[RedeemRequest],
+    period_start: DateTime<Utc>,
+    vaults: &[Vault],
+) -> Vec<TaxLedgerEntry> {
+    let mut entries = Vec::new();
+    for claim in claims {
+        let (Some(tx_hash), Some(claimed_at), Some(claimed_assets)) = (
+            claim.claim_tx_hash.as_ref(),
+            claim.claimed_at,
+            claim.claimed_assets,
+        ) else {
+            continue;
+        };
+        if claimed_at < period_start {
+            continue;
+        }
+
+        let amount_usd = value_in_usd(vault_id, claimed_assets, claimed_at, vaults).await;
+        entries.push(TaxLedgerEntry {
+            timestamp: claimed_at,
+            tx_hash: tx_hash.clone(),
+            transaction_type: TransactionType::Claim,
+            shares: Some(claim.requested_shares.to_string()),
+            share_price: None,
+            // NOTE: Amount should be normalized
+            amount: claimed_assets.to_string(),
+            amount_usd: amount_usd.map(|a| a.to_string()),
+        });
+    }
+    entries
+}
+
+/// Build the report of a vault along with its realized gain over the period.
+/// Transactions prior to the period are replayed to rebuild the open lots, each booked at
+/// its USD value at transaction time from `amounts_usd` so cost basis and proceeds are in USD.
+fn build_vault_report(
+    vault_id: String,
+    transactions: &[UserTransaction],
+    amounts_usd: &[Option<Decimal>],
+    method: CostBasisMethod,
+    period_start: DateTime<Utc>,
+    share_price: Option<Decimal>,
+    share_price_usd: Option<Decimal>,
+) -> Result<(VaultTaxReport, Decimal), ApiError> {
+    let ledger =
+        LotLedger::from_valued_transactions(transactions, amounts_usd, method).map_err(|e| {
+            if let KpiError::MissingValue(msg) = e {
+                return ApiError::ServiceUnavailable(format!(
+                    "USD prices are unavailable for vault {vault_id}: {msg}"
+                ));
+            }
+            tracing::error!("Failed to build tax lots for vault {vault_id}: {e}");
+            ApiError::InternalServerError
+        })?;
+
+    let entries: Vec<TaxLedgerEntry> = transactions
+        .iter()
+        .zip(amounts_usd)
+        .filter(|(tx, _)| {
+            tx.block_timestamp >= period_start && tx.status == TransactionStatus::Confirmed.as_str()
+        })
+        .filter_map(|(tx, amount_usd)| {
+            let transaction_type = match tx.type_.as_str() {
+                "deposit" => TransactionType::Deposit,
+                "withdraw" => TransactionType::Withdraw,
+                _ => return None,
+            };
+            Some(TaxLedgerEntry {
+                timestamp: tx.block_timestamp,
+                tx_hash: tx.tx_hash.clone(),
+                transaction_type,
+                shares: tx.shares_amount.map(|s| s.to_string()),
+                share_price: tx.share_price.map(|p| p.to_string()),
+                // NOTE: Amount should be normalized
+                amount: tx.amount.to_string(),
+                amount_usd: amount_usd.map(|a| a.to_string()),
+            })
+        })
+        .collect();
+
+    let realized: Vec<&zerod_kpi::RealizedLot> = ledger
+        .realized_lots()
+        .iter()
+        .filter(|lot| lot.disposed_at >= period_start)
+        .collect();
+    let realized_gain: Decimal = realized.iter().map(|lot| lot.realized_gain).sum();
+
+    let shares = ledger.share_balance();
+    let cost_basis = ledger.cost_basis();
+    let value = share_price_usd.map(|price| shares * price);
+
+    let report = VaultTaxReport {
+        vault_id,
+        entries,
+        realized_lots: realized.into_iter().map(RealizedLot::from).collect(),
+        realized_gain_usd: realized_gain.to_string(),
+        year_end_holding: YearEndHolding {
+            shares: shares.to_string(),
+            cost_basis_usd: cost_basis.to_string(),
+            share_price: share_price.map(|p| p.to_string()),
+            value_usd: value.map(|v| v.to_string()),
+            unrealized_gain_usd: value.map(|v| (v - cost_basis).to_string()),
+        },
+    };
+
+    Ok((report, realized_gain))
+}
+
+/// Flatten the report into one CSV document: ledger entries, disposals and holdings
+fn write_csv(report: &TaxReport) -> CsvWriter {
+    let mut writer = CsvWriter::new(&CSV_HEADERS);
+
+    for vault in &report.vaults {
+        for entry in &vault.entries {
+            writer.write_row([
+                "transaction",
+                &vault.vault_id,
+                &entry.timestamp.to_rfc3339(),
+                &entry.tx_hash,
+                entry.transaction_type.as_str(),
+                entry.shares.as_deref().unwrap_or_default(),
+                entry.share_price.as_deref().unwrap_or_default(),
+                &entry.amount,
+                entry.amount_usd.as_deref().unwrap_or_default(),
+            ]);
+        }
+
+        for lot in &vault.realized_lots {
+            writer.write_row([
+                "disposal",
+                &vault.vault_id,
+                &lot.disposed_at.to_rfc3339(),
+                &lot.disposal_tx_hash,
+                TransactionType::Withdraw.as_str(),
+                &lot.shares,
+                "",
+                "",
+                &lot.proceeds_usd,
+                &lot.acquired_at.to_rfc3339(),
+                &lot.holding_period_days.to_string(),
+                if lot.long_term { "long" } else { "short" },
+                &lot.cost_basis_usd,
+                &lot.proceeds_usd,
+                &lot.realized_gain_usd,
+            ]);
+        }
+
+        let holding = &vault.year_end_holding;
+        writer.write_row([
+            "holding",
+            &vault.vault_id,
+            &report.period_end.to_rfc3339(),
+            "",
+            "",
+            &holding.shares,
+            holding.share_price.as_deref().unwrap_or_default(),
+            "",
+            holding.value_usd.as_deref().unwrap_or_default(),
+            "",
+            "",
+            "",
+            &holding.cost_basis_usd,
+            "",
+            holding.unrealized_gain_usd.as_deref().unwrap_or_default(),
+        ]);
+    }
+
+    writer
+}
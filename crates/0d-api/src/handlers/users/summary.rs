@@ -8,16 +8,21 @@ use crate::{
     AppState,
     dto::{ApiResponse, UserPositionSummary},
     errors::{ApiError, DatabaseErrorExt},
-    helpers::{normalize_address, validate_indexer_status},
+    helpers::{
+        call_vault_backend, fetch_vault_with_client, normalize_address, validate_indexer_status,
+    },
 };
 use chrono::Utc;
 use rust_decimal::Decimal;
 use zerod_db::{
     ZerodPool,
-    models::{UserPosition, UserTransaction, Vault},
+    models::{
+        RedeemRequest, UserPosition, UserTransaction,
+        user_transaction::{TransactionStatus, TransactionType},
+    },
+    types::CostBasisMethod,
 };
-use zerod_master::JaffarClient;
-use zerod_master::VaultMasterClient;
+use zerod_kpi::calculate_user_pnl;
 
 #[utoipa::path(
     get,
@@ -43,61 +48,91 @@ pub async fn get_user_position_summary(
     // Validate that the indexer is synced before serving user data
     validate_indexer_status(&vault_id, &state.pool).await?;
 
-    // Find the user's position in the vault
-    let address_clone = address.clone();
-    let vault_id_clone = vault_id.clone();
-    let position = state
-        .pool
-        .interact_with_context(
+    // Run parallel database queries for better performance
+    let (position_result, transactions_result, pending_result) = tokio::join!(
+        state.pool.interact_with_context(
             format!("find position for user {address} in vault {vault_id}"),
-            move |conn| UserPosition::find_by_user_and_vault(&address_clone, &vault_id_clone, conn),
-        )
-        .await
-        .map_err(|e| {
-            e.or_not_found(format!(
-                "User {address} position in vault {vault_id} not found"
-            ))
-        })?;
+            {
+                let address = address.clone();
+                let vault_id = vault_id.clone();
+                move |conn| UserPosition::find_by_user_and_vault(&address, &vault_id, conn)
+            }
+        ),
+        state.pool.interact_with_context(
+            format!("fetch transactions for user {address} in vault {vault_id}"),
+            {
+                let address = address.clone();
+                let vault_id = vault_id.clone();
+                move |conn| {
+                    UserTransaction::find_by_user_and_vault_chronological(&address, &vault_id, conn)
+                }
+            }
+        ),
+        state.pool.interact_with_context(
+            format!("fetch pending redeems for user {address} in vault {vault_id}"),
+            {
+                let address = address.clone();
+                let vault_id = vault_id.clone();
+                move |conn| RedeemRequest::pending_shares_by_vault(&address, Some(&vault_id), conn)
+            }
+        ),
+    );
+
+    let position = position_result.map_err(|e| {
+        e.or_not_found(format!(
+            "User {address} position in vault {vault_id} not found"
+        ))
+    })?;
+    let transactions = transactions_result?;
+    let pending_redeems = pending_result?.into_iter().next();
 
-    // Get vault metadata to fetch share price
-    let vault_id_clone = vault_id.clone();
-    let vault = state
-        .pool
-        .interact_with_context(format!("find vault by id: {vault_id}"), move |conn| {
-            Vault::find_by_id(&vault_id_clone, conn)
+    // Fetch current share price from the vault's own backend
+    let (vault, client) = fetch_vault_with_client(&state, &vault_id).await?;
+    let vault_info =
+        call_vault_backend(&client, &vault, "fetch vault info", |backend| async move {
+            backend.get_vault_info().await
         })
-        .await
-        .map_err(|e| e.or_not_found(format!("Vault {vault_id} not found")))?;
+        .await?;
+    let share_price = vault_info
+        .share_price_in_usd
+        .parse::<Decimal>()
+        .map_err(|e| {
+            tracing::error!(
+                "Failed to parse share price '{}': {e}",
+                vault_info.share_price_in_usd
+            );
+            ApiError::InternalServerError
+        })?;
 
-    // Fetch current share price from vault API
-    let client = JaffarClient::new(&vault.api_endpoint);
-    let share_price_str = client.get_vault_info().await?.share_price_in_usd;
-    let share_price = share_price_str.parse::<Decimal>().map_err(|e| {
-        tracing::error!("Failed to parse share price '{share_price_str}': {e}");
+    // Earnings account for withdrawals through the cost basis and realized PnL
+    let pnl = calculate_user_pnl(
+        &position,
+        &transactions,
+        share_price,
+        CostBasisMethod::default(),
+    )
+    .map_err(|e| {
+        tracing::error!("Failed to calculate PnL for user {address} in vault {vault_id}: {e}");
         ApiError::InternalServerError
     })?;
 
-    // Calculate total deposits using database query
-    let address_clone = address.clone();
-    let vault_id_clone = vault_id.clone();
-    let total_deposits = state
-        .pool
-        .interact_with_context(
-            format!("calculate total deposits for user {address} in vault {vault_id}"),
-            move |conn| {
-                UserTransaction::total_deposits_by_user_and_vault(
-                    &address_clone,
-                    &vault_id_clone,
-                    conn,
-                )
-                .map(std::option::Option::unwrap_or_default)
-            },
-        )
-        .await?;
+    let total_deposits: Decimal = transactions
+        .iter()
+        .filter(|tx| {
+            tx.type_ == TransactionType::Deposit.as_str()
+                && tx.status == TransactionStatus::Confirmed.as_str()
+        })
+        .map(|tx| tx.amount)
+        .sum();
+
+    // Pending redeems are valued at the current share price, like the position
+    let (pending_redeems_value, pending_redeems_count) = pending_redeems
+        .map_or((Decimal::ZERO, 0), |pending| {
+            (pending.shares * share_price, pending.requests)
+        });
 
     // Calculate position metrics
     let position_value = position.share_balance * share_price;
-    let all_time_earned = position_value.saturating_sub(total_deposits);
 
     let summary = UserPositionSummary {
         vault_id: vault_id.clone(),
@@ -107,7 +142,12 @@ pub async fn get_user_position_summary(
         share_price: share_price.to_string(),
         first_deposit_at: position.first_deposit_at,
         total_deposits: total_deposits.to_string(),
-        all_time_earned: all_time_earned.to_string(),
+        all_time_earned: pnl.all_time_pnl.to_string(),
+        unrealized_pnl: pnl.unrealized_pnl.to_string(),
+        realized_pnl: pnl.realized_pnl.to_string(),
+        cost_basis: pnl.cost_basis.to_string(),
+        pending_redeems: pending_redeems_value.to_string(),
+        pending_redeems_count: usize::try_from(pending_redeems_count).unwrap_or_default(),
     };
 
     Ok(Json(ApiResponse::ok(summary)))
@@ -1,24 +1,55 @@
+use std::collections::HashMap;
+
 use axum::{
-    Json,
-    extract::{Path, Query, State},
-    response::IntoResponse,
+    extract::{OriginalUri, Path, Query, State},
+    http::{HeaderMap, HeaderValue, header},
+    response::Response,
 };
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
 use crate::{
     AppState,
-    dto::{ApiResponse, UserTransaction, UserTransactionHistory},
+    csv::CsvWriter,
+    dto::{UserTransaction, UserTransactionDetails, UserTransactionsQuery},
     errors::ApiError,
-    helpers::{normalize_address, validate_indexer_status},
+    helpers::{
+        explorer_tx_url, normalize_address, validate_indexer_status, value_at_transaction_time,
+    },
+    pagination::{Page, PaginationQuery, paginated},
+};
+use zerod_db::{
+    ZerodPool,
+    models::{
+        TransactionStatus, TransactionType, UserTransaction as DbUserTransaction,
+        UserTransactionFilter, Vault,
+    },
+    types::SortOrder,
 };
-use serde::Deserialize;
-use zerod_db::{ZerodPool, models::UserTransaction as DbUserTransaction};
+
+/// Maximum number of transactions of a CSV export
+const MAX_EXPORT_ROWS: i64 = 10_000;
+
+const CSV_HEADERS: [&str; 13] = [
+    "id",
+    "vault_id",
+    "type",
+    "status",
+    "amount",
+    "underlying_currency",
+    "shares",
+    "share_price",
+    "amount_usd",
+    "tx_hash",
+    "block_number",
+    "timestamp",
+    "explorer_url",
+];
 
 #[derive(Debug, Deserialize)]
 pub struct TransactionQuery {
     #[serde(rename = "type")]
-    pub transaction_type: Option<String>,
-    pub limit: Option<i64>,
-    pub cursor: Option<String>,
+    pub transaction_type: Option<TransactionType>,
 }
 
 #[utoipa::path(
@@ -28,12 +59,12 @@ pub struct TransactionQuery {
     params(
         ("address" = String, Path, description = "User wallet address"),
         ("vault_id" = String, Path, description = "Vault identifier"),
-        ("type" = Option<String>, Query, description = "Transaction type filter"),
-        ("limit" = Option<i64>, Query, description = "Number of transactions to return (1-200, default: 50)"),
-        ("cursor" = Option<String>, Query, description = "Pagination cursor")
+        ("type" = Option<TransactionType>, Query, description = "Transaction type filter"),
+        PaginationQuery
     ),
     responses(
-        (status = 200, description = "User transaction history", body = UserTransactionHistory),
+        (status = 200, description = "User transaction history", body = Page<UserTransaction>),
+        (status = 400, description = "Invalid parameters"),
         (status = 404, description = "User or vault not found"),
         (status = 503, description = "Indexer not synced or experiencing issues"),
         (status = 500, description = "Internal server error")
@@ -43,32 +74,189 @@ pub async fn get_user_transaction_history(
     State(state): State<AppState>,
     Path((address, vault_id)): Path<(String, String)>,
     Query(query): Query<TransactionQuery>,
-) -> Result<impl IntoResponse, ApiError> {
+    Query(pagination): Query<PaginationQuery>,
+    OriginalUri(uri): OriginalUri,
+) -> Result<Response, ApiError> {
     let address = normalize_address(&address);
 
     // Validate that the indexer is synced before serving user data
     validate_indexer_status(&vault_id, &state.pool).await?;
 
-    // Validate and set limit
-    let limit = query.limit.unwrap_or(50).clamp(1, 200);
+    let filter = UserTransactionFilter {
+        vault_ids: vec![vault_id.clone()],
+        type_: query.transaction_type,
+        ..Default::default()
+    };
+
+    let page = fetch_transactions_page(
+        &state,
+        &address,
+        filter,
+        SortOrder::Desc,
+        &pagination,
+        pagination.limit(),
+    )
+    .await?
+    .map(UserTransaction::from);
+    let next_cursor = page.next_cursor.clone();
 
-    // Parse cursor for pagination (using transaction ID)
-    let cursor_id: Option<i32> = query.cursor.and_then(|c| c.parse().ok());
+    Ok(paginated(page, &uri, next_cursor.as_deref()))
+}
+
+#[utoipa::path(
+    get,
+    path = "/users/{address}/transactions",
+    tag = "User",
+    params(
+        ("address" = String, Path, description = "User wallet address"),
+        ("vault_ids" = Option<String>, Query, description = "Comma-separated vault ids (defaults to all vaults)"),
+        ("type" = Option<TransactionType>, Query, description = "Transaction type filter"),
+        ("status" = Option<TransactionStatus>, Query, description = "Transaction status filter"),
+        ("from" = Option<DateTime<Utc>>, Query, description = "Inclusive start of the period (RFC 3339)"),
+        ("to" = Option<DateTime<Utc>>, Query, description = "Exclusive end of the period (RFC 3339)"),
+        ("order" = Option<SortOrder>, Query, description = "Sort order by time (asc, desc), defaults to desc"),
+        PaginationQuery
+    ),
+    responses(
+        (status = 200, description = "User transactions across vaults, as CSV with `Accept: text/csv` (up to 10000 rows, next cursor in `X-Next-Cursor`)", body = Page<UserTransactionDetails>),
+        (status = 400, description = "Invalid parameters"),
+        (status = 503, description = "Indexer not synced or experiencing issues"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_user_transactions(
+    State(state): State<AppState>,
+    Path(address): Path<String>,
+    Query(query): Query<UserTransactionsQuery>,
+    Query(pagination): Query<PaginationQuery>,
+    OriginalUri(uri): OriginalUri,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let address = normalize_address(&address);
+    let as_csv = headers
+        .get(header::ACCEPT)
+        .and_then(|accept| accept.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/csv"));
 
-    let address_clone = address.clone();
-    let vault_id_clone = vault_id.clone();
-    let transaction_type_clone = query.transaction_type.clone();
+    if let (Some(from), Some(to)) = (query.from, query.to)
+        && from >= to
+    {
+        return Err(ApiError::BadRequest("from must be before to".to_string()));
+    }
+    let mut vault_ids: Vec<String> = query
+        .vault_ids
+        .as_deref()
+        .map(|ids| {
+            ids.split(',')
+                .map(str::trim)
+                .filter(|id| !id.is_empty())
+                .map(ToString::to_string)
+                .collect()
+        })
+        .unwrap_or_default();
+    vault_ids.sort_unstable();
+    vault_ids.dedup();
+    // Validate that the indexer is synced before serving user data
+    for vault_id in &vault_ids {
+        validate_indexer_status(vault_id, &state.pool).await?;
+    }
 
+    let limit = if as_csv {
+        pagination.limit_within(MAX_EXPORT_ROWS, MAX_EXPORT_ROWS)
+    } else {
+        pagination.limit()
+    };
+    let filter = UserTransactionFilter {
+        vault_ids,
+        type_: query.transaction_type,
+        status: query.status,
+        from: query.from,
+        to: query.to,
+    };
+
+    let Page {
+        items: transactions,
+        next_cursor,
+    } = fetch_transactions_page(&state, &address, filter, query.order, &pagination, limit).await?;
+
+    let all_vaults = state
+        .pool
+        .interact_with_context("fetch all vaults".to_string(), Vault::find_all)
+        .await?;
+    let amounts_usd = value_at_transaction_time(&transactions, &all_vaults).await;
+    let vaults: HashMap<&str, &Vault> = all_vaults
+        .iter()
+        .map(|vault| (vault.id.as_str(), vault))
+        .collect();
+
+    let items: Vec<UserTransactionDetails> = transactions
+        .into_iter()
+        .zip(amounts_usd)
+        .map(|(tx, amount_usd)| {
+            let vault = vaults.get(tx.vault_id.as_str());
+            UserTransactionDetails {
+                underlying_currency: vault.map(|v| v.base_asset.clone()).unwrap_or_default(),
+                shares: tx.shares_amount.map(|s| s.to_string()),
+                share_price: tx.share_price.map(|p| p.to_string()),
+                amount_usd: amount_usd.map(|a| a.to_string()),
+                block_number: tx.block_number,
+                explorer_url: vault.and_then(|v| explorer_tx_url(&v.chain, &tx.tx_hash)),
+                transaction: UserTransaction::from(tx),
+            }
+        })
+        .collect();
+
+    if !as_csv {
+        let page = Page {
+            items,
+            next_cursor: next_cursor.clone(),
+        };
+        return Ok(paginated(page, &uri, next_cursor.as_deref()));
+    }
+
+    let mut response = write_csv(&items).into_response(&format!("transactions-{address}.csv"));
+    if let Some(next_cursor) = next_cursor
+        && let Ok(value) = HeaderValue::from_str(&next_cursor)
+    {
+        response.headers_mut().insert("x-next-cursor", value);
+    }
+    Ok(response)
+}
+
+/// Filters the cursors of a transactions listing are bound to
+#[derive(Serialize)]
+struct CursorFilters<'a> {
+    filter: &'a UserTransactionFilter,
+    order: SortOrder,
+}
+
+/// Fetch a page of the transactions of a user, keyed by (timestamp, id). The cursors only
+/// resume a listing with the same filters and order.
+async fn fetch_transactions_page(
+    state: &AppState,
+    address: &str,
+    filter: UserTransactionFilter,
+    order: SortOrder,
+    pagination: &PaginationQuery,
+    limit: i64,
+) -> Result<Page<DbUserTransaction>, ApiError> {
+    let filters = CursorFilters {
+        filter: &filter,
+        order,
+    };
+    let after: Option<(DateTime<Utc>, i32)> = pagination.after_filtered(&filters)?;
+    let filter_clone = filter.clone();
+    let address_clone = address.to_string();
     let transactions = state
         .pool
         .interact_with_context(
-            format!("fetch transactions for user {address} in vault {vault_id}"),
+            format!("fetch transactions for user {address}"),
             move |conn| {
-                DbUserTransaction::find_by_user_and_vault_paginated(
+                DbUserTransaction::find_by_user_filtered(
                     &address_clone,
-                    &vault_id_clone,
-                    transaction_type_clone.as_deref(),
-                    cursor_id,
+                    &filter_clone,
+                    order,
+                    after,
                     limit + 1, // Get one extra to determine if there's a next page
                     conn,
                 )
@@ -76,27 +264,30 @@ pub async fn get_user_transaction_history(
         )
         .await?;
 
-    // Determine if there are more results and set next cursor
-    let has_more = transactions.len() > limit as usize;
-    let transactions_to_return = if has_more {
-        &transactions[..limit as usize]
-    } else {
-        &transactions
-    };
-
-    let next_cursor = if has_more {
-        transactions_to_return.last().map(|tx| tx.id.to_string())
-    } else {
-        None
-    };
-
-    // Convert to DTOs
-    let items: Vec<UserTransaction> = transactions_to_return
-        .iter()
-        .map(|tx| UserTransaction::from(tx.clone()))
-        .collect();
-
-    let response = UserTransactionHistory { items, next_cursor };
+    Ok(Page::from_filtered(transactions, limit, &filters, |tx| {
+        (tx.block_timestamp, tx.id)
+    }))
+}
 
-    Ok(Json(ApiResponse::ok(response)))
+fn write_csv(items: &[UserTransactionDetails]) -> CsvWriter {
+    let mut writer = CsvWriter::new(&CSV_HEADERS);
+    for item in items {
+        let tx = &item.transaction;
+        writer.write_row([
+            tx.id.as_str(),
+            &tx.vault_id,
+            tx.transaction_type.as_str(),
+            tx.status.as_str(),
+            &tx.amount,
+            &item.underlying_currency,
+            item.shares.as_deref().unwrap_or_default(),
+            item.share_price.as_deref().unwrap_or_default(),
+            item.amount_usd.as_deref().unwrap_or_default(),
+            &tx.tx_hash,
+            &item.block_number.to_string(),
+            &tx.timestamp.to_rfc3339(),
+            item.explorer_url.as_deref().unwrap_or_default(),
+        ]);
+    }
+    writer
 }
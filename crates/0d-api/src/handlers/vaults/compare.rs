@@ -0,0 +1,277 @@
+use std::cmp::Ordering;
+
+use axum::{
+    extract::{OriginalUri, Query, State},
+    response::Response,
+};
+use chrono::{DateTime, Utc};
+use futures::future::{join_all, try_join_all};
+use rust_decimal::{Decimal, dec, prelude::ToPrimitive};
+use zerod_master::{AprPoint, TimeseriesPoint as BackendPoint};
+
+use zerod_db::{ZerodPool, models::Vault, types::Timeframe};
+
+use crate::{
+    AppState,
+    dto::{TimeseriesPoint, VaultCompareQuery, VaultCompareResponse, VaultComparison},
+    errors::ApiError,
+    helpers::{VaultBackendClient, call_vault_backend, fetch_vault},
+    pagination::{Page, PaginationQuery, paginated},
+};
+
+/// Maximum number of vaults compared at once
+const MAX_COMPARED_VAULTS: usize = 10;
+
+#[utoipa::path(
+    get,
+    path = "/vaults/compare",
+    tag = "Vaults",
+    params(
+        ("ids" = Option<String>, Query, description = "Comma-separated vault ids (up to 10), a page of the live vaults ranked by APR when omitted", example = "1,2,3"),
+        ("timeframe" = Option<Timeframe>, Query, description = "Time period (7d, 30d, 1y, all)", example = "30d"),
+        PaginationQuery
+    ),
+    responses(
+        (status = 200, description = "Side-by-side performance of the vaults", body = VaultCompareResponse),
+        (status = 400, description = "Invalid parameters"),
+        (status = 404, description = "Vault not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn compare_vaults(
+    State(state): State<AppState>,
+    Query(query): Query<VaultCompareQuery>,
+    Query(pagination): Query<PaginationQuery>,
+    OriginalUri(uri): OriginalUri,
+) -> Result<Response, ApiError> {
+    let mut ids: Vec<String> = Vec::new();
+    for id in query.ids.as_deref().unwrap_or_default().split(',') {
+        let id = id.trim();
+        if !id.is_empty() && !ids.iter().any(|existing| existing == id) {
+            ids.push(id.to_string());
+        }
+    }
+    if ids.len() > MAX_COMPARED_VAULTS {
+        return Err(ApiError::BadRequest(format!(
+            "At most {MAX_COMPARED_VAULTS} vaults can be compared"
+        )));
+    }
+
+    // Without ids, every live vault is ranked by APR as a leaderboard, served a page at a
+    // time so that no more vaults than in a comparison are detailed at once
+    let (vaults, next_cursor) = if ids.is_empty() {
+        let ranked = rank_live_vaults(&state, &query.timeframe).await?;
+        let max = i64::try_from(MAX_COMPARED_VAULTS).unwrap_or(i64::MAX);
+        let limit = pagination.limit_within(max, max);
+        let after: Option<(Option<f64>, String)> = pagination.after_filtered(&query.timeframe)?;
+        let page: Vec<(Vault, Option<f64>)> = ranked
+            .into_iter()
+            .filter(|(vault, apr_pct)| {
+                after.as_ref().is_none_or(|(after_apr_pct, after_id)| {
+                    leaderboard_order((*apr_pct, &vault.id), (*after_apr_pct, after_id))
+                        == Ordering::Greater
+                })
+            })
+            .take(usize::try_from(limit).unwrap_or_default() + 1)
+            .collect();
+        let Page { items, next_cursor } =
+            Page::from_filtered(page, limit, &query.timeframe, |(vault, apr_pct)| {
+                (*apr_pct, vault.id.clone())
+            });
+        (items, next_cursor)
+    } else {
+        let vaults = try_join_all(ids.iter().map(|id| fetch_vault(&state, id))).await?;
+        let aprs = join_all(
+            vaults
+                .iter()
+                .map(|vault| fetch_apr_pct(vault, &query.timeframe)),
+        )
+        .await;
+        (vaults.into_iter().zip(aprs).collect(), None)
+    };
+
+    let comparisons = try_join_all(
+        vaults
+            .into_iter()
+            .map(|(vault, apr_pct)| fetch_vault_comparison(vault, apr_pct, &query.timeframe)),
+    )
+    .await?;
+
+    Ok(paginated(
+        VaultCompareResponse {
+            timeframe: query.timeframe,
+            vaults: comparisons,
+            next_cursor: next_cursor.clone(),
+        },
+        &uri,
+        next_cursor.as_deref(),
+    ))
+}
+
+/// Live vaults ranked by their APR over the timeframe, see `leaderboard_order`
+async fn rank_live_vaults(
+    state: &AppState,
+    timeframe: &Timeframe,
+) -> Result<Vec<(Vault, Option<f64>)>, ApiError> {
+    let vaults = state
+        .pool
+        .interact_with_context("fetch live vaults".to_string(), Vault::find_live)
+        .await?;
+    let aprs = join_all(vaults.iter().map(|vault| fetch_apr_pct(vault, timeframe))).await;
+
+    let mut ranked: Vec<(Vault, Option<f64>)> = vaults.into_iter().zip(aprs).collect();
+    ranked.sort_by(|(a, a_apr_pct), (b, b_apr_pct)| {
+        leaderboard_order((*a_apr_pct, &a.id), (*b_apr_pct, &b.id))
+    });
+    Ok(ranked)
+}
+
+/// Leaderboard order: highest APR first, vaults without an APR last, ties broken by id
+fn leaderboard_order(a: (Option<f64>, &str), b: (Option<f64>, &str)) -> Ordering {
+    let by_apr = match (a.0, b.0) {
+        (Some(a), Some(b)) => b.total_cmp(&a),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    };
+    by_apr.then_with(|| a.1.cmp(b.1))
+}
+
+/// Average APR of a vault over the timeframe, `None` when its backend can't provide it
+async fn fetch_apr_pct(vault: &Vault, timeframe: &Timeframe) -> Option<f64> {
+    let client = VaultBackendClient::new(vault).ok()?;
+    let timeframe = timeframe.as_str();
+    let series = call_vault_backend(&client, vault, "fetch APR series", |backend| async move {
+        backend.get_vault_apr_series(timeframe).await
+    })
+    .await
+    .ok()?;
+    average_apr_pct(&series.points)
+}
+
+/// Fetch the stats, the KPIs and the share price series of a vault concurrently.
+/// Metrics the vault backend can't provide are left out rather than failing the comparison.
+async fn fetch_vault_comparison(
+    vault: Vault,
+    apr_pct: Option<f64>,
+    timeframe: &Timeframe,
+) -> Result<VaultComparison, ApiError> {
+    let client = VaultBackendClient::new(&vault)?;
+    let timeframe = timeframe.as_str();
+
+    let (vault_stats, vault_kpis, share_prices) = tokio::join!(
+        call_vault_backend(&client, &vault, "fetch vault stats", |backend| async move {
+            backend.get_vault_stats().await
+        }),
+        call_vault_backend(&client, &vault, "fetch vault KPIs", |backend| async move {
+            backend.get_vault_kpis(timeframe).await
+        }),
+        call_vault_backend(
+            &client,
+            &vault,
+            "fetch share price series",
+            |backend| async move { backend.get_vault_share_price_series(timeframe).await }
+        ),
+    );
+    let (vault_stats, vault_kpis) = (vault_stats.ok(), vault_kpis.ok());
+    let share_prices = share_prices
+        .ok()
+        .and_then(|series| parse_points(&vault.id, &series.points))
+        .unwrap_or_default();
+    let rebased = rebase(&share_prices);
+
+    Ok(VaultComparison {
+        vault_id: vault.id,
+        name: vault.name,
+        symbol: vault.symbol,
+        underlying_currency: vault.base_asset,
+        tvl: vault_stats.as_ref().map(|stats| stats.tvl.clone()),
+        tvl_usd: vault_stats.map(|stats| stats.tvl_usd),
+        apr_pct,
+        max_drawdown_pct: vault_kpis.as_ref().map(|kpis| kpis.max_drawdown_pct),
+        sharpe: vault_kpis.map(|kpis| kpis.sharpe),
+        return_pct: rebased.last().and_then(|(_, v)| (*v - dec!(100)).to_f64()),
+        share_price_series: rebased
+            .into_iter()
+            .map(|(t, v)| TimeseriesPoint {
+                t: t.to_rfc3339(),
+                v: v.round_dp(4).to_string(),
+            })
+            .collect(),
+    })
+}
+
+/// Parse the points of a backend series, `None` if any of them is invalid
+fn parse_points(vault_id: &str, points: &[BackendPoint]) -> Option<Vec<(DateTime<Utc>, Decimal)>> {
+    points
+        .iter()
+        .map(|point| {
+            let t = DateTime::parse_from_rfc3339(&point.t).ok()?.to_utc();
+            let v = point.v.parse::<Decimal>().ok()?;
+            Some((t, v))
+        })
+        .collect::<Option<Vec<_>>>()
+        .or_else(|| {
+            tracing::error!("Invalid share price series from the backend of vault {vault_id}");
+            None
+        })
+}
+
+/// Average of the APR points of a series, `None` for an empty series
+fn average_apr_pct(points: &[AprPoint]) -> Option<f64> {
+    if points.is_empty() {
+        return None;
+    }
+    let count = f64::from(u32::try_from(points.len()).ok()?);
+    Some(points.iter().map(|point| point.apr_pct).sum::<f64>() / count)
+}
+
+/// Rebase a share price series to 100 at its first non-zero price
+fn rebase<T: Copy>(points: &[(T, Decimal)]) -> Vec<(T, Decimal)> {
+    let Some(start) = points.iter().position(|(_, price)| !price.is_zero()) else {
+        return Vec::new();
+    };
+    let base = points[start].1;
+
+    points[start..]
+        .iter()
+        .map(|(t, price)| (*t, price / base * dec!(100)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rebase_skips_leading_zeros() {
+        let rebased = rebase(&[(1, dec!(0)), (2, dec!(2)), (3, dec!(3))]);
+        assert_eq!(rebased, vec![(2, dec!(100)), (3, dec!(150))]);
+    }
+
+    #[test]
+    fn test_leaderboard_order() {
+        let mut ranked = vec![
+            (None, "1"),
+            (Some(5.0), "3"),
+            (Some(8.0), "4"),
+            (Some(5.0), "2"),
+        ];
+        ranked.sort_by(|a, b| leaderboard_order(*a, *b));
+        assert_eq!(
+            ranked,
+            vec![
+                (Some(8.0), "4"),
+                (Some(5.0), "2"),
+                (Some(5.0), "3"),
+                (None, "1")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_rebase_single_point() {
+        assert_eq!(rebase(&[(1, dec!(1.5))]), vec![(1, dec!(100))]);
+        assert!(rebase(&[(1, dec!(0))]).is_empty());
+    }
+}
@@ -0,0 +1,166 @@
+use std::collections::HashMap;
+
+use axum::{
+    extract::{OriginalUri, Path, Query, State},
+    response::Response,
+};
+use chrono::{DateTime, Duration, Utc};
+
+use zerod_db::{
+    ZerodPool,
+    models::{UserTransaction, VaultFlowTotals},
+    types::{BucketInterval, Timeframe},
+};
+use zerod_master::FlowStatsDTO;
+
+use crate::{
+    AppState,
+    dto::{VaultFlowPoint, VaultFlowsQuery, VaultFlowsResponse},
+    errors::ApiError,
+    helpers::fetch_vault,
+    pagination::{Page, PaginationQuery, paginated},
+};
+
+#[utoipa::path(
+    get,
+    path = "/vaults/{vault_id}/flows",
+    tag = "Vaults",
+    params(
+        ("vault_id" = String, Path, description = "Vault identifier"),
+        ("timeframe" = Option<Timeframe>, Query, description = "Time period (7d, 30d, 1y, all)"),
+        ("interval" = Option<BucketInterval>, Query, description = "Bucket width (1d, 1w)"),
+        PaginationQuery
+    ),
+    responses(
+        (status = 200, description = "Deposits, redemptions and depositors of the vault over time", body = VaultFlowsResponse),
+        (status = 400, description = "Invalid cursor"),
+        (status = 404, description = "Vault not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_vault_flows(
+    State(state): State<AppState>,
+    Path(vault_id): Path<String>,
+    Query(query): Query<VaultFlowsQuery>,
+    Query(pagination): Query<PaginationQuery>,
+    OriginalUri(uri): OriginalUri,
+) -> Result<Response, ApiError> {
+    let vault = fetch_vault(&state, &vault_id).await?;
+
+    let now = Utc::now();
+    let interval = query.interval;
+    let since = query
+        .timeframe
+        .to_days()
+        .map(|days| now - Duration::days(days));
+    let limit = pagination.limit();
+    let series = (query.timeframe.clone(), interval);
+    let after: Option<DateTime<Utc>> = pagination.after_filtered(&series)?;
+
+    let vault_id_clone = vault_id.clone();
+    let buckets = state
+        .pool
+        .interact_with_context(
+            format!("aggregate flows of vault: {vault_id}"),
+            move |conn| {
+                UserTransaction::vault_flow_series(
+                    &vault_id_clone,
+                    interval,
+                    since,
+                    after,
+                    limit + 1, // Get one extra to determine if there's a next page
+                    conn,
+                )
+            },
+        )
+        .await?;
+
+    // Fill the buckets without activity so that the series is evenly spaced
+    let first_bucket = after.map_or_else(
+        || {
+            since
+                .map(|since| interval.bucket_start(since))
+                .or_else(|| buckets.first().map(|bucket| bucket.bucket))
+        },
+        |after| Some(after + interval.duration()),
+    );
+    let by_bucket: HashMap<_, _> = buckets
+        .into_iter()
+        .map(|bucket| (bucket.bucket, bucket))
+        .collect();
+
+    let max_points = usize::try_from(limit + 1).unwrap_or_default();
+    let mut points = Vec::new();
+    let mut t = first_bucket.unwrap_or(now + interval.duration());
+    while t <= now && points.len() < max_points {
+        let point = by_bucket.get(&t).map_or_else(
+            || VaultFlowPoint {
+                t,
+                deposits: "0".to_string(),
+                redemptions: "0".to_string(),
+                net_flows: "0".to_string(),
+                unique_depositors: 0,
+                new_users: 0,
+                returning_users: 0,
+            },
+            |bucket| VaultFlowPoint {
+                t,
+                deposits: bucket.deposits.to_string(),
+                redemptions: bucket.redemptions.to_string(),
+                net_flows: (bucket.deposits - bucket.redemptions).to_string(),
+                unique_depositors: bucket.unique_depositors,
+                new_users: bucket.new_users,
+                returning_users: bucket.unique_depositors - bucket.new_users,
+            },
+        );
+        points.push(point);
+        t += interval.duration();
+    }
+
+    let Page {
+        items: points,
+        next_cursor,
+    } = Page::from_filtered(points, limit, &series, |point| point.t);
+
+    Ok(paginated(
+        VaultFlowsResponse {
+            vault_id,
+            underlying_currency: vault.base_asset,
+            timeframe: query.timeframe,
+            interval,
+            points,
+            next_cursor: next_cursor.clone(),
+        },
+        &uri,
+        next_cursor.as_deref(),
+    ))
+}
+
+/// Aggregate the flows of a vault over a timeframe from the indexed transactions
+pub(crate) async fn fetch_vault_flow_stats(
+    state: &AppState,
+    vault_id: &str,
+    timeframe: Timeframe,
+) -> Result<FlowStatsDTO, ApiError> {
+    let since = timeframe
+        .to_days()
+        .map(|days| Utc::now() - Duration::days(days));
+    let vault_id_clone = vault_id.to_string();
+    let totals: VaultFlowTotals = state
+        .pool
+        .interact_with_context(
+            format!("aggregate flow totals of vault: {vault_id}"),
+            move |conn| UserTransaction::vault_flow_totals(&vault_id_clone, since, conn),
+        )
+        .await?;
+
+    Ok(FlowStatsDTO {
+        timeframe,
+        deposits: totals.deposits.to_string(),
+        redemptions: totals.redemptions.to_string(),
+        net_flows: (totals.deposits - totals.redemptions).to_string(),
+        unique_depositors: totals.unique_depositors,
+        new_users: totals.new_users,
+        returning_users: totals.unique_depositors - totals.new_users,
+    })
+}
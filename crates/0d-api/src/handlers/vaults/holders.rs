@@ -0,0 +1,181 @@
+use axum::{
+    Json,
+    extract::{OriginalUri, Path, Query, State},
+    response::{IntoResponse, Response},
+};
+use chrono::{Duration, Utc};
+use rust_decimal::{Decimal, dec, prelude::ToPrimitive};
+
+use zerod_db::{
+    ZerodPool,
+    models::{HolderCursor, UserPortfolioHistory, UserPosition},
+    types::{HolderSort, SortOrder, Timeframe},
+};
+
+use crate::{
+    AppState,
+    dto::{
+        ApiResponse, HolderCountPoint, TimeframeQuery, VaultHolder, VaultHolderConcentration,
+        VaultHoldersQuery, VaultHoldersResponse,
+    },
+    errors::ApiError,
+    helpers::{call_vault_backend, fetch_vault, fetch_vault_with_client},
+    pagination::{Page, PaginationQuery, paginated},
+};
+
+#[utoipa::path(
+    get,
+    path = "/vaults/{vault_id}/holders",
+    tag = "Vaults",
+    params(
+        ("vault_id" = String, Path, description = "Vault identifier"),
+        ("sort_by" = Option<HolderSort>, Query, description = "Sort key (share_balance, first_deposit_at)"),
+        ("order" = Option<SortOrder>, Query, description = "Sort order (asc, desc), defaults to desc"),
+        PaginationQuery
+    ),
+    responses(
+        (status = 200, description = "Holders of the vault", body = VaultHoldersResponse),
+        (status = 400, description = "Invalid cursor"),
+        (status = 404, description = "Vault not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_vault_holders(
+    State(state): State<AppState>,
+    Path(vault_id): Path<String>,
+    Query(query): Query<VaultHoldersQuery>,
+    Query(pagination): Query<PaginationQuery>,
+    OriginalUri(uri): OriginalUri,
+) -> Result<Response, ApiError> {
+    let limit = pagination.limit();
+    let sorting = (query.sort_by, query.order);
+    let after: Option<HolderCursor> = pagination.after_filtered(&sorting)?;
+
+    let (vault, client) = fetch_vault_with_client(&state, &vault_id).await?;
+    let info = call_vault_backend(&client, &vault, "fetch vault info", |backend| async move {
+        backend.get_vault_info().await
+    })
+    .await?;
+    let share_price_usd = info.share_price_in_usd.parse::<Decimal>().map_err(|e| {
+        tracing::error!(
+            "Failed to parse share price '{}': {e}",
+            info.share_price_in_usd
+        );
+        ApiError::InternalServerError
+    })?;
+
+    let vault_id_clone = vault_id.clone();
+    let (positions, concentration) = state
+        .pool
+        .interact_with_context(format!("fetch holders of vault: {vault_id}"), move |conn| {
+            let positions = UserPosition::find_holders(
+                &vault_id_clone,
+                query.sort_by,
+                query.order,
+                after.as_ref(),
+                limit + 1, // Get one extra to determine if there's a next page
+                conn,
+            )?;
+            let concentration = UserPosition::holder_concentration(&vault_id_clone, conn)?;
+            Ok::<_, diesel::result::Error>((positions, concentration))
+        })
+        .await?;
+
+    let page = Page::from_filtered(positions, limit, &sorting, |position| {
+        HolderCursor::from(position)
+    })
+    .map(|position| VaultHolder {
+        value_usd: (position.share_balance * share_price_usd).to_string(),
+        share_of_supply_pct: pct(position.share_balance, concentration.total_shares),
+        share_balance: position.share_balance.to_string(),
+        user_address: position.user_address,
+        first_deposit_at: position.first_deposit_at,
+        last_activity_at: position.last_activity_at,
+    });
+    let next_cursor = page.next_cursor.clone();
+
+    Ok(paginated(
+        VaultHoldersResponse {
+            vault_id,
+            total_holders: concentration.holders,
+            total_shares: concentration.total_shares.to_string(),
+            share_price_usd: share_price_usd.to_string(),
+            page,
+        },
+        &uri,
+        next_cursor.as_deref(),
+    ))
+}
+
+#[utoipa::path(
+    get,
+    path = "/vaults/{vault_id}/holders/concentration",
+    tag = "Vaults",
+    params(
+        ("vault_id" = String, Path, description = "Vault identifier"),
+        ("timeframe" = Option<Timeframe>, Query, description = "Time period of the holder count series (7d, 30d, 1y, all)")
+    ),
+    responses(
+        (status = 200, description = "Concentration of the vault shares among its holders", body = VaultHolderConcentration),
+        (status = 404, description = "Vault not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_vault_holder_concentration(
+    State(state): State<AppState>,
+    Path(vault_id): Path<String>,
+    Query(query): Query<TimeframeQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    fetch_vault(&state, &vault_id).await?;
+
+    let since = query
+        .timeframe
+        .to_days()
+        .map(|days| Utc::now() - Duration::days(days));
+    let vault_id_clone = vault_id.clone();
+    let (concentration, buckets) = state
+        .pool
+        .interact_with_context(
+            format!("compute holder concentration of vault: {vault_id}"),
+            move |conn| {
+                let concentration = UserPosition::holder_concentration(&vault_id_clone, conn)?;
+                let buckets =
+                    UserPortfolioHistory::holder_count_series(&vault_id_clone, since, conn)?;
+                Ok::<_, diesel::result::Error>((concentration, buckets))
+            },
+        )
+        .await?;
+
+    let herfindahl_index = concentration.herfindahl_index();
+    let series = buckets
+        .iter()
+        .map(|bucket| HolderCountPoint {
+            t: bucket.bucket,
+            holders: bucket.holders,
+            herfindahl_index: bucket.herfindahl_index().to_f64().unwrap_or_default(),
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::ok(VaultHolderConcentration {
+        vault_id,
+        holders: concentration.holders,
+        total_shares: concentration.total_shares.to_string(),
+        top_10_share_pct: (concentration.top_10_share() * dec!(100))
+            .to_f64()
+            .unwrap_or_default(),
+        herfindahl_index: herfindahl_index.to_f64().unwrap_or_default(),
+        effective_holders: (!herfindahl_index.is_zero())
+            .then(|| (Decimal::ONE / herfindahl_index).to_f64())
+            .flatten(),
+        timeframe: query.timeframe,
+        series,
+    })))
+}
+
+/// Percentage of `part` in `total`, 0 when `total` is zero
+fn pct(part: Decimal, total: Decimal) -> f64 {
+    if total.is_zero() {
+        return 0.0;
+    }
+    (part / total * dec!(100)).to_f64().unwrap_or_default()
+}
@@ -3,15 +3,15 @@ use axum::{
     extract::{Path, Query, State},
     response::IntoResponse,
 };
+use rust_decimal::prelude::ToPrimitive;
 
-use zerod_db::types::Timeframe;
-use zerod_master::KpisDTO;
+use zerod_db::{ZerodPool, models::VaultKpi, types::Timeframe};
 
 use crate::{
     AppState,
-    dto::{ApiResponse, TimeframeQuery},
+    dto::{ApiResponse, RiskMetrics, VaultKpisQuery, VaultKpisResponse},
     errors::ApiError,
-    helpers::{call_vault_backend, fetch_vault_with_client},
+    helpers::{call_vault_backend, fetch_vault_with_client, parse_risk_metrics},
 };
 
 #[utoipa::path(
@@ -20,10 +20,11 @@ use crate::{
     tag = "Vaults",
     params(
         ("vault_id" = String, Path, description = "Vault identifier"),
-        ("timeframe" = Timeframe, Query, description = "Time period for KPI calculation", example = "all")
+        ("timeframe" = Timeframe, Query, description = "Time period for KPI calculation", example = "all"),
+        ("metrics" = Option<String>, Query, description = "Comma-separated risk metrics to return (defaults to all)", example = "volatility,var_historical,cvar")
     ),
     responses(
-        (status = 200, description = "Vault performance KPIs", body = KpisDTO),
+        (status = 200, description = "Vault performance KPIs", body = VaultKpisResponse),
         (status = 400, description = "Invalid parameters"),
         (status = 404, description = "Vault not found"),
         (status = 500, description = "Internal server error")
@@ -32,10 +33,12 @@ use crate::{
 pub async fn get_vault_kpis(
     State(state): State<AppState>,
     Path(vault_id): Path<String>,
-    Query(params): Query<TimeframeQuery>,
+    Query(params): Query<VaultKpisQuery>,
 ) -> Result<impl IntoResponse, ApiError> {
     tracing::info!("Getting vault KPIs for {:?}", params);
 
+    let selected_metrics = parse_risk_metrics(params.metrics.as_deref())?;
+
     let (vault, client) = fetch_vault_with_client(&state, &vault_id).await?;
     let timeframe = params.timeframe.as_str().to_owned();
     let kpis = call_vault_backend(
@@ -46,5 +49,33 @@ pub async fn get_vault_kpis(
     )
     .await?;
 
-    Ok(Json(ApiResponse::ok(kpis)))
+    // Risk analytics are computed daily by the KPI service on the share price history
+    let vault_id_clone = vault_id.clone();
+    let vault_kpi = state
+        .pool
+        .interact_with_context(
+            format!("fetch risk KPIs for vault: {vault_id}"),
+            move |conn| VaultKpi::find_by_vault(&vault_id_clone, conn),
+        )
+        .await?;
+
+    let response = VaultKpisResponse {
+        kpis,
+        risk_metrics: vault_kpi.as_ref().map(|kpi| {
+            RiskMetrics::from_selection(&selected_metrics, |metric| {
+                kpi.get_risk_metric_value(metric)
+            })
+        }),
+        risk_free_rate: vault_kpi
+            .as_ref()
+            .and_then(|kpi| kpi.risk_free_rate)
+            .and_then(|rate| rate.to_f64()),
+        periods_per_year: vault_kpi
+            .as_ref()
+            .and_then(|kpi| kpi.periods_per_year)
+            .and_then(|periods| periods.to_f64()),
+        risk_calculated_at: vault_kpi.and_then(|kpi| kpi.calculated_at),
+    };
+
+    Ok(Json(ApiResponse::ok(response)))
 }
@@ -1,11 +1,15 @@
-use axum::{Json, extract::State, response::IntoResponse};
+use axum::{
+    extract::{OriginalUri, Query, State},
+    response::Response,
+};
 use futures::future::try_join_all;
 
 use crate::{
     AppState,
-    dto::{ApiResponse, VaultListItem, VaultListResponse},
+    dto::VaultListItem,
     errors::ApiError,
     helpers::{VaultBackendClient, call_vault_backend, map_status},
+    pagination::{Page, PaginationQuery, paginated},
 };
 use zerod_db::{ZerodPool, models::Vault};
 
@@ -13,18 +17,30 @@ use zerod_db::{ZerodPool, models::Vault};
     get,
     path = "/vaults",
     tag = "Vaults",
+    params(PaginationQuery),
     responses(
-        (status = 200, description = "Vault list", body = VaultListResponse),
+        (status = 200, description = "Vault list, by id", body = Page<VaultListItem>),
+        (status = 400, description = "Invalid cursor"),
         (status = 500, description = "Internal server error")
     )
 )]
-pub async fn list_vaults(State(state): State<AppState>) -> Result<impl IntoResponse, ApiError> {
+pub async fn list_vaults(
+    State(state): State<AppState>,
+    Query(pagination): Query<PaginationQuery>,
+    OriginalUri(uri): OriginalUri,
+) -> Result<Response, ApiError> {
+    let limit = pagination.limit();
+    let after: Option<String> = pagination.after()?;
     let vaults = state
         .pool
-        .interact_with_context("fetch all vaults".to_string(), Vault::find_all)
+        .interact_with_context("fetch vaults page".to_string(), move |conn| {
+            Vault::find_page(after.as_deref(), limit + 1, conn)
+        })
         .await?;
+    // Only the vaults of the page are queried for their stats
+    let Page { items, next_cursor } = Page::from_fetched(vaults, limit, |vault| vault.id.clone());
 
-    let fetch_futures = vaults.into_iter().map(|vault| async move {
+    let fetch_futures = items.into_iter().map(|vault| async move {
         let client = VaultBackendClient::new(&vault)?;
         if let Ok(stats) =
             call_vault_backend(&client, &vault, "fetch vault stats", |backend| async move {
@@ -64,5 +80,12 @@ pub async fn list_vaults(State(state): State<AppState>) -> Result<impl IntoRespo
 
     let items = try_join_all(fetch_futures).await?;
 
-    Ok(Json(ApiResponse::ok(VaultListResponse { items })))
+    Ok(paginated(
+        Page {
+            items,
+            next_cursor: next_cursor.clone(),
+        },
+        &uri,
+        next_cursor.as_deref(),
+    ))
 }
@@ -1,21 +1,29 @@
 pub mod apr;
+pub mod compare;
 pub mod composition;
+pub mod flows;
 pub mod get;
+pub mod holders;
 pub mod kpis;
 pub mod liquidity;
 pub mod list;
 pub mod misc;
+pub mod redeems;
 pub mod share_price;
 pub mod stats;
 pub mod timeseries;
 
 pub use apr::{get_vault_apr_series, get_vault_apr_summary};
+pub use compare::compare_vaults;
 pub use composition::{get_vault_composition, get_vault_composition_series};
+pub use flows::get_vault_flows;
 pub use get::get_vault;
+pub use holders::{get_vault_holder_concentration, get_vault_holders};
 pub use kpis::get_vault_kpis;
 pub use liquidity::{get_vault_liquidity, get_vault_slippage_curve, simulate_vault_liquidity};
 pub use list::list_vaults;
 pub use misc::{get_vault_caps, get_vault_info, get_vault_nav_latest};
+pub use redeems::get_vault_redeems;
 pub use share_price::get_vault_share_price_series;
 pub use stats::get_vault_stats;
 pub use timeseries::get_vault_timeseries;
@@ -0,0 +1,112 @@
+use axum::{
+    Json,
+    extract::{Path, Query, State},
+    response::IntoResponse,
+};
+use chrono::{Duration, Utc};
+use rust_decimal::Decimal;
+
+use zerod_db::{ZerodPool, models::RedeemRequest, types::Timeframe};
+
+use crate::{
+    AppState,
+    dto::{
+        ApiResponse, RedeemLatencyStats, RedeemQueueEpoch, TimeframeQuery, VaultRedeemsResponse,
+    },
+    errors::ApiError,
+    helpers::{call_vault_backend, fetch_vault_with_client},
+};
+
+#[utoipa::path(
+    get,
+    path = "/vaults/{vault_id}/redeems",
+    tag = "Vaults",
+    params(
+        ("vault_id" = String, Path, description = "Vault identifier"),
+        ("timeframe" = Option<Timeframe>, Query, description = "Period of the claimed redemptions used for the latency (7d, 30d, 1y, all)")
+    ),
+    responses(
+        (status = 200, description = "Pending redemption queue of the vault per epoch", body = VaultRedeemsResponse),
+        (status = 404, description = "Vault not found"),
+        (status = 500, description = "Internal server error")
+    )
+)]
+pub async fn get_vault_redeems(
+    State(state): State<AppState>,
+    Path(vault_id): Path<String>,
+    Query(query): Query<TimeframeQuery>,
+) -> Result<impl IntoResponse, ApiError> {
+    let (vault, client) = fetch_vault_with_client(&state, &vault_id).await?;
+    let info = call_vault_backend(&client, &vault, "fetch vault info", |backend| async move {
+        backend.get_vault_info().await
+    })
+    .await?;
+    let current_epoch = info.current_epoch.parse::<i64>().map_err(|e| {
+        tracing::error!(
+            "Failed to parse current epoch '{}': {e}",
+            info.current_epoch
+        );
+        ApiError::InternalServerError
+    })?;
+    let buffer = info.buffer.parse::<Decimal>().map_err(|e| {
+        tracing::error!("Failed to parse buffer '{}': {e}", info.buffer);
+        ApiError::InternalServerError
+    })?;
+
+    let since = query
+        .timeframe
+        .to_days()
+        .map(|days| Utc::now() - Duration::days(days));
+    let vault_id_clone = vault_id.clone();
+    let (queue, latency) = state
+        .pool
+        .interact_with_context(
+            format!("fetch redemption queue of vault: {vault_id}"),
+            move |conn| {
+                let queue = RedeemRequest::queue_by_epoch(&vault_id_clone, conn)?;
+                let latency = RedeemRequest::claim_latency(&vault_id_clone, since, conn)?;
+                Ok::<_, diesel::result::Error>((queue, latency))
+            },
+        )
+        .await?;
+
+    let total_pending_shares: Decimal = queue.iter().map(|epoch| epoch.shares).sum();
+    let total_pending_assets: Decimal = queue.iter().map(|epoch| epoch.assets).sum();
+    // Requests of the current epoch and the ones left over are settled by the next report
+    let next_epoch_liquidity_need: Decimal = queue
+        .iter()
+        .filter(|epoch| epoch.epoch <= current_epoch)
+        .map(|epoch| epoch.assets)
+        .sum();
+    let next_epoch_shortfall = (next_epoch_liquidity_need - buffer).max(Decimal::ZERO);
+
+    let epochs = queue
+        .into_iter()
+        .map(|epoch| RedeemQueueEpoch {
+            epoch: epoch.epoch,
+            requests: epoch.requests,
+            users: epoch.users,
+            shares: epoch.shares.to_string(),
+            assets: epoch.assets.to_string(),
+            oldest_requested_at: epoch.oldest_requested_at,
+        })
+        .collect();
+
+    Ok(Json(ApiResponse::ok(VaultRedeemsResponse {
+        vault_id,
+        underlying_currency: info.underlying_currency,
+        current_epoch,
+        total_pending_shares: total_pending_shares.to_string(),
+        total_pending_assets: total_pending_assets.to_string(),
+        next_epoch_liquidity_need: next_epoch_liquidity_need.to_string(),
+        buffer: buffer.to_string(),
+        next_epoch_shortfall: next_epoch_shortfall.to_string(),
+        epochs,
+        latency: RedeemLatencyStats {
+            timeframe: query.timeframe,
+            claimed: latency.claimed,
+            p50_seconds: latency.p50_seconds,
+            p90_seconds: latency.p90_seconds,
+        },
+    })))
+}
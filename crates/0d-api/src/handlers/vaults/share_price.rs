@@ -1,17 +1,20 @@
 use axum::{
-    Json,
-    extract::{Path, Query, State},
-    response::IntoResponse,
+    extract::{OriginalUri, Path, Query, State},
+    response::Response,
 };
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use serde::Deserialize;
 
 use zerod_db::ZerodPool;
 use zerod_db::models::UserPortfolioHistory;
 use zerod_db::types::Timeframe;
-use zerod_master::{TimeseriesPoint, TimeseriesResponseDTO};
 
-use crate::{AppState, dto::ApiResponse, errors::ApiError};
+use crate::{
+    AppState,
+    dto::{SharePriceSeriesResponse, TimeseriesPoint},
+    errors::ApiError,
+    pagination::{Page, PaginationQuery, paginated},
+};
 
 #[derive(Debug, Deserialize)]
 pub struct SharePriceSeriesQuery {
@@ -25,10 +28,12 @@ pub struct SharePriceSeriesQuery {
     tag = "Vaults",
     params(
         ("vault_id" = String, Path, description = "Vault identifier"),
-        ("timeframe" = Option<Timeframe>, Query, description = "Time period", example = "all")
+        ("timeframe" = Option<Timeframe>, Query, description = "Time period", example = "all"),
+        PaginationQuery
     ),
     responses(
-        (status = 200, description = "Share price time series", body = TimeseriesResponseDTO),
+        (status = 200, description = "Share price time series", body = SharePriceSeriesResponse),
+        (status = 400, description = "Invalid cursor"),
         (status = 404, description = "Vault not found"),
         (status = 500, description = "Internal server error")
     )
@@ -37,22 +42,39 @@ pub async fn get_vault_share_price_series(
     State(state): State<AppState>,
     Path(vault_id): Path<String>,
     Query(params): Query<SharePriceSeriesQuery>,
-) -> Result<impl IntoResponse, ApiError> {
+    Query(pagination): Query<PaginationQuery>,
+    OriginalUri(uri): OriginalUri,
+) -> Result<Response, ApiError> {
     let since = params
         .timeframe
         .to_days()
         .map(|days| Utc::now() - chrono::Duration::days(days));
+    let limit = pagination.limit();
+    let after: Option<DateTime<Utc>> = pagination.after_filtered(&params.timeframe)?;
 
     let vault_id_clone = vault_id.clone();
     let points = state
         .pool
         .interact_with_context(
             format!("fetch share price series for vault: {vault_id}"),
-            move |conn| UserPortfolioHistory::get_share_price_series(&vault_id_clone, since, conn),
+            move |conn| {
+                UserPortfolioHistory::get_share_price_series_page(
+                    &vault_id_clone,
+                    since,
+                    after,
+                    limit + 1, // Get one extra to determine if there's a next page
+                    conn,
+                )
+            },
         )
         .await?;
 
-    let response = TimeseriesResponseDTO {
+    let Page {
+        items: points,
+        next_cursor,
+    } = Page::from_filtered(points, limit, &params.timeframe, |(ts, _)| *ts);
+
+    let response = SharePriceSeriesResponse {
         metric: "share_price".to_string(),
         timeframe: params.timeframe.as_str().to_string(),
         points: points
@@ -62,7 +84,8 @@ pub async fn get_vault_share_price_series(
                 v: price.to_string(),
             })
             .collect(),
+        next_cursor: next_cursor.clone(),
     };
 
-    Ok(Json(ApiResponse::ok(response)))
+    Ok(paginated(response, &uri, next_cursor.as_deref()))
 }
@@ -4,12 +4,14 @@ use axum::{
     response::IntoResponse,
 };
 
+use zerod_db::types::Timeframe;
 use zerod_master::GetStatsDTO;
 
 use crate::{
     AppState,
     dto::ApiResponse,
     errors::ApiError,
+    handlers::vaults::flows::fetch_vault_flow_stats,
     helpers::{call_vault_backend, fetch_vault_with_client},
 };
 
@@ -31,11 +33,16 @@ pub async fn get_vault_stats(
     Path(vault_id): Path<String>,
 ) -> Result<impl IntoResponse, ApiError> {
     let (vault, client) = fetch_vault_with_client(&state, &vault_id).await?;
-    let vault_stats =
+    let mut vault_stats =
         call_vault_backend(&client, &vault, "fetch vault stats", |backend| async move {
             backend.get_vault_stats().await
         })
         .await?;
 
+    if vault_stats.flows.is_none() {
+        vault_stats.flows =
+            Some(fetch_vault_flow_stats(&state, &vault_id, Timeframe::ThirtyDays).await?);
+    }
+
     Ok(Json(ApiResponse::ok(vault_stats)))
 }
@@ -4,14 +4,17 @@ use axum::{
     response::IntoResponse,
 };
 
+use chrono::DateTime;
+use rust_decimal::Decimal;
+
 use zerod_db::types::{Currency, Metric, Timeframe};
-use zerod_master::TimeseriesResponseDTO;
+use zerod_master::{TimeseriesPoint, TimeseriesResponseDTO};
 
 use crate::{
     AppState,
     dto::{ApiResponse, TimeseriesQuery},
     errors::ApiError,
-    helpers::{call_vault_backend, fetch_vault_with_client},
+    helpers::{call_vault_backend, fetch_vault_with_client, quote_series_to_currency},
 };
 
 #[utoipa::path(
@@ -39,18 +42,51 @@ pub async fn get_vault_timeseries(
     let (vault, client) = fetch_vault_with_client(&state, &vault_id).await?;
     let metric = params.metric.as_str().to_owned();
     let timeframe = params.timeframe.as_str().to_owned();
-    let currency = params.currency.to_string();
-    let timeseries =
-        call_vault_backend(&client, &vault, "fetch vault timeseries", move |backend| {
-            let metric = metric.clone();
-            let timeframe = timeframe.clone();
-            async move {
-                backend
-                    .get_vault_timeseries(&metric, &timeframe, &currency)
-                    .await
+    // Vault backends only quote in USD, other currencies are converted here
+    let mut timeseries = call_vault_backend(
+        &client,
+        &vault,
+        "fetch vault timeseries",
+        |backend| async move {
+            backend
+                .get_vault_timeseries(&metric, &timeframe, Currency::USD.as_ref())
+                .await
+        },
+    )
+    .await?;
+
+    if params.currency != Currency::USD {
+        timeseries.points = convert_points(timeseries.points, params.currency).await?;
+    }
+
+    Ok(Json(ApiResponse::ok(timeseries)))
+}
+
+/// Convert USD points to a currency, each at the rate of its own timestamp
+async fn convert_points(
+    points: Vec<TimeseriesPoint>,
+    currency: Currency,
+) -> Result<Vec<TimeseriesPoint>, ApiError> {
+    let series = points
+        .iter()
+        .map(|point| {
+            let t = DateTime::parse_from_rfc3339(&point.t).map(|t| t.to_utc());
+            let v = point.v.parse::<Decimal>();
+            if let (Ok(t), Ok(v)) = (t, v) {
+                Ok((t, v))
+            } else {
+                tracing::error!("Invalid timeseries point from vault backend: {point:?}");
+                Err(ApiError::InternalServerError)
             }
         })
-        .await?;
+        .collect::<Result<Vec<_>, _>>()?;
 
-    Ok(Json(ApiResponse::ok(timeseries)))
+    Ok(quote_series_to_currency(series, currency)
+        .await?
+        .into_iter()
+        .map(|point| TimeseriesPoint {
+            t: point.t.to_rfc3339(),
+            v: point.v,
+        })
+        .collect())
 }
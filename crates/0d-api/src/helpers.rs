@@ -1,15 +1,18 @@
-use std::{future::Future, sync::Arc};
+use std::{collections::BTreeMap, future::Future, str::FromStr, sync::Arc};
 
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use zerod_db::{
     ZerodPool,
-    models::{IndexerState, Vault},
+    models::{IndexerState, UserTransaction, Vault},
+    types::RiskMetric,
 };
 use zerod_master::{JaffarClient, MasterApiError, VaultMasterClient, VesuClient};
 use zerod_quoting::currencies::{CURRENCIES_PRICES, Currency};
 
 use crate::{
     AppState,
+    dto::HistoricalDataPoint,
     errors::{ApiError, DatabaseErrorExt},
 };
 
@@ -23,6 +26,26 @@ pub fn normalize_address(addr: &str) -> String {
     )
 }
 
+/// Parse a comma-separated risk metrics selector, defaulting to all metrics
+pub fn parse_risk_metrics(metrics: Option<&str>) -> Result<Vec<RiskMetric>, ApiError> {
+    let Some(metrics) = metrics.filter(|m| !m.trim().is_empty()) else {
+        return Ok(RiskMetric::ALL.to_vec());
+    };
+
+    metrics
+        .split(',')
+        .map(|m| m.trim().parse::<RiskMetric>().map_err(ApiError::BadRequest))
+        .collect()
+}
+
+/// Link to a transaction on the block explorer of its chain
+pub fn explorer_tx_url(chain: &str, tx_hash: &str) -> Option<String> {
+    match chain {
+        "starknet" => Some(format!("https://voyager.online/tx/{tx_hash}")),
+        _ => None,
+    }
+}
+
 pub fn map_status(status: &str) -> String {
     match status {
         "active" => "live".to_string(),
@@ -184,16 +207,91 @@ pub async fn validate_indexer_status(
     Ok(())
 }
 
-/// Quote an amount to a target currencys
+/// Quote a USD amount in a target currency
 pub async fn quote_to_currency(
     amount: Decimal,
     target_currency: Currency,
 ) -> Result<Decimal, ApiError> {
-    // Get the price of the target currency in USD
-    let price = CURRENCIES_PRICES.of(target_currency).await.map_err(|e| {
-        tracing::error!("Failed to fetch price for {target_currency:?}: {e}");
-        ApiError::InternalServerError
-    })?;
+    convert_currency(amount, Currency::USD, target_currency).await
+}
+
+/// Convert an amount between any two supported currencies, crossing through USD
+pub async fn convert_currency(
+    amount: Decimal,
+    from: Currency,
+    to: Currency,
+) -> Result<Decimal, ApiError> {
+    CURRENCIES_PRICES
+        .convert(amount, from, to)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to convert {from} to {to}: {e:#}");
+            ApiError::InternalServerError
+        })
+}
+
+/// Quote a USD time series in a target currency, each point at the rate of its own timestamp
+pub async fn quote_series_to_currency(
+    series: Vec<(DateTime<Utc>, Decimal)>,
+    target_currency: Currency,
+) -> Result<Vec<HistoricalDataPoint>, ApiError> {
+    let values = CURRENCIES_PRICES
+        .convert_series(&series, Currency::USD, target_currency)
+        .await
+        .map_err(|e| {
+            tracing::error!("Failed to convert series to {target_currency}: {e:#}");
+            ApiError::InternalServerError
+        })?;
+
+    Ok(series
+        .into_iter()
+        .zip(values)
+        .map(|((t, _), v)| HistoricalDataPoint {
+            t,
+            v: v.to_string(),
+        })
+        .collect())
+}
+
+/// Value the amounts of transactions in USD at the time of each transaction.
+/// Amounts in a currency that can't be priced are left out.
+pub async fn value_at_transaction_time(
+    transactions: &[UserTransaction],
+    vaults: &[Vault],
+) -> Vec<Option<Decimal>> {
+    let mut by_vault: BTreeMap<&str, Vec<usize>> = BTreeMap::new();
+    for (i, tx) in transactions.iter().enumerate() {
+        by_vault.entry(&tx.vault_id).or_default().push(i);
+    }
+
+    let mut values = vec![None; transactions.len()];
+    for (vault_id, indexes) in by_vault {
+        let Some(currency) = vaults
+            .iter()
+            .find(|vault| vault.id == vault_id)
+            .and_then(|vault| Currency::from_str(&vault.base_asset).ok())
+        else {
+            continue;
+        };
+        let points: Vec<(DateTime<Utc>, Decimal)> = indexes
+            .iter()
+            .map(|&i| (transactions[i].block_timestamp, transactions[i].amount))
+            .collect();
+
+        match CURRENCIES_PRICES
+            .convert_series(&points, currency, Currency::USD)
+            .await
+        {
+            Ok(converted) => {
+                for (i, value) in indexes.into_iter().zip(converted) {
+                    values[i] = Some(value);
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Failed to value transactions of vault {vault_id} in USD: {e:#}");
+            }
+        }
+    }
 
-    Ok(amount / price)
+    values
 }
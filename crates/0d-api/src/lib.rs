@@ -1,9 +1,12 @@
+pub mod csv;
+pub mod cursor;
 pub mod docs;
 pub mod dto;
 pub mod errors;
 pub mod handlers;
 pub mod helpers;
 pub mod middleware;
+pub mod pagination;
 pub mod router;
 
 use std::collections::HashSet;
@@ -21,6 +24,7 @@ use tower_governor::key_extractor::SmartIpKeyExtractor;
 use tower_http::cors::{AllowHeaders, AllowMethods, AllowOrigin, CorsLayer};
 
 use pragma_common::services::{Service, ServiceRunner};
+use zerod_types::EventBus;
 
 use docs::ApiDoc;
 use middleware::RateLimitConfig;
@@ -29,6 +33,7 @@ use router::api_router;
 #[derive(Clone)]
 pub struct AppState {
     pub pool: Pool,
+    pub events: EventBus,
 }
 
 pub struct ApiService {
@@ -104,6 +109,7 @@ fn cors_layer_from_env() -> CorsLayer {
 impl Service for ApiService {
     async fn start<'a>(&mut self, mut runner: ServiceRunner<'a>) -> anyhow::Result<()> {
         ApiDoc::generate_openapi_json("./".into())?;
+        cursor::ensure_key()?;
 
         let host = self.host.clone();
         let port = self.port;
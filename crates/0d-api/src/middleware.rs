@@ -11,6 +11,8 @@ use std::sync::Arc;
 use tower_governor::governor::SharedRateLimiter;
 use tower_governor::key_extractor::{KeyExtractor, SmartIpKeyExtractor};
 
+use crate::errors::ApiError;
+
 // Re-export timeout layer for use in lib.rs
 pub use tower_http::timeout::TimeoutLayer;
 
@@ -128,6 +130,52 @@ where
     }
 }
 
+/// API key required by the admin endpoints, read from `ADMIN_API_KEY`
+#[derive(Clone)]
+pub struct AdminAuth {
+    api_key: Arc<str>,
+}
+
+impl AdminAuth {
+    /// `None` when no key is configured, in which case the admin endpoints are disabled
+    pub fn from_env() -> Option<Self> {
+        std::env::var("ADMIN_API_KEY")
+            .ok()
+            .map(|key| key.trim().to_string())
+            .filter(|key| !key.is_empty())
+            .map(|key| Self {
+                api_key: key.into(),
+            })
+    }
+}
+
+/// Middleware rejecting the requests without an `Authorization: Bearer <ADMIN_API_KEY>` header
+pub async fn admin_auth_middleware(
+    auth: AdminAuth,
+    request: Request,
+    next: Next,
+) -> Result<Response, ApiError> {
+    let provided = request
+        .headers()
+        .get("authorization")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "));
+
+    match provided {
+        Some(key) if constant_time_eq(key.as_bytes(), auth.api_key.as_bytes()) => {
+            Ok(next.run(request).await)
+        }
+        _ => Err(ApiError::Unauthorized(
+            "Missing or invalid admin API key".to_string(),
+        )),
+    }
+}
+
+/// Compare two secrets without leaking where they differ through timing
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,4 +200,12 @@ mod tests {
         );
         assert_eq!(extract_domain(""), None);
     }
+
+    #[test]
+    fn test_constant_time_eq() {
+        assert!(constant_time_eq(b"secret", b"secret"));
+        assert!(!constant_time_eq(b"secret", b"secreT"));
+        assert!(!constant_time_eq(b"secret", b"secret2"));
+        assert!(!constant_time_eq(b"", b"secret"));
+    }
 }
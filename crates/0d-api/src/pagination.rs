@@ -0,0 +1,157 @@
+use axum::{
+    Json,
+    http::{HeaderValue, Uri, header},
+    response::{IntoResponse, Response},
+};
+use serde::{Deserialize, Serialize, de::DeserializeOwned};
+use utoipa::{IntoParams, ToSchema};
+
+use crate::{cursor, dto::ApiResponse, errors::ApiError};
+
+pub const DEFAULT_LIMIT: i64 = 50;
+pub const MAX_LIMIT: i64 = 200;
+
+/// Query parameters of the paginated list endpoints
+#[derive(Debug, Clone, Default, Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct PaginationQuery {
+    /// Number of items to return (1-200, default: 50)
+    pub limit: Option<i64>,
+    /// Opaque cursor of the page to return, the `next_cursor` of the previous page
+    pub cursor: Option<String>,
+}
+
+impl PaginationQuery {
+    pub fn limit(&self) -> i64 {
+        self.limit_within(DEFAULT_LIMIT, MAX_LIMIT)
+    }
+
+    /// Page size for endpoints with their own bounds
+    pub fn limit_within(&self, default: i64, max: i64) -> i64 {
+        self.limit.unwrap_or(default).clamp(1, max)
+    }
+
+    /// Decode the position the page starts after, `None` for the first page
+    pub fn after<K: DeserializeOwned>(&self) -> Result<Option<K>, ApiError> {
+        self.cursor.as_deref().map(cursor::decode).transpose()
+    }
+
+    /// Same as `after`, for a cursor handed out by `Page::from_filtered` with the same filters
+    pub fn after_filtered<K: DeserializeOwned, F: Serialize>(
+        &self,
+        filters: &F,
+    ) -> Result<Option<K>, ApiError> {
+        self.cursor
+            .as_deref()
+            .map(|cursor| cursor::decode_filtered(cursor, filters))
+            .transpose()
+    }
+}
+
+/// A page of a listing, along with the cursor of the next one
+#[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
+pub struct Page<T> {
+    #[schema(inline)]
+    pub items: Vec<T>,
+    /// Cursor of the next page, `None` on the last page
+    pub next_cursor: Option<String>,
+}
+
+impl<T> Page<T> {
+    /// Build a page out of up to `limit + 1` items fetched after the cursor, the extra item
+    /// telling whether there is a next page. `key` gives the (sort key, id) of an item.
+    pub fn from_fetched<K: Serialize>(
+        mut items: Vec<T>,
+        limit: i64,
+        key: impl FnOnce(&T) -> K,
+    ) -> Self {
+        let limit = usize::try_from(limit).unwrap_or_default();
+        let has_more = items.len() > limit;
+        items.truncate(limit);
+        let next_cursor = items
+            .last()
+            .filter(|_| has_more)
+            .map(|item| cursor::encode(&key(item)));
+
+        Self { items, next_cursor }
+    }
+
+    /// Same as `from_fetched`, binding the cursor of the next page to the filters of the
+    /// listing so that it can't be replayed with other ones
+    pub fn from_filtered<K: Serialize, F: Serialize>(
+        mut items: Vec<T>,
+        limit: i64,
+        filters: &F,
+        key: impl FnOnce(&T) -> K,
+    ) -> Self {
+        let limit = usize::try_from(limit).unwrap_or_default();
+        let has_more = items.len() > limit;
+        items.truncate(limit);
+        let next_cursor = items
+            .last()
+            .filter(|_| has_more)
+            .map(|item| cursor::encode_filtered(&key(item), filters));
+
+        Self { items, next_cursor }
+    }
+
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> Page<U> {
+        Page {
+            items: self.items.into_iter().map(f).collect(),
+            next_cursor: self.next_cursor,
+        }
+    }
+}
+
+/// Respond with a listing, linking to its next page with a `Link` header
+pub fn paginated<B: Serialize>(body: B, uri: &Uri, next_cursor: Option<&str>) -> Response {
+    let mut response = Json(ApiResponse::ok(body)).into_response();
+    if let Some(link) = next_cursor
+        .map(|cursor| format!("<{}>; rel=\"next\"", next_page_uri(uri, cursor)))
+        .and_then(|link| HeaderValue::from_str(&link).ok())
+    {
+        response.headers_mut().insert(header::LINK, link);
+    }
+    response
+}
+
+/// URI of the request with its cursor replaced by the one of the next page
+fn next_page_uri(uri: &Uri, cursor: &str) -> String {
+    let cursor_param = format!("cursor={cursor}");
+    let params: Vec<&str> = uri
+        .query()
+        .unwrap_or_default()
+        .split('&')
+        .filter(|param| !param.is_empty() && !param.starts_with("cursor="))
+        .chain(std::iter::once(cursor_param.as_str()))
+        .collect();
+
+    format!("{}?{}", uri.path(), params.join("&"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_page_from_fetched() {
+        let page = Page::from_fetched(vec![1, 2, 3], 2, |item| *item);
+        assert_eq!(page.items, vec![1, 2]);
+        assert_eq!(
+            cursor::decode::<i32>(&page.next_cursor.unwrap()).unwrap(),
+            2
+        );
+
+        let last = Page::from_fetched(vec![1, 2], 2, |item| *item);
+        assert!(last.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_next_page_uri() {
+        let uri: Uri = "/v1/vaults?limit=10&cursor=abc".parse().unwrap();
+        assert_eq!(next_page_uri(&uri, "def"), "/v1/vaults?limit=10&cursor=def");
+
+        let uri: Uri = "/v1/vaults".parse().unwrap();
+        assert_eq!(next_page_uri(&uri, "def"), "/v1/vaults?cursor=def");
+    }
+}
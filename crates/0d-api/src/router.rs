@@ -1,16 +1,18 @@
 use axum::Router;
 use axum::http::StatusCode;
+use axum::middleware::from_fn;
 use axum::response::IntoResponse;
 use axum::routing::{get, post};
 
 use utoipa::OpenApi as OpenApiT;
 use utoipa_swagger_ui::SwaggerUi;
 
-use crate::{AppState, handlers};
+use crate::{AppState, handlers, middleware::AdminAuth};
 
 fn create_vaults_router() -> Router<AppState> {
     Router::new()
         .route("/", get(handlers::list_vaults))
+        .route("/compare", get(handlers::compare_vaults))
         .route("/{vault_id}", get(handlers::get_vault))
         .route("/{vault_id}/stats", get(handlers::get_vault_stats))
         .route(
@@ -18,6 +20,13 @@ fn create_vaults_router() -> Router<AppState> {
             get(handlers::get_vault_timeseries),
         )
         .route("/{vault_id}/kpis", get(handlers::get_vault_kpis))
+        .route("/{vault_id}/flows", get(handlers::get_vault_flows))
+        .route("/{vault_id}/holders", get(handlers::get_vault_holders))
+        .route(
+            "/{vault_id}/holders/concentration",
+            get(handlers::get_vault_holder_concentration),
+        )
+        .route("/{vault_id}/redeems", get(handlers::get_vault_redeems))
         .route("/{vault_id}/liquidity", get(handlers::get_vault_liquidity))
         .route(
             "/{vault_id}/liquidity/curve",
@@ -49,6 +58,7 @@ fn create_vaults_router() -> Router<AppState> {
             get(handlers::get_vault_nav_latest),
         )
         .route("/{vault_id}/info", get(handlers::get_vault_info))
+        .route("/{vault_id}/events", get(handlers::stream_vault_events))
         .route(
             "/{vault_id}/share-price/series",
             get(handlers::get_vault_share_price_series),
@@ -58,10 +68,17 @@ fn create_vaults_router() -> Router<AppState> {
 fn create_users_router() -> Router<AppState> {
     Router::new()
         .route("/{address}", get(handlers::get_user_profile))
+        .route("/{address}/portfolio", get(handlers::get_user_portfolio))
+        .route("/{address}/events", get(handlers::stream_user_events))
         .route(
             "/{address}/redeems",
             get(handlers::get_user_pending_redeems),
         )
+        .route("/{address}/reports/tax", get(handlers::get_user_tax_report))
+        .route(
+            "/{address}/transactions",
+            get(handlers::get_user_transactions),
+        )
         .route(
             "/{address}/vaults/{vault_id}/summary",
             get(handlers::get_user_position_summary),
@@ -80,13 +97,81 @@ fn create_users_router() -> Router<AppState> {
         )
 }
 
+fn create_partners_router() -> Router<AppState> {
+    Router::new()
+        .route("/leaderboard", get(handlers::get_partner_leaderboard))
+        .route("/{partner_id}", get(handlers::get_partner_summary))
+        .route(
+            "/{partner_id}/users",
+            get(handlers::get_partner_referred_users),
+        )
+        .route("/{partner_id}/flows", get(handlers::get_partner_flows))
+}
+
+fn create_admin_router(auth: AdminAuth) -> Router<AppState> {
+    Router::new()
+        .route(
+            "/webhooks",
+            get(handlers::list_webhook_subscriptions).post(handlers::create_webhook_subscription),
+        )
+        .route(
+            "/webhooks/{id}",
+            get(handlers::get_webhook_subscription)
+                .patch(handlers::update_webhook_subscription)
+                .delete(handlers::delete_webhook_subscription),
+        )
+        .route(
+            "/webhooks/{id}/deliveries",
+            get(handlers::list_webhook_deliveries),
+        )
+        .route(
+            "/webhooks/deliveries/{delivery_id}/retry",
+            post(handlers::retry_webhook_delivery),
+        )
+        .route(
+            "/partners",
+            get(handlers::list_partners).post(handlers::create_partner),
+        )
+        .route(
+            "/partners/{partner_id}",
+            get(handlers::get_partner).patch(handlers::update_partner),
+        )
+        .route(
+            "/partners/{partner_id}/accruals",
+            get(handlers::list_partner_accruals),
+        )
+        .route(
+            "/payouts",
+            get(handlers::list_payout_batches).post(handlers::generate_payout_batches),
+        )
+        .route("/payouts/{batch_id}", get(handlers::get_payout_batch))
+        .route(
+            "/payouts/{batch_id}/paid",
+            post(handlers::mark_payout_batch_paid),
+        )
+        .route_layer(from_fn(move |req, next| {
+            crate::middleware::admin_auth_middleware(auth.clone(), req, next)
+        }))
+}
+
 pub fn api_router<T: OpenApiT>(_state: AppState) -> Router<AppState> {
     let open_api = T::openapi();
 
-    Router::new()
+    let router = Router::new()
         .route("/health", get(health))
+        .route("/v1/ws", get(handlers::stream_events))
         .nest("/v1/vaults", create_vaults_router())
         .nest("/v1/users", create_users_router())
+        .nest("/v1/partners", create_partners_router());
+
+    let router = if let Some(auth) = AdminAuth::from_env() {
+        router.nest("/v1/admin", create_admin_router(auth))
+    } else {
+        tracing::info!("ADMIN_API_KEY not set; admin endpoints disabled");
+        router
+    };
+
+    router
         .merge(SwaggerUi::new("/v1/docs").url("/v1/docs/openapi.json", open_api))
         .fallback(handler_404)
 }
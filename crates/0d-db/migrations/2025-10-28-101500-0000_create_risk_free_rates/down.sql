@@ -0,0 +1,3 @@
+ALTER TABLE user_kpis DROP COLUMN periods_per_year;
+ALTER TABLE user_kpis DROP COLUMN risk_free_rate;
+DROP TABLE IF EXISTS risk_free_rates;
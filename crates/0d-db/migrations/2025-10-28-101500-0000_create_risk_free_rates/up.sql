@@ -0,0 +1,19 @@
+CREATE TABLE risk_free_rates (
+    id SERIAL PRIMARY KEY,
+    base_asset VARCHAR(20) NOT NULL,
+    annual_rate DECIMAL(36, 18) NOT NULL,
+    effective_from TIMESTAMPTZ NOT NULL,
+    source VARCHAR(100),
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE(base_asset, effective_from)
+);
+
+CREATE INDEX idx_risk_free_rates_lookup ON risk_free_rates(base_asset, effective_from DESC);
+
+-- Seed with the rate that was previously hardcoded in the KPI service
+INSERT INTO risk_free_rates (base_asset, annual_rate, effective_from, source)
+VALUES ('USDC', 0.05, '2025-01-01T00:00:00Z', 'default');
+
+-- Parameters used for the risk metrics, exposed for transparency
+ALTER TABLE user_kpis ADD COLUMN risk_free_rate DECIMAL(36,18);
+ALTER TABLE user_kpis ADD COLUMN periods_per_year DECIMAL(36,18);
@@ -0,0 +1,10 @@
+DROP TABLE IF EXISTS vault_kpis;
+
+ALTER TABLE user_kpis DROP COLUMN recovery_time_days;
+ALTER TABLE user_kpis DROP COLUMN max_drawdown_duration_days;
+ALTER TABLE user_kpis DROP COLUMN omega_ratio;
+ALTER TABLE user_kpis DROP COLUMN calmar_ratio;
+ALTER TABLE user_kpis DROP COLUMN cvar_pct;
+ALTER TABLE user_kpis DROP COLUMN var_parametric_pct;
+ALTER TABLE user_kpis DROP COLUMN var_historical_pct;
+ALTER TABLE user_kpis DROP COLUMN volatility_pct;
@@ -0,0 +1,32 @@
+-- Extended risk analytics for user positions
+ALTER TABLE user_kpis ADD COLUMN volatility_pct DECIMAL(36,18);
+ALTER TABLE user_kpis ADD COLUMN var_historical_pct DECIMAL(36,18);
+ALTER TABLE user_kpis ADD COLUMN var_parametric_pct DECIMAL(36,18);
+ALTER TABLE user_kpis ADD COLUMN cvar_pct DECIMAL(36,18);
+ALTER TABLE user_kpis ADD COLUMN calmar_ratio DECIMAL(36,18);
+ALTER TABLE user_kpis ADD COLUMN omega_ratio DECIMAL(36,18);
+ALTER TABLE user_kpis ADD COLUMN max_drawdown_duration_days DECIMAL(36,18);
+ALTER TABLE user_kpis ADD COLUMN recovery_time_days DECIMAL(36,18);
+
+-- Risk analytics computed on the vault share price series
+CREATE TABLE vault_kpis (
+    id SERIAL PRIMARY KEY,
+    vault_id VARCHAR(50) NOT NULL REFERENCES vaults(id),
+    max_drawdown_pct DECIMAL(36,18),
+    sharpe_ratio DECIMAL(36,18),
+    sortino_ratio DECIMAL(36,18),
+    volatility_pct DECIMAL(36,18),
+    var_historical_pct DECIMAL(36,18),
+    var_parametric_pct DECIMAL(36,18),
+    cvar_pct DECIMAL(36,18),
+    calmar_ratio DECIMAL(36,18),
+    omega_ratio DECIMAL(36,18),
+    max_drawdown_duration_days DECIMAL(36,18),
+    recovery_time_days DECIMAL(36,18),
+    risk_free_rate DECIMAL(36,18),
+    periods_per_year DECIMAL(36,18),
+    calculated_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE(vault_id)
+);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS kpi_runs;
+DROP TABLE IF EXISTS user_risk_states;
@@ -0,0 +1,30 @@
+-- Running risk-metric accumulators so that each KPI run only appends the latest point
+CREATE TABLE user_risk_states (
+    id SERIAL PRIMARY KEY,
+    user_address VARCHAR(100) NOT NULL,
+    vault_id VARCHAR(50) NOT NULL,
+    state JSONB NOT NULL,
+    last_point_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE(user_address, vault_id)
+);
+
+CREATE INDEX idx_user_risk_states_vault ON user_risk_states(vault_id);
+
+-- Timing metrics of each KPI computation run
+CREATE TABLE kpi_runs (
+    id SERIAL PRIMARY KEY,
+    started_at TIMESTAMPTZ NOT NULL,
+    finished_at TIMESTAMPTZ NOT NULL,
+    vaults_processed INTEGER NOT NULL,
+    positions_processed INTEGER NOT NULL,
+    errors INTEGER NOT NULL,
+    fetch_ms BIGINT NOT NULL,
+    compute_ms BIGINT NOT NULL,
+    write_ms BIGINT NOT NULL,
+    total_ms BIGINT NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+CREATE INDEX idx_kpi_runs_started_at ON kpi_runs(started_at DESC);
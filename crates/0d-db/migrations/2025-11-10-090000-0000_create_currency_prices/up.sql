@@ -0,0 +1,13 @@
+-- USD price history of the supported currencies, used to convert time series
+-- at the rate of each point rather than at the current spot price
+CREATE TABLE currency_prices (
+    id SERIAL PRIMARY KEY,
+    currency VARCHAR(10) NOT NULL,
+    price DECIMAL(36, 18) NOT NULL,
+    sampled_at TIMESTAMPTZ NOT NULL,
+    source VARCHAR(32) NOT NULL,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE(currency, sampled_at)
+);
+
+CREATE INDEX idx_currency_prices_lookup ON currency_prices(currency, sampled_at DESC);
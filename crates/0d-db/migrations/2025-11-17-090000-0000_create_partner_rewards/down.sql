@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS partner_reward_accruals;
+DROP TABLE IF EXISTS partner_payout_batches;
+DROP TABLE IF EXISTS partners;
@@ -0,0 +1,49 @@
+-- Registry of the partners attributed to deposits, keyed by the `partner_id` of the deposits
+CREATE TABLE partners (
+    id VARCHAR(100) PRIMARY KEY,
+    display_name VARCHAR(100) NOT NULL,
+    payout_address VARCHAR(100) NOT NULL,
+    reward_share_bps INTEGER NOT NULL CHECK (reward_share_bps BETWEEN 0 AND 10000),
+    active_from TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    active_until TIMESTAMPTZ,
+    created_at TIMESTAMPTZ DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- Rewards owed to a partner, batched per partner for payment
+CREATE TABLE partner_payout_batches (
+    id SERIAL PRIMARY KEY,
+    partner_id VARCHAR(100) NOT NULL REFERENCES partners(id),
+    payout_address VARCHAR(100) NOT NULL,
+    period_start DATE NOT NULL,
+    period_end DATE NOT NULL,
+    accruals_count INTEGER NOT NULL,
+    total_reward_usd NUMERIC NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending',
+    tx_hash VARCHAR(100),
+    paid_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW()
+);
+
+-- Ledger of the rewards accrued daily by a partner on the positions it referred in a vault
+CREATE TABLE partner_reward_accruals (
+    id SERIAL PRIMARY KEY,
+    partner_id VARCHAR(100) NOT NULL REFERENCES partners(id),
+    vault_id VARCHAR(50) NOT NULL REFERENCES vaults(id),
+    accrual_date DATE NOT NULL,
+    referred_positions INTEGER NOT NULL,
+    referred_tvl_usd NUMERIC NOT NULL,
+    yield_usd NUMERIC NOT NULL,
+    management_fees_usd NUMERIC NOT NULL,
+    performance_fees_usd NUMERIC NOT NULL,
+    reward_share_bps INTEGER NOT NULL,
+    reward_usd NUMERIC NOT NULL,
+    payout_batch_id INTEGER REFERENCES partner_payout_batches(id),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (partner_id, vault_id, accrual_date)
+);
+
+CREATE INDEX idx_partner_reward_accruals_unbatched ON partner_reward_accruals(partner_id, accrual_date) WHERE payout_batch_id IS NULL;
+CREATE INDEX idx_partner_reward_accruals_batch ON partner_reward_accruals(payout_batch_id);
+CREATE INDEX idx_partner_payout_batches_partner ON partner_payout_batches(partner_id, created_at DESC);
@@ -0,0 +1,3 @@
+-- Vault flows are aggregated per UTC day or week of their block timestamp
+CREATE INDEX idx_user_tx_vault_flows ON user_transactions(vault_id, block_timestamp)
+    WHERE type IN ('deposit', 'withdraw');
@@ -0,0 +1,68 @@
+-- Lifecycle of the redemptions of a vault, from the request to the claim
+CREATE TABLE redeem_requests (
+    id SERIAL PRIMARY KEY,
+    vault_id VARCHAR(50) NOT NULL REFERENCES vaults(id),
+    redeem_id VARCHAR(100) NOT NULL,
+    user_address VARCHAR(100) NOT NULL REFERENCES users(address),
+    receiver VARCHAR(100) NOT NULL,
+    epoch BIGINT NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'claimed')),
+
+    -- Amounts
+    requested_shares DECIMAL(36, 18) NOT NULL,
+    nominal_assets DECIMAL(36, 18) NOT NULL, -- In base asset, at the share price of the request
+    claimed_assets DECIMAL(36, 18), -- In base asset, received on claim
+
+    request_tx_hash VARCHAR(100) NOT NULL,
+    request_block_number BIGINT NOT NULL,
+    requested_at TIMESTAMPTZ NOT NULL,
+    claim_tx_hash VARCHAR(100),
+    claim_block_number BIGINT,
+    claimed_at TIMESTAMPTZ,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ DEFAULT NOW(),
+    UNIQUE (vault_id, redeem_id)
+);
+
+CREATE INDEX idx_redeem_requests_user ON redeem_requests(user_address, vault_id);
+CREATE INDEX idx_redeem_requests_queue ON redeem_requests(vault_id, epoch)
+    WHERE status = 'pending';
+CREATE INDEX idx_redeem_requests_claims ON redeem_requests(vault_id, requested_at)
+    WHERE status = 'claimed';
+
+-- Move the redemptions recorded as withdraw transactions, whose redeem id and epoch are in
+-- their metadata. The claim overwrote the amount
+-- of the transaction, so the nominal of claimed ones comes back from the request share price.
+-- The claim block was not recorded and the claim time is the last update of the transaction.
+INSERT INTO redeem_requests (
+    vault_id, redeem_id, user_address, receiver, epoch, status,
+    requested_shares, nominal_assets, claimed_assets,
+    request_tx_hash, request_block_number, requested_at,
+    claim_tx_hash, claimed_at
+)
+SELECT
+    vault_id,
+    metadata->>'redeem_id',
+    user_address,
+    COALESCE(metadata->>'receiver', user_address),
+    CAST(metadata->>'epoch' AS BIGINT),
+    CASE WHEN status = 'confirmed' THEN 'claimed' ELSE 'pending' END,
+    COALESCE(shares_amount, 0),
+    CASE
+        WHEN status = 'confirmed' AND shares_amount IS NOT NULL AND share_price IS NOT NULL
+            THEN shares_amount * share_price
+        ELSE amount
+    END,
+    CASE WHEN status = 'confirmed' THEN amount END,
+    tx_hash,
+    block_number,
+    block_timestamp,
+    CASE WHEN status = 'confirmed' THEN metadata->>'claim_tx_hash' END,
+    CASE WHEN status = 'confirmed' THEN updated_at END
+FROM user_transactions
+WHERE type = 'withdraw'
+  AND status IN ('pending', 'confirmed')
+  AND metadata ? 'redeem_id'
+  AND metadata ? 'epoch'
+ON CONFLICT (vault_id, redeem_id) DO NOTHING;
@@ -0,0 +1,2 @@
+-- Keyset pagination of the transaction history of a user
+CREATE INDEX idx_user_tx_user_timeline ON user_transactions(user_address, block_timestamp, id);
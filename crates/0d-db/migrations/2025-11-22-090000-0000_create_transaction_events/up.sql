@@ -0,0 +1,59 @@
+-- Ordered log of the transaction events, replayed by the event streams on resume.
+-- A claim updates the row of its request in user_transactions, so it gets its own entry here.
+CREATE TABLE transaction_events (
+    id BIGSERIAL PRIMARY KEY,
+    transaction_id INTEGER NOT NULL REFERENCES user_transactions(id),
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('deposit', 'redeem_requested', 'redeem_claimed')),
+    vault_id VARCHAR(50) NOT NULL REFERENCES vaults(id),
+    user_address VARCHAR(100) NOT NULL REFERENCES users(address),
+    partner_id VARCHAR(100),
+    tx_hash VARCHAR(100) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    amount DECIMAL(36, 18) NOT NULL, -- In base asset
+    shares_amount DECIMAL(36, 18),
+    block_number BIGINT NOT NULL,
+    block_timestamp TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_transaction_events_user ON transaction_events(user_address, id);
+CREATE INDEX idx_transaction_events_vault ON transaction_events(vault_id, id);
+
+-- Rebuild the log of the existing transactions in the order they were indexed.
+-- Claimed redeems get back their request amount, and their claim is logged at its last update.
+INSERT INTO transaction_events (
+    transaction_id, kind, vault_id, user_address, partner_id, tx_hash, status,
+    amount, shares_amount, block_number, block_timestamp, created_at
+)
+SELECT
+    transaction_id, kind, vault_id, user_address, partner_id, tx_hash, status,
+    amount, shares_amount, block_number, block_timestamp, logged_at
+FROM (
+    SELECT
+        id AS transaction_id, 'deposit' AS kind, vault_id, user_address, partner_id, tx_hash,
+        status, amount, shares_amount, block_number, block_timestamp,
+        COALESCE(created_at, block_timestamp) AS logged_at
+    FROM user_transactions
+    WHERE type = 'deposit'
+    UNION ALL
+    SELECT
+        id, 'redeem_requested', vault_id, user_address, partner_id, tx_hash,
+        'pending',
+        CASE
+            WHEN status = 'confirmed' AND shares_amount IS NOT NULL AND share_price IS NOT NULL
+                THEN shares_amount * share_price
+            ELSE amount
+        END,
+        shares_amount, block_number, block_timestamp,
+        COALESCE(created_at, block_timestamp)
+    FROM user_transactions
+    WHERE type = 'withdraw'
+    UNION ALL
+    SELECT
+        id, 'redeem_claimed', vault_id, user_address, partner_id, tx_hash,
+        status, amount, shares_amount, block_number, block_timestamp,
+        COALESCE(updated_at, block_timestamp)
+    FROM user_transactions
+    WHERE type = 'withdraw' AND status = 'confirmed'
+) AS events
+ORDER BY logged_at, transaction_id, kind;
@@ -0,0 +1 @@
+-- The attribution of the withdrawals is kept, it is what the indexer now records
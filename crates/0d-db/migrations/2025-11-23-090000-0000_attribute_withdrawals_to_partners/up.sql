@@ -0,0 +1,21 @@
+-- Attribute the withdrawals to the partner the position was referred by,
+-- the partner of the first deposit made in the vault through a partner
+UPDATE user_transactions AS withdrawal
+SET partner_id = referral.partner_id
+FROM (
+    SELECT DISTINCT ON (user_address, vault_id) user_address, vault_id, partner_id
+    FROM user_transactions
+    WHERE type = 'deposit' AND partner_id IS NOT NULL
+    ORDER BY user_address, vault_id, block_timestamp, id
+) AS referral
+WHERE withdrawal.type = 'withdraw'
+  AND withdrawal.partner_id IS NULL
+  AND withdrawal.user_address = referral.user_address
+  AND withdrawal.vault_id = referral.vault_id;
+
+UPDATE transaction_events AS event
+SET partner_id = withdrawal.partner_id
+FROM user_transactions AS withdrawal
+WHERE event.transaction_id = withdrawal.id
+  AND withdrawal.type = 'withdraw'
+  AND event.partner_id IS NULL;
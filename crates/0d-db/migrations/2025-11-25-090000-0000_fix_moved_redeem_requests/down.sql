@@ -0,0 +1 @@
+-- The decimal receivers and the approximate claim times are not restored
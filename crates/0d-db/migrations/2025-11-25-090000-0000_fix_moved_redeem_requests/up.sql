@@ -0,0 +1,27 @@
+-- The receivers moved from the withdraw metadata were decimal felts, store them as hex
+-- addresses like the indexer does
+CREATE FUNCTION pg_temp.felt_to_hex(felt NUMERIC) RETURNS TEXT AS $$
+DECLARE
+    hex TEXT := '';
+BEGIN
+    IF felt = 0 THEN
+        RETURN '0x0';
+    END IF;
+    WHILE felt > 0 LOOP
+        hex := SUBSTR('0123456789abcdef', CAST(MOD(felt, 16) AS INTEGER) + 1, 1) || hex;
+        felt := DIV(felt, 16);
+    END LOOP;
+    RETURN '0x' || hex;
+END;
+$$ LANGUAGE plpgsql IMMUTABLE;
+
+UPDATE redeem_requests
+SET receiver = pg_temp.felt_to_hex(CAST(receiver AS NUMERIC))
+WHERE receiver ~ '^[0-9]+$';
+
+-- The claim time of the moved redemptions was the last update of their transaction, not the
+-- claim itself. It is unknown, which leaves them out of the claim latencies.
+UPDATE redeem_requests
+SET claimed_at = NULL
+WHERE status = 'claimed'
+  AND claim_block_number IS NULL;
@@ -0,0 +1,14 @@
+-- The claim overwrote the amount of the withdraw transactions moved to redeem_requests with
+-- the assets received. Withdraw transactions book the nominal of their request, the assets
+-- received are on the redeem request.
+UPDATE user_transactions t
+SET amount = r.nominal_assets,
+    updated_at = NOW()
+FROM redeem_requests r
+WHERE t.type = 'withdraw'
+  AND t.vault_id = r.vault_id
+  AND t.user_address = r.user_address
+  AND t.tx_hash = r.request_tx_hash
+  AND r.status = 'claimed'
+  AND r.claim_block_number IS NULL
+  AND t.amount = r.claimed_assets;
@@ -0,0 +1,99 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::currency_prices;
+
+/// USD price of a currency sampled at a given point in time
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = currency_prices)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct CurrencyPrice {
+    pub id: i32,
+    pub currency: String,
+    pub price: Decimal,
+    pub sampled_at: DateTime<Utc>,
+    pub source: String,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = currency_prices)]
+pub struct NewCurrencyPrice {
+    pub currency: String,
+    pub price: Decimal,
+    pub sampled_at: DateTime<Utc>,
+    pub source: String,
+}
+
+impl CurrencyPrice {
+    /// Insert price samples, ignoring the ones already recorded at the same time
+    pub fn insert_batch(
+        new_prices: &[NewCurrencyPrice],
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<usize> {
+        diesel::insert_into(currency_prices::table)
+            .values(new_prices)
+            .on_conflict((currency_prices::currency, currency_prices::sampled_at))
+            .do_nothing()
+            .execute(conn)
+    }
+
+    /// Find the latest sample of a currency taken at or before `at`
+    pub fn find_at_or_before(
+        currency: &str,
+        at: DateTime<Utc>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Option<Self>> {
+        currency_prices::table
+            .filter(currency_prices::currency.eq(currency))
+            .filter(currency_prices::sampled_at.le(at))
+            .order(currency_prices::sampled_at.desc())
+            .first(conn)
+            .optional()
+    }
+
+    /// Find the earliest sample of a currency taken after `at`
+    pub fn find_after(
+        currency: &str,
+        at: DateTime<Utc>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Option<Self>> {
+        currency_prices::table
+            .filter(currency_prices::currency.eq(currency))
+            .filter(currency_prices::sampled_at.gt(at))
+            .order(currency_prices::sampled_at.asc())
+            .first(conn)
+            .optional()
+    }
+
+    /// Get the samples of a currency within `[from, to]`, oldest first
+    pub fn find_between(
+        currency: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        currency_prices::table
+            .filter(currency_prices::currency.eq(currency))
+            .filter(currency_prices::sampled_at.ge(from))
+            .filter(currency_prices::sampled_at.le(to))
+            .order(currency_prices::sampled_at.asc())
+            .load(conn)
+    }
+
+    /// Get the timestamps already sampled for a currency since a given time
+    pub fn find_sampled_since(
+        currency: &str,
+        since: DateTime<Utc>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<DateTime<Utc>>> {
+        currency_prices::table
+            .filter(currency_prices::currency.eq(currency))
+            .filter(currency_prices::sampled_at.ge(since))
+            .select(currency_prices::sampled_at)
+            .order(currency_prices::sampled_at.asc())
+            .load(conn)
+    }
+}
@@ -0,0 +1,54 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::kpi_runs;
+
+/// Timing metrics of a KPI computation run
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = kpi_runs)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct KpiRun {
+    pub id: i32,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub vaults_processed: i32,
+    pub positions_processed: i32,
+    pub errors: i32,
+    pub fetch_ms: i64,
+    pub compute_ms: i64,
+    pub write_ms: i64,
+    pub total_ms: i64,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = kpi_runs)]
+pub struct NewKpiRun {
+    pub started_at: DateTime<Utc>,
+    pub finished_at: DateTime<Utc>,
+    pub vaults_processed: i32,
+    pub positions_processed: i32,
+    pub errors: i32,
+    pub fetch_ms: i64,
+    pub compute_ms: i64,
+    pub write_ms: i64,
+    pub total_ms: i64,
+}
+
+impl KpiRun {
+    /// Record a KPI run
+    pub fn create(new_run: &NewKpiRun, conn: &mut diesel::PgConnection) -> QueryResult<Self> {
+        diesel::insert_into(kpi_runs::table)
+            .values(new_run)
+            .get_result(conn)
+    }
+
+    /// Get the most recent KPI runs
+    pub fn find_recent(limit: i64, conn: &mut diesel::PgConnection) -> QueryResult<Vec<Self>> {
+        kpi_runs::table
+            .order(kpi_runs::started_at.desc())
+            .limit(limit)
+            .load(conn)
+    }
+}
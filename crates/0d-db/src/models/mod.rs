@@ -1,17 +1,57 @@
+pub mod currency_price;
 pub mod indexer_state;
+pub mod kpi_run;
+pub mod partner;
+pub mod partner_payout_batch;
+pub mod partner_referral;
+pub mod partner_reward_accrual;
+pub mod redeem_request;
+pub mod risk_free_rate;
+pub mod transaction_event;
 pub mod user;
 pub mod user_kpi;
 pub mod user_portfolio_history;
 pub mod user_position;
+pub mod user_risk_state;
 pub mod user_transaction;
 pub mod vault;
+pub mod vault_kpi;
+pub mod webhook_delivery;
+pub mod webhook_subscription;
 
+pub use currency_price::{CurrencyPrice, NewCurrencyPrice};
 pub use indexer_state::{IndexerState, IndexerStateUpdate, IndexerStatus, NewIndexerState};
+pub use kpi_run::{KpiRun, NewKpiRun};
+pub use partner::{NewPartner, Partner, PartnerUpdate};
+pub use partner_payout_batch::{NewPartnerPayoutBatch, PartnerPayoutBatch, PayoutBatchStatus};
+pub use partner_referral::{
+    PartnerReferredUsers, PartnerVaultReferrals, ReferredPosition, ValuedReferredPosition,
+};
+pub use partner_reward_accrual::{NewPartnerRewardAccrual, PartnerRewardAccrual};
+pub use redeem_request::{
+    NewRedeemRequest, PendingShares, RedeemClaim, RedeemEpochStart, RedeemLatency,
+    RedeemQueueEpoch, RedeemRequest, RedeemRequestStatus,
+};
+pub use risk_free_rate::{NewRiskFreeRate, RiskFreeRate};
+pub use transaction_event::{NewTransactionEventRecord, TransactionEventRecord};
 pub use user::{NewUser, User};
 pub use user_kpi::{NewUserKpi, UserKpi, UserKpiUpdate};
-pub use user_portfolio_history::{NewUserPortfolioHistory, UserPortfolioHistory};
-pub use user_position::{NewUserPosition, UserPosition, UserPositionUpdate};
+pub use user_portfolio_history::{
+    HolderCountBucket, NewUserPortfolioHistory, UserPortfolioHistory,
+};
+pub use user_position::{
+    HolderConcentration, HolderCursor, NewUserPosition, UserPosition, UserPositionUpdate,
+};
+pub use user_risk_state::{NewUserRiskState, UserRiskState};
 pub use user_transaction::{
-    NewUserTransaction, TransactionStatus, TransactionType, UserTransaction, UserTransactionUpdate,
+    NewUserTransaction, TransactionStatus, TransactionType, UserTransaction, UserTransactionFilter,
+    UserTransactionUpdate, VaultFlowBucket, VaultFlowTotals,
 };
 pub use vault::Vault;
+pub use vault_kpi::{NewVaultKpi, VaultKpi};
+pub use webhook_delivery::{
+    NewWebhookDelivery, WebhookDelivery, WebhookDeliveryStatus, WebhookEvent,
+};
+pub use webhook_subscription::{
+    NewWebhookSubscription, WebhookSubscription, WebhookSubscriptionUpdate,
+};
@@ -0,0 +1,76 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::partners;
+
+/// Partner attributed to deposits through the `partner_id` of the deposits. Rewards
+/// only accrue while the partner is active.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = partners)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct Partner {
+    pub id: String,
+    pub display_name: String,
+    pub payout_address: String,
+    /// Share of the fees generated by the referred positions paid to the partner
+    pub reward_share_bps: i32,
+    pub active_from: DateTime<Utc>,
+    pub active_until: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = partners)]
+pub struct NewPartner {
+    pub id: String,
+    pub display_name: String,
+    pub payout_address: String,
+    pub reward_share_bps: i32,
+    pub active_from: DateTime<Utc>,
+    pub active_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, AsChangeset)]
+#[diesel(table_name = partners)]
+pub struct PartnerUpdate {
+    pub display_name: Option<String>,
+    pub payout_address: Option<String>,
+    pub reward_share_bps: Option<i32>,
+    pub active_from: Option<DateTime<Utc>>,
+    /// `Some(None)` reopens the active window
+    pub active_until: Option<Option<DateTime<Utc>>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl Partner {
+    pub fn create(new_partner: &NewPartner, conn: &mut diesel::PgConnection) -> QueryResult<Self> {
+        diesel::insert_into(partners::table)
+            .values(new_partner)
+            .get_result(conn)
+    }
+
+    pub fn find_by_id(id: &str, conn: &mut diesel::PgConnection) -> QueryResult<Self> {
+        partners::table.find(id).first(conn)
+    }
+
+    pub fn find_all(conn: &mut diesel::PgConnection) -> QueryResult<Vec<Self>> {
+        partners::table.order(partners::id.asc()).load(conn)
+    }
+
+    pub fn update(
+        &self,
+        updates: &PartnerUpdate,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Self> {
+        diesel::update(partners::table.find(&self.id))
+            .set(updates)
+            .get_result(conn)
+    }
+
+    /// Whether the partner earns rewards at a point in time
+    pub fn is_active_at(&self, at: DateTime<Utc>) -> bool {
+        self.active_from <= at && self.active_until.is_none_or(|until| at < until)
+    }
+}
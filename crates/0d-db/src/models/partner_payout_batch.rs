@@ -0,0 +1,164 @@
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::models::{Partner, PartnerRewardAccrual};
+use crate::schema::{partner_payout_batches, partners};
+
+/// Rewards accrued by a partner over a period, paid out in a single transfer
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = partner_payout_batches)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PartnerPayoutBatch {
+    pub id: i32,
+    pub partner_id: String,
+    /// Payout address of the partner when the batch was generated
+    pub payout_address: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub accruals_count: i32,
+    pub total_reward_usd: Decimal,
+    pub status: String,
+    pub tx_hash: Option<String>,
+    pub paid_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = partner_payout_batches)]
+pub struct NewPartnerPayoutBatch {
+    pub partner_id: String,
+    pub payout_address: String,
+    pub period_start: NaiveDate,
+    pub period_end: NaiveDate,
+    pub accruals_count: i32,
+    pub total_reward_usd: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PayoutBatchStatus {
+    Pending,
+    Paid,
+}
+
+impl PayoutBatchStatus {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Paid => "paid",
+        }
+    }
+}
+
+impl PartnerPayoutBatch {
+    /// Batch the accruals not paid out yet, up to a day included, into one batch per partner.
+    ///
+    /// Partners with nothing to pay are skipped; their accruals are batched once they
+    /// add up to a positive reward.
+    pub fn generate(until: NaiveDate, conn: &mut diesel::PgConnection) -> QueryResult<Vec<Self>> {
+        conn.transaction(|conn| {
+            let accruals = PartnerRewardAccrual::find_unbatched_for_update(until, conn)?;
+
+            let mut by_partner: BTreeMap<String, Vec<PartnerRewardAccrual>> = BTreeMap::new();
+            for accrual in accruals {
+                by_partner
+                    .entry(accrual.partner_id.clone())
+                    .or_default()
+                    .push(accrual);
+            }
+
+            let partner_ids: Vec<&String> = by_partner.keys().collect();
+            let payout_addresses: BTreeMap<String, String> = partners::table
+                .filter(partners::id.eq_any(partner_ids))
+                .select(Partner::as_select())
+                .load(conn)?
+                .into_iter()
+                .map(|partner| (partner.id, partner.payout_address))
+                .collect();
+
+            let mut batches = Vec::new();
+            for (partner_id, accruals) in by_partner {
+                let total_reward_usd: Decimal = accruals.iter().map(|a| a.reward_usd).sum();
+                let (Some(first), Some(last), Some(payout_address)) = (
+                    accruals.first(),
+                    accruals.last(),
+                    payout_addresses.get(&partner_id),
+                ) else {
+                    continue;
+                };
+                if total_reward_usd <= Decimal::ZERO {
+                    continue;
+                }
+
+                let new_batch = NewPartnerPayoutBatch {
+                    partner_id,
+                    payout_address: payout_address.clone(),
+                    period_start: first.accrual_date,
+                    period_end: last.accrual_date,
+                    accruals_count: i32::try_from(accruals.len()).unwrap_or(i32::MAX),
+                    total_reward_usd,
+                };
+                let batch: Self = diesel::insert_into(partner_payout_batches::table)
+                    .values(&new_batch)
+                    .get_result(conn)?;
+
+                let ids: Vec<i32> = accruals.iter().map(|a| a.id).collect();
+                PartnerRewardAccrual::assign_batch(&ids, batch.id, conn)?;
+                batches.push(batch);
+            }
+
+            Ok(batches)
+        })
+    }
+
+    pub fn find_by_id(id: i32, conn: &mut diesel::PgConnection) -> QueryResult<Self> {
+        partner_payout_batches::table.find(id).first(conn)
+    }
+
+    /// Find the most recent batches, optionally of a partner and with a given status
+    pub fn find_recent(
+        partner_id: Option<&str>,
+        status: Option<&str>,
+        limit: i64,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        let mut query = partner_payout_batches::table
+            .order(partner_payout_batches::created_at.desc())
+            .limit(limit)
+            .into_boxed();
+
+        if let Some(partner_id) = partner_id {
+            query = query.filter(partner_payout_batches::partner_id.eq(partner_id));
+        }
+        if let Some(status) = status {
+            query = query.filter(partner_payout_batches::status.eq(status));
+        }
+
+        query.load(conn)
+    }
+
+    /// Mark a pending batch as paid. Returns `None` when the batch is not pending.
+    pub fn mark_paid(
+        id: i32,
+        tx_hash: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Option<Self>> {
+        let now = Utc::now();
+        diesel::update(partner_payout_batches::table.find(id))
+            .filter(partner_payout_batches::status.eq(PayoutBatchStatus::Pending.as_str()))
+            .set((
+                partner_payout_batches::status.eq(PayoutBatchStatus::Paid.as_str()),
+                partner_payout_batches::tx_hash.eq(tx_hash),
+                partner_payout_batches::paid_at.eq(now),
+                partner_payout_batches::updated_at.eq(now),
+            ))
+            .get_result(conn)
+            .optional()
+    }
+}
@@ -0,0 +1,268 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Array, BigInt, Nullable, Numeric, Text, Timestamptz};
+use rust_decimal::Decimal;
+
+/// Positions referred by a partner along with the deposits made through it.
+///
+/// A position (user, vault) is referred by the partner of the first deposit made into it
+/// through a partner, and only the deposits made through that partner are attributed to it.
+/// Deposits are valued in USD at the price of the underlying currency at deposit time,
+/// carrying the nearest recorded price.
+/// Binds: `$1` optional partner id.
+const REFERRED_POSITIONS_CTE: &str = "
+WITH referrals AS (
+    SELECT DISTINCT ON (user_address, vault_id)
+        user_address, vault_id, partner_id, block_timestamp AS referred_at
+    FROM user_transactions
+    WHERE type = 'deposit'
+      AND partner_id IS NOT NULL
+      AND status NOT IN ('failed', 'cancelled')
+    ORDER BY user_address, vault_id, block_timestamp, id
+),
+referred_positions AS (
+    SELECT
+        r.partner_id,
+        r.user_address,
+        r.vault_id,
+        r.referred_at,
+        COUNT(*) AS deposit_count,
+        SUM(t.amount) AS deposit_volume,
+        COALESCE(SUM(t.amount * COALESCE(
+            CASE WHEN UPPER(v.base_asset) = 'USD' THEN 1 END,
+            price_before.price,
+            price_after.price
+        )), 0) AS deposit_volume_usd,
+        MAX(t.block_timestamp) AS last_deposit_at,
+        COALESCE(MAX(p.share_balance), 0) AS share_balance,
+        COALESCE(MAX(p.cost_basis), 0) AS cost_basis
+    FROM referrals r
+    JOIN vaults v ON v.id = r.vault_id
+    JOIN user_transactions t
+        ON t.user_address = r.user_address
+       AND t.vault_id = r.vault_id
+       AND t.partner_id = r.partner_id
+       AND t.type = 'deposit'
+       AND t.status NOT IN ('failed', 'cancelled')
+    LEFT JOIN user_positions p
+        ON p.user_address = r.user_address
+       AND p.vault_id = r.vault_id
+    LEFT JOIN LATERAL (
+        SELECT cp.price
+        FROM currency_prices cp
+        WHERE cp.currency = UPPER(v.base_asset) AND cp.sampled_at <= t.block_timestamp
+        ORDER BY cp.sampled_at DESC
+        LIMIT 1
+    ) price_before ON TRUE
+    LEFT JOIN LATERAL (
+        SELECT cp.price
+        FROM currency_prices cp
+        WHERE cp.currency = UPPER(v.base_asset) AND cp.sampled_at > t.block_timestamp
+        ORDER BY cp.sampled_at ASC
+        LIMIT 1
+    ) price_after ON TRUE
+    WHERE ($1 IS NULL OR r.partner_id = $1)
+    GROUP BY r.partner_id, r.user_address, r.vault_id, r.referred_at
+)";
+
+/// Position referred by a partner, see [`REFERRED_POSITIONS_CTE`] for the attribution rule
+#[derive(Debug, Clone, QueryableByName)]
+pub struct ReferredPosition {
+    #[diesel(sql_type = Text)]
+    pub partner_id: String,
+    #[diesel(sql_type = Text)]
+    pub user_address: String,
+    #[diesel(sql_type = Text)]
+    pub vault_id: String,
+    /// First deposit through the partner
+    #[diesel(sql_type = Timestamptz)]
+    pub referred_at: DateTime<Utc>,
+    #[diesel(sql_type = BigInt)]
+    pub deposit_count: i64,
+    /// Deposited through the partner, in the underlying currency
+    #[diesel(sql_type = Numeric)]
+    pub deposit_volume: Decimal,
+    /// Deposited through the partner, in USD at deposit time
+    #[diesel(sql_type = Numeric)]
+    pub deposit_volume_usd: Decimal,
+    #[diesel(sql_type = Numeric)]
+    pub share_balance: Decimal,
+    #[diesel(sql_type = Numeric)]
+    pub cost_basis: Decimal,
+}
+
+/// Referred position along with its current value
+#[derive(Debug, Clone, QueryableByName)]
+pub struct ValuedReferredPosition {
+    #[diesel(embed)]
+    pub position: ReferredPosition,
+    /// Shares held at the current share price, 0 when the share price is unknown
+    #[diesel(sql_type = Numeric)]
+    pub value_usd: Decimal,
+}
+
+/// Referral totals of a partner in one vault
+#[derive(Debug, Clone, QueryableByName)]
+pub struct PartnerVaultReferrals {
+    #[diesel(sql_type = Text)]
+    pub partner_id: String,
+    #[diesel(sql_type = Text)]
+    pub vault_id: String,
+    #[diesel(sql_type = BigInt)]
+    pub referred_users: i64,
+    #[diesel(sql_type = BigInt)]
+    pub deposit_count: i64,
+    #[diesel(sql_type = Numeric)]
+    pub deposit_volume: Decimal,
+    #[diesel(sql_type = Numeric)]
+    pub deposit_volume_usd: Decimal,
+    /// Shares currently held by the referred users
+    #[diesel(sql_type = Numeric)]
+    pub share_balance: Decimal,
+    /// Shares held multiplied by the seconds elapsed since they were referred
+    #[diesel(sql_type = Numeric)]
+    pub share_seconds: Decimal,
+    /// Sum of the positive gains of the referred positions at the latest recorded share price,
+    /// in the underlying currency
+    #[diesel(sql_type = Numeric)]
+    pub unrealized_gain: Decimal,
+    #[diesel(sql_type = Timestamptz)]
+    pub first_referral_at: DateTime<Utc>,
+    #[diesel(sql_type = Timestamptz)]
+    pub last_deposit_at: DateTime<Utc>,
+}
+
+/// Number of distinct users referred by a partner, across vaults
+#[derive(Debug, Clone, QueryableByName)]
+pub struct PartnerReferredUsers {
+    #[diesel(sql_type = Text)]
+    pub partner_id: String,
+    #[diesel(sql_type = BigInt)]
+    pub referred_users: i64,
+}
+
+impl ReferredPosition {
+    /// Find the positions referred by a partner, or by any partner, oldest referral first
+    pub fn find_by_partner(
+        partner_id: Option<&str>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        diesel::sql_query(format!(
+            "{REFERRED_POSITIONS_CTE}
+             SELECT partner_id, user_address, vault_id, referred_at, deposit_count,
+                    deposit_volume, deposit_volume_usd, share_balance, cost_basis
+             FROM referred_positions
+             ORDER BY referred_at, user_address, vault_id"
+        ))
+        .bind::<Nullable<Text>, _>(partner_id)
+        .load(conn)
+    }
+}
+
+impl ValuedReferredPosition {
+    /// Find a page of the positions referred by a partner by decreasing value, after the
+    /// (value, user address, vault id) of `after`. Ties are broken by user address, then
+    /// vault id.
+    pub fn find_page_by_partner(
+        partner_id: &str,
+        share_prices_usd: &[(String, Decimal)],
+        after: Option<(Decimal, String, String)>,
+        limit: i64,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        let (vault_ids, share_prices): (Vec<String>, Vec<Decimal>) =
+            share_prices_usd.iter().cloned().unzip();
+        let (after_value, after_user, after_vault) = after.map_or((None, None, None), |after| {
+            (Some(after.0), Some(after.1), Some(after.2))
+        });
+
+        diesel::sql_query(format!(
+            "{REFERRED_POSITIONS_CTE},
+             valued_positions AS (
+                SELECT rp.*, COALESCE(rp.share_balance * sp.share_price_usd, 0) AS value_usd
+                FROM referred_positions rp
+                LEFT JOIN UNNEST($2, $3) AS sp(vault_id, share_price_usd)
+                    ON sp.vault_id = rp.vault_id
+             )
+             SELECT partner_id, user_address, vault_id, referred_at, deposit_count,
+                    deposit_volume, deposit_volume_usd, share_balance, cost_basis, value_usd
+             FROM valued_positions
+             WHERE $4 IS NULL
+                OR value_usd < $4
+                OR (value_usd = $4 AND (user_address, vault_id) > ($5, $6))
+             ORDER BY value_usd DESC, user_address, vault_id
+             LIMIT $7"
+        ))
+        .bind::<Nullable<Text>, _>(Some(partner_id))
+        .bind::<Array<Text>, _>(vault_ids)
+        .bind::<Array<Numeric>, _>(share_prices)
+        .bind::<Nullable<Numeric>, _>(after_value)
+        .bind::<Nullable<Text>, _>(after_user)
+        .bind::<Nullable<Text>, _>(after_vault)
+        .bind::<BigInt, _>(limit)
+        .load(conn)
+    }
+}
+
+impl PartnerVaultReferrals {
+    /// Aggregate the referred positions of a partner, or of every partner, per vault
+    pub fn find_by_partner(
+        partner_id: Option<&str>,
+        now: DateTime<Utc>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        diesel::sql_query(format!(
+            "{REFERRED_POSITIONS_CTE},
+             vault_share_prices AS (
+                SELECT v.vault_id, sp.share_price
+                FROM (SELECT DISTINCT vault_id FROM referred_positions) v
+                CROSS JOIN LATERAL (
+                    SELECT h.share_price
+                    FROM user_portfolio_history h
+                    WHERE h.vault_id = v.vault_id
+                    ORDER BY h.calculated_at DESC
+                    LIMIT 1
+                ) sp
+             )
+             SELECT
+                rp.partner_id,
+                rp.vault_id,
+                COUNT(*) AS referred_users,
+                CAST(SUM(rp.deposit_count) AS BIGINT) AS deposit_count,
+                SUM(rp.deposit_volume) AS deposit_volume,
+                SUM(rp.deposit_volume_usd) AS deposit_volume_usd,
+                SUM(rp.share_balance) AS share_balance,
+                SUM(rp.share_balance * GREATEST(EXTRACT(EPOCH FROM ($2 - rp.referred_at)), 0))
+                    AS share_seconds,
+                COALESCE(SUM(GREATEST(rp.share_balance * sp.share_price - rp.cost_basis, 0)), 0)
+                    AS unrealized_gain,
+                MIN(rp.referred_at) AS first_referral_at,
+                MAX(rp.last_deposit_at) AS last_deposit_at
+             FROM referred_positions rp
+             LEFT JOIN vault_share_prices sp ON sp.vault_id = rp.vault_id
+             GROUP BY rp.partner_id, rp.vault_id
+             ORDER BY rp.partner_id, rp.vault_id"
+        ))
+        .bind::<Nullable<Text>, _>(partner_id)
+        .bind::<Timestamptz, _>(now)
+        .load(conn)
+    }
+}
+
+impl PartnerReferredUsers {
+    /// Count the distinct users referred by a partner, or by every partner
+    pub fn find_by_partner(
+        partner_id: Option<&str>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        diesel::sql_query(format!(
+            "{REFERRED_POSITIONS_CTE}
+             SELECT partner_id, COUNT(DISTINCT user_address) AS referred_users
+             FROM referred_positions
+             GROUP BY partner_id
+             ORDER BY partner_id"
+        ))
+        .bind::<Nullable<Text>, _>(partner_id)
+        .load(conn)
+    }
+}
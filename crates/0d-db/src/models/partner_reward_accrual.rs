@@ -0,0 +1,120 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::partner_reward_accruals;
+
+/// Ledger entry: rewards accrued by a partner in a day on the positions it referred in a vault.
+/// All amounts are in USD.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = partner_reward_accruals)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct PartnerRewardAccrual {
+    pub id: i32,
+    pub partner_id: String,
+    pub vault_id: String,
+    pub accrual_date: NaiveDate,
+    pub referred_positions: i32,
+    pub referred_tvl_usd: Decimal,
+    pub yield_usd: Decimal,
+    pub management_fees_usd: Decimal,
+    pub performance_fees_usd: Decimal,
+    /// Reward share of the partner when the rewards accrued
+    pub reward_share_bps: i32,
+    pub reward_usd: Decimal,
+    pub payout_batch_id: Option<i32>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = partner_reward_accruals)]
+pub struct NewPartnerRewardAccrual {
+    pub partner_id: String,
+    pub vault_id: String,
+    pub accrual_date: NaiveDate,
+    pub referred_positions: i32,
+    pub referred_tvl_usd: Decimal,
+    pub yield_usd: Decimal,
+    pub management_fees_usd: Decimal,
+    pub performance_fees_usd: Decimal,
+    pub reward_share_bps: i32,
+    pub reward_usd: Decimal,
+}
+
+impl PartnerRewardAccrual {
+    /// Insert a batch of accruals. Days already accrued are left untouched, so that
+    /// running the accrual twice in a day is a no-op.
+    pub fn insert_batch(
+        accruals: &[NewPartnerRewardAccrual],
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<usize> {
+        if accruals.is_empty() {
+            return Ok(0);
+        }
+
+        diesel::insert_into(partner_reward_accruals::table)
+            .values(accruals)
+            .on_conflict((
+                partner_reward_accruals::partner_id,
+                partner_reward_accruals::vault_id,
+                partner_reward_accruals::accrual_date,
+            ))
+            .do_nothing()
+            .execute(conn)
+    }
+
+    /// Find the most recent accruals of a partner
+    pub fn find_by_partner(
+        partner_id: &str,
+        limit: i64,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        partner_reward_accruals::table
+            .filter(partner_reward_accruals::partner_id.eq(partner_id))
+            .order((
+                partner_reward_accruals::accrual_date.desc(),
+                partner_reward_accruals::vault_id.asc(),
+            ))
+            .limit(limit)
+            .load(conn)
+    }
+
+    pub fn find_by_batch(batch_id: i32, conn: &mut diesel::PgConnection) -> QueryResult<Vec<Self>> {
+        partner_reward_accruals::table
+            .filter(partner_reward_accruals::payout_batch_id.eq(batch_id))
+            .order((
+                partner_reward_accruals::accrual_date.asc(),
+                partner_reward_accruals::vault_id.asc(),
+            ))
+            .load(conn)
+    }
+
+    /// Find the accruals not paid out yet, up to a day included, locking them until the
+    /// end of the transaction
+    pub fn find_unbatched_for_update(
+        until: NaiveDate,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        partner_reward_accruals::table
+            .filter(partner_reward_accruals::payout_batch_id.is_null())
+            .filter(partner_reward_accruals::accrual_date.le(until))
+            .order((
+                partner_reward_accruals::partner_id.asc(),
+                partner_reward_accruals::accrual_date.asc(),
+            ))
+            .for_update()
+            .load(conn)
+    }
+
+    pub fn assign_batch(
+        ids: &[i32],
+        batch_id: i32,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<usize> {
+        diesel::update(partner_reward_accruals::table)
+            .filter(partner_reward_accruals::id.eq_any(ids))
+            .set(partner_reward_accruals::payout_batch_id.eq(batch_id))
+            .execute(conn)
+    }
+}
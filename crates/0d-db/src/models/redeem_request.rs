@@ -0,0 +1,324 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Double, Nullable, Numeric, Text, Timestamptz};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::schema::redeem_requests;
+
+/// A redemption of vault shares, from its request to its claim
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = redeem_requests)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RedeemRequest {
+    pub id: i32,
+    pub vault_id: String,
+    pub redeem_id: String,
+    /// Owner of the redeemed shares
+    pub user_address: String,
+    pub receiver: String,
+    pub epoch: i64,
+    pub status: String,
+    pub requested_shares: Decimal,
+    /// Assets of the requested shares at the share price of the request
+    pub nominal_assets: Decimal,
+    pub claimed_assets: Option<Decimal>,
+    pub request_tx_hash: String,
+    pub request_block_number: i64,
+    pub requested_at: DateTime<Utc>,
+    pub claim_tx_hash: Option<String>,
+    pub claim_block_number: Option<i64>,
+    pub claimed_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = redeem_requests)]
+pub struct NewRedeemRequest {
+    pub vault_id: String,
+    pub redeem_id: String,
+    pub user_address: String,
+    pub receiver: String,
+    pub epoch: i64,
+    pub requested_shares: Decimal,
+    pub nominal_assets: Decimal,
+    pub request_tx_hash: String,
+    pub request_block_number: i64,
+    pub requested_at: DateTime<Utc>,
+}
+
+/// Claim of a pending redemption
+#[derive(Debug, Clone)]
+pub struct RedeemClaim {
+    pub claim_tx_hash: String,
+    pub claim_block_number: i64,
+    pub claimed_at: DateTime<Utc>,
+    pub claimed_assets: Decimal,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RedeemRequestStatus {
+    Pending,
+    Claimed,
+}
+
+impl RedeemRequestStatus {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Claimed => "claimed",
+        }
+    }
+}
+
+/// Pending redemptions of a vault requested during the same epoch, in its underlying currency
+#[derive(Debug, Clone, QueryableByName)]
+pub struct RedeemQueueEpoch {
+    #[diesel(sql_type = BigInt)]
+    pub epoch: i64,
+    #[diesel(sql_type = BigInt)]
+    pub requests: i64,
+    #[diesel(sql_type = BigInt)]
+    pub users: i64,
+    #[diesel(sql_type = Numeric)]
+    pub shares: Decimal,
+    /// Assets at the share price of the requests
+    #[diesel(sql_type = Numeric)]
+    pub assets: Decimal,
+    #[diesel(sql_type = Timestamptz)]
+    pub oldest_requested_at: DateTime<Utc>,
+}
+
+/// Shares of the pending redemptions of a user in a vault
+#[derive(Debug, Clone, QueryableByName)]
+pub struct PendingShares {
+    #[diesel(sql_type = Text)]
+    pub vault_id: String,
+    #[diesel(sql_type = BigInt)]
+    pub requests: i64,
+    #[diesel(sql_type = Numeric)]
+    pub shares: Decimal,
+}
+
+/// First redemption requested during an epoch of a vault
+#[derive(Debug, Clone, QueryableByName)]
+pub struct RedeemEpochStart {
+    #[diesel(sql_type = BigInt)]
+    pub epoch: i64,
+    #[diesel(sql_type = Timestamptz)]
+    pub first_requested_at: DateTime<Utc>,
+}
+
+/// Distribution of the time between the request and the claim of redemptions, in seconds
+#[derive(Debug, Clone, QueryableByName)]
+pub struct RedeemLatency {
+    #[diesel(sql_type = BigInt)]
+    pub claimed: i64,
+    #[diesel(sql_type = Nullable<Double>)]
+    pub p50_seconds: Option<f64>,
+    #[diesel(sql_type = Nullable<Double>)]
+    pub p90_seconds: Option<f64>,
+}
+
+impl RedeemRequest {
+    pub fn create(new_request: &NewRedeemRequest, conn: &mut PgConnection) -> QueryResult<Self> {
+        diesel::insert_into(redeem_requests::table)
+            .values(new_request)
+            .get_result(conn)
+    }
+
+    /// Find a redemption of a vault by its onchain id, regardless of its status
+    pub fn find_by_redeem_id(
+        vault_id: &str,
+        redeem_id: &str,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Self> {
+        redeem_requests::table
+            .filter(redeem_requests::vault_id.eq(vault_id))
+            .filter(redeem_requests::redeem_id.eq(redeem_id))
+            .first(conn)
+    }
+
+    /// Find the pending redemptions of a user, optionally in a single vault, newest first,
+    /// starting after a (`requested_at`, id) position
+    pub fn find_pending_by_user(
+        user_address: &str,
+        vault_id: Option<&str>,
+        after: Option<(DateTime<Utc>, i32)>,
+        limit: i64,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        let mut query = redeem_requests::table
+            .filter(redeem_requests::user_address.eq(user_address))
+            .filter(redeem_requests::status.eq(RedeemRequestStatus::Pending.as_str()))
+            .into_boxed();
+
+        if let Some(vault) = vault_id {
+            query = query.filter(redeem_requests::vault_id.eq(vault));
+        }
+        if let Some((requested_at, id)) = after {
+            query = query.filter(
+                redeem_requests::requested_at
+                    .lt(requested_at)
+                    .or(redeem_requests::requested_at
+                        .eq(requested_at)
+                        .and(redeem_requests::id.lt(id))),
+            );
+        }
+
+        query
+            .order((
+                redeem_requests::requested_at.desc(),
+                redeem_requests::id.desc(),
+            ))
+            .limit(limit)
+            .load(conn)
+    }
+
+    /// Find the claims of a user recorded before a point in time, oldest first
+    pub fn find_claimed_by_user(
+        user_address: &str,
+        before: DateTime<Utc>,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        redeem_requests::table
+            .filter(redeem_requests::user_address.eq(user_address))
+            .filter(redeem_requests::status.eq(RedeemRequestStatus::Claimed.as_str()))
+            .filter(redeem_requests::claimed_at.lt(before))
+            .order((redeem_requests::claimed_at.asc(), redeem_requests::id.asc()))
+            .load(conn)
+    }
+
+    /// Sum the shares of the pending redemptions of a user per vault
+    pub fn pending_shares_by_vault(
+        user_address: &str,
+        vault_id: Option<&str>,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<PendingShares>> {
+        diesel::sql_query(
+            "SELECT
+                vault_id,
+                COUNT(*) AS requests,
+                SUM(requested_shares) AS shares
+             FROM redeem_requests
+             WHERE user_address = $1
+               AND ($2 IS NULL OR vault_id = $2)
+               AND status = 'pending'
+             GROUP BY vault_id
+             ORDER BY vault_id",
+        )
+        .bind::<Text, _>(user_address)
+        .bind::<Nullable<Text>, _>(vault_id)
+        .load(conn)
+    }
+
+    /// Record the claim of the redemption
+    pub fn claim(&self, claim: &RedeemClaim, conn: &mut PgConnection) -> QueryResult<Self> {
+        diesel::update(self)
+            .set((
+                redeem_requests::status.eq(RedeemRequestStatus::Claimed.as_str()),
+                redeem_requests::claimed_assets.eq(claim.claimed_assets),
+                redeem_requests::claim_tx_hash.eq(&claim.claim_tx_hash),
+                redeem_requests::claim_block_number.eq(claim.claim_block_number),
+                redeem_requests::claimed_at.eq(claim.claimed_at),
+                redeem_requests::updated_at.eq(Utc::now()),
+            ))
+            .get_result(conn)
+    }
+
+    /// Calculate the average time it took a user to claim their redemptions, in seconds
+    pub fn average_claim_delay(
+        user_address: &str,
+        vault_id: Option<&str>,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Option<i64>> {
+        #[derive(QueryableByName)]
+        struct AvgDelayResult {
+            #[diesel(sql_type = Nullable<BigInt>)]
+            avg_delay_seconds: Option<i64>,
+        }
+
+        let result: AvgDelayResult = diesel::sql_query(
+            "SELECT CAST(AVG(EXTRACT(EPOCH FROM (claimed_at - requested_at))) AS BIGINT) AS avg_delay_seconds
+             FROM redeem_requests
+             WHERE user_address = $1
+               AND ($2 IS NULL OR vault_id = $2)
+               AND status = 'claimed'
+               AND claimed_at IS NOT NULL",
+        )
+        .bind::<Text, _>(user_address)
+        .bind::<Nullable<Text>, _>(vault_id)
+        .get_result(conn)?;
+
+        Ok(result.avg_delay_seconds)
+    }
+
+    /// Aggregate the pending redemptions of a vault per epoch, oldest epoch first
+    pub fn queue_by_epoch(
+        vault_id: &str,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<RedeemQueueEpoch>> {
+        diesel::sql_query(
+            "SELECT
+                epoch,
+                COUNT(*) AS requests,
+                COUNT(DISTINCT user_address) AS users,
+                SUM(requested_shares) AS shares,
+                SUM(nominal_assets) AS assets,
+                MIN(requested_at) AS oldest_requested_at
+             FROM redeem_requests
+             WHERE vault_id = $1
+               AND status = 'pending'
+             GROUP BY epoch
+             ORDER BY epoch",
+        )
+        .bind::<Text, _>(vault_id)
+        .load(conn)
+    }
+
+    /// Get the time of the first redemption requested during each epoch of a vault, oldest
+    /// epoch first
+    pub fn epoch_starts(
+        vault_id: &str,
+        conn: &mut PgConnection,
+    ) -> QueryResult<Vec<RedeemEpochStart>> {
+        diesel::sql_query(
+            "SELECT epoch, MIN(requested_at) AS first_requested_at
+             FROM redeem_requests
+             WHERE vault_id = $1
+             GROUP BY epoch
+             ORDER BY epoch",
+        )
+        .bind::<Text, _>(vault_id)
+        .load(conn)
+    }
+
+    /// Get the p50 and p90 of the time it took to claim the redemptions of a vault
+    /// requested since a point in time, or over its whole history
+    pub fn claim_latency(
+        vault_id: &str,
+        since: Option<DateTime<Utc>>,
+        conn: &mut PgConnection,
+    ) -> QueryResult<RedeemLatency> {
+        diesel::sql_query(
+            "SELECT
+                COUNT(*) AS claimed,
+                percentile_cont(0.5) WITHIN GROUP (ORDER BY latency_seconds) AS p50_seconds,
+                percentile_cont(0.9) WITHIN GROUP (ORDER BY latency_seconds) AS p90_seconds
+             FROM (
+                SELECT CAST(EXTRACT(EPOCH FROM (claimed_at - requested_at)) AS DOUBLE PRECISION) AS latency_seconds
+                FROM redeem_requests
+                WHERE vault_id = $1
+                  AND status = 'claimed'
+                  AND claimed_at IS NOT NULL
+                  AND ($2 IS NULL OR requested_at >= $2)
+             ) claims",
+        )
+        .bind::<Text, _>(vault_id)
+        .bind::<Nullable<Timestamptz>, _>(since)
+        .get_result(conn)
+    }
+}
@@ -0,0 +1,66 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::risk_free_rates;
+
+/// Annualized risk-free rate for a base asset, effective from a given point in time.
+/// Successive rows for the same asset form a step-wise time series.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = risk_free_rates)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct RiskFreeRate {
+    pub id: i32,
+    pub base_asset: String,
+    pub annual_rate: Decimal,
+    pub effective_from: DateTime<Utc>,
+    pub source: Option<String>,
+    pub created_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = risk_free_rates)]
+pub struct NewRiskFreeRate {
+    pub base_asset: String,
+    pub annual_rate: Decimal,
+    pub effective_from: DateTime<Utc>,
+    pub source: Option<String>,
+}
+
+impl RiskFreeRate {
+    /// Create a new risk-free rate entry
+    pub fn create(
+        new_rate: &NewRiskFreeRate,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Self> {
+        diesel::insert_into(risk_free_rates::table)
+            .values(new_rate)
+            .get_result(conn)
+    }
+
+    /// Find the rate in effect for a base asset at a given time (latest `effective_from` <= `at`)
+    pub fn find_effective(
+        base_asset: &str,
+        at: DateTime<Utc>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Option<Self>> {
+        risk_free_rates::table
+            .filter(risk_free_rates::base_asset.eq(base_asset.to_uppercase()))
+            .filter(risk_free_rates::effective_from.le(at))
+            .order(risk_free_rates::effective_from.desc())
+            .first(conn)
+            .optional()
+    }
+
+    /// Get the full rate history for a base asset, oldest first
+    pub fn find_by_base_asset(
+        base_asset: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        risk_free_rates::table
+            .filter(risk_free_rates::base_asset.eq(base_asset.to_uppercase()))
+            .order(risk_free_rates::effective_from.asc())
+            .load(conn)
+    }
+}
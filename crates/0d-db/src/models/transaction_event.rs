@@ -0,0 +1,134 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use zerod_types::events::{TransactionEvent, TransactionEventKind};
+
+use crate::models::UserTransaction;
+use crate::schema::transaction_events;
+
+/// Entry of the ordered log of transaction events.
+///
+/// The id increases with every event, claims included, so that event streams can resume
+/// from the last one they sent.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = transaction_events)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct TransactionEventRecord {
+    pub id: i64,
+    pub transaction_id: i32,
+    pub kind: String,
+    pub vault_id: String,
+    pub user_address: String,
+    pub partner_id: Option<String>,
+    pub tx_hash: String,
+    pub status: String,
+    pub amount: Decimal,
+    pub shares_amount: Option<Decimal>,
+    pub block_number: i64,
+    pub block_timestamp: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = transaction_events)]
+pub struct NewTransactionEventRecord {
+    pub transaction_id: i32,
+    pub kind: String,
+    pub vault_id: String,
+    pub user_address: String,
+    pub partner_id: Option<String>,
+    pub tx_hash: String,
+    pub status: String,
+    pub amount: Decimal,
+    pub shares_amount: Option<Decimal>,
+    pub block_number: i64,
+    pub block_timestamp: DateTime<Utc>,
+}
+
+impl NewTransactionEventRecord {
+    /// Snapshot of a transaction as it is when the event happens
+    pub fn new(kind: TransactionEventKind, transaction: &UserTransaction) -> Self {
+        Self {
+            transaction_id: transaction.id,
+            kind: kind.as_str().to_string(),
+            vault_id: transaction.vault_id.clone(),
+            user_address: transaction.user_address.clone(),
+            partner_id: transaction.partner_id.clone(),
+            tx_hash: transaction.tx_hash.clone(),
+            status: transaction.status.clone(),
+            amount: transaction.amount,
+            shares_amount: transaction.shares_amount,
+            block_number: transaction.block_number,
+            block_timestamp: transaction.block_timestamp,
+        }
+    }
+}
+
+impl TransactionEventRecord {
+    /// Append an event to the log.
+    ///
+    /// Meant to be called within the database transaction writing the transaction, so that
+    /// the order of the log is the order of the commits.
+    pub fn create(
+        new_event: &NewTransactionEventRecord,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Self> {
+        diesel::insert_into(transaction_events::table)
+            .values(new_event)
+            .returning(Self::as_returning())
+            .get_result(conn)
+    }
+
+    /// Find the events logged after a given id, oldest first (optionally filtered by
+    /// user and vault). Used to resume event streams.
+    pub fn find_after_id(
+        user_address: Option<&str>,
+        vault_id: Option<&str>,
+        after_id: i64,
+        limit: i64,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        let mut query = transaction_events::table
+            .filter(transaction_events::id.gt(after_id))
+            .into_boxed();
+
+        if let Some(user) = user_address {
+            query = query.filter(transaction_events::user_address.eq(user));
+        }
+        if let Some(vault) = vault_id {
+            query = query.filter(transaction_events::vault_id.eq(vault));
+        }
+
+        query
+            .order(transaction_events::id.asc())
+            .limit(limit)
+            .load(conn)
+    }
+
+    /// Event published to the streams and webhooks, `None` for an unknown kind
+    pub fn to_event(&self) -> Option<TransactionEvent> {
+        let kind = [
+            TransactionEventKind::Deposit,
+            TransactionEventKind::RedeemRequested,
+            TransactionEventKind::RedeemClaimed,
+        ]
+        .into_iter()
+        .find(|kind| kind.as_str() == self.kind)?;
+
+        Some(TransactionEvent {
+            event_id: self.id,
+            id: self.transaction_id,
+            kind,
+            vault_id: self.vault_id.clone(),
+            user_address: self.user_address.clone(),
+            partner_id: self.partner_id.clone(),
+            tx_hash: self.tx_hash.clone(),
+            status: self.status.clone(),
+            amount: self.amount.to_string(),
+            shares: self.shares_amount.map(|s| s.to_string()),
+            block_number: self.block_number,
+            block_timestamp: self.block_timestamp,
+        })
+    }
+}
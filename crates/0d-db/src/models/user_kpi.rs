@@ -4,7 +4,41 @@ use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::user_kpis;
-use crate::types::{PerformanceMetric, Timeframe};
+use crate::types::{PerformanceMetric, RiskMetric, Timeframe};
+
+/// Assignments used on conflict to overwrite a KPI record with the incoming values
+macro_rules! upsert_excluded_assignments {
+    () => {{
+        use diesel::pg::upsert::excluded;
+
+        (
+            user_kpis::all_time_pnl.eq(excluded(user_kpis::all_time_pnl)),
+            user_kpis::unrealized_pnl.eq(excluded(user_kpis::unrealized_pnl)),
+            user_kpis::realized_pnl.eq(excluded(user_kpis::realized_pnl)),
+            user_kpis::max_drawdown_pct.eq(excluded(user_kpis::max_drawdown_pct)),
+            user_kpis::sharpe_ratio.eq(excluded(user_kpis::sharpe_ratio)),
+            user_kpis::sortino_ratio.eq(excluded(user_kpis::sortino_ratio)),
+            user_kpis::total_deposits.eq(excluded(user_kpis::total_deposits)),
+            user_kpis::total_withdrawals.eq(excluded(user_kpis::total_withdrawals)),
+            user_kpis::total_fees_paid.eq(excluded(user_kpis::total_fees_paid)),
+            user_kpis::calculated_at.eq(excluded(user_kpis::calculated_at)),
+            user_kpis::share_price_used.eq(excluded(user_kpis::share_price_used)),
+            user_kpis::share_balance.eq(excluded(user_kpis::share_balance)),
+            user_kpis::risk_free_rate.eq(excluded(user_kpis::risk_free_rate)),
+            user_kpis::periods_per_year.eq(excluded(user_kpis::periods_per_year)),
+            user_kpis::volatility_pct.eq(excluded(user_kpis::volatility_pct)),
+            user_kpis::var_historical_pct.eq(excluded(user_kpis::var_historical_pct)),
+            user_kpis::var_parametric_pct.eq(excluded(user_kpis::var_parametric_pct)),
+            user_kpis::cvar_pct.eq(excluded(user_kpis::cvar_pct)),
+            user_kpis::calmar_ratio.eq(excluded(user_kpis::calmar_ratio)),
+            user_kpis::omega_ratio.eq(excluded(user_kpis::omega_ratio)),
+            user_kpis::max_drawdown_duration_days
+                .eq(excluded(user_kpis::max_drawdown_duration_days)),
+            user_kpis::recovery_time_days.eq(excluded(user_kpis::recovery_time_days)),
+            user_kpis::updated_at.eq(Some(Utc::now())),
+        )
+    }};
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
 #[diesel(table_name = user_kpis)]
@@ -27,6 +61,16 @@ pub struct UserKpi {
     pub updated_at: Option<DateTime<Utc>>,
     pub sortino_ratio: Option<Decimal>,
     pub share_balance: Option<Decimal>,
+    pub risk_free_rate: Option<Decimal>,
+    pub periods_per_year: Option<Decimal>,
+    pub volatility_pct: Option<Decimal>,
+    pub var_historical_pct: Option<Decimal>,
+    pub var_parametric_pct: Option<Decimal>,
+    pub cvar_pct: Option<Decimal>,
+    pub calmar_ratio: Option<Decimal>,
+    pub omega_ratio: Option<Decimal>,
+    pub max_drawdown_duration_days: Option<Decimal>,
+    pub recovery_time_days: Option<Decimal>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
@@ -46,6 +90,47 @@ pub struct NewUserKpi {
     pub calculated_at: Option<DateTime<Utc>>,
     pub share_price_used: Option<Decimal>,
     pub share_balance: Option<Decimal>,
+    pub risk_free_rate: Option<Decimal>,
+    pub periods_per_year: Option<Decimal>,
+    pub volatility_pct: Option<Decimal>,
+    pub var_historical_pct: Option<Decimal>,
+    pub var_parametric_pct: Option<Decimal>,
+    pub cvar_pct: Option<Decimal>,
+    pub calmar_ratio: Option<Decimal>,
+    pub omega_ratio: Option<Decimal>,
+    pub max_drawdown_duration_days: Option<Decimal>,
+    pub recovery_time_days: Option<Decimal>,
+}
+
+impl NewUserKpi {
+    pub fn from_update(user_address: &str, vault_id: &str, kpi_data: &UserKpiUpdate) -> Self {
+        Self {
+            user_address: user_address.to_string(),
+            vault_id: vault_id.to_string(),
+            all_time_pnl: kpi_data.all_time_pnl,
+            unrealized_pnl: kpi_data.unrealized_pnl,
+            realized_pnl: kpi_data.realized_pnl,
+            max_drawdown_pct: kpi_data.max_drawdown_pct,
+            sharpe_ratio: kpi_data.sharpe_ratio,
+            sortino_ratio: kpi_data.sortino_ratio,
+            total_deposits: kpi_data.total_deposits,
+            total_withdrawals: kpi_data.total_withdrawals,
+            total_fees_paid: kpi_data.total_fees_paid,
+            calculated_at: kpi_data.calculated_at,
+            share_price_used: kpi_data.share_price_used,
+            share_balance: kpi_data.share_balance,
+            risk_free_rate: kpi_data.risk_free_rate,
+            periods_per_year: kpi_data.periods_per_year,
+            volatility_pct: kpi_data.volatility_pct,
+            var_historical_pct: kpi_data.var_historical_pct,
+            var_parametric_pct: kpi_data.var_parametric_pct,
+            cvar_pct: kpi_data.cvar_pct,
+            calmar_ratio: kpi_data.calmar_ratio,
+            omega_ratio: kpi_data.omega_ratio,
+            max_drawdown_duration_days: kpi_data.max_drawdown_duration_days,
+            recovery_time_days: kpi_data.recovery_time_days,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, AsChangeset)]
@@ -63,6 +148,16 @@ pub struct UserKpiUpdate {
     pub calculated_at: Option<DateTime<Utc>>,
     pub share_price_used: Option<Decimal>,
     pub share_balance: Option<Decimal>,
+    pub risk_free_rate: Option<Decimal>,
+    pub periods_per_year: Option<Decimal>,
+    pub volatility_pct: Option<Decimal>,
+    pub var_historical_pct: Option<Decimal>,
+    pub var_parametric_pct: Option<Decimal>,
+    pub cvar_pct: Option<Decimal>,
+    pub calmar_ratio: Option<Decimal>,
+    pub omega_ratio: Option<Decimal>,
+    pub max_drawdown_duration_days: Option<Decimal>,
+    pub recovery_time_days: Option<Decimal>,
     pub updated_at: Option<DateTime<Utc>>,
 }
 
@@ -76,6 +171,20 @@ impl UserKpi {
         }
     }
 
+    /// Get the value for a specific risk metric
+    pub const fn get_risk_metric_value(&self, metric: &RiskMetric) -> Option<Decimal> {
+        crate::risk_metric_field!(self, metric)
+    }
+
+    /// Clear the risk metrics that were not selected
+    pub fn retain_risk_metrics(&mut self, selected: &[RiskMetric]) {
+        for metric in RiskMetric::ALL {
+            if !selected.contains(&metric) {
+                *crate::risk_metric_field!(&mut self, metric) = None;
+            }
+        }
+    }
+
     /// Find a specific user's KPIs for a vault
     pub fn find_by_user_and_vault(
         user_address: &str,
@@ -113,47 +222,33 @@ impl UserKpi {
         kpi_data: &UserKpiUpdate,
         conn: &mut diesel::PgConnection,
     ) -> QueryResult<Self> {
-        use diesel::pg::upsert::excluded;
-
-        let new_kpi = NewUserKpi {
-            user_address: user_address.to_string(),
-            vault_id: vault_id.to_string(),
-            all_time_pnl: kpi_data.all_time_pnl,
-            unrealized_pnl: kpi_data.unrealized_pnl,
-            realized_pnl: kpi_data.realized_pnl,
-            max_drawdown_pct: kpi_data.max_drawdown_pct,
-            sharpe_ratio: kpi_data.sharpe_ratio,
-            sortino_ratio: kpi_data.sortino_ratio,
-            total_deposits: kpi_data.total_deposits,
-            total_withdrawals: kpi_data.total_withdrawals,
-            total_fees_paid: kpi_data.total_fees_paid,
-            calculated_at: kpi_data.calculated_at,
-            share_price_used: kpi_data.share_price_used,
-            share_balance: kpi_data.share_balance,
-        };
+        let new_kpi = NewUserKpi::from_update(user_address, vault_id, kpi_data);
 
         diesel::insert_into(user_kpis::table)
             .values(&new_kpi)
             .on_conflict((user_kpis::user_address, user_kpis::vault_id))
             .do_update()
-            .set((
-                user_kpis::all_time_pnl.eq(excluded(user_kpis::all_time_pnl)),
-                user_kpis::unrealized_pnl.eq(excluded(user_kpis::unrealized_pnl)),
-                user_kpis::realized_pnl.eq(excluded(user_kpis::realized_pnl)),
-                user_kpis::max_drawdown_pct.eq(excluded(user_kpis::max_drawdown_pct)),
-                user_kpis::sharpe_ratio.eq(excluded(user_kpis::sharpe_ratio)),
-                user_kpis::sortino_ratio.eq(excluded(user_kpis::sortino_ratio)),
-                user_kpis::total_deposits.eq(excluded(user_kpis::total_deposits)),
-                user_kpis::total_withdrawals.eq(excluded(user_kpis::total_withdrawals)),
-                user_kpis::total_fees_paid.eq(excluded(user_kpis::total_fees_paid)),
-                user_kpis::calculated_at.eq(excluded(user_kpis::calculated_at)),
-                user_kpis::share_price_used.eq(excluded(user_kpis::share_price_used)),
-                user_kpis::share_balance.eq(excluded(user_kpis::share_balance)),
-                user_kpis::updated_at.eq(Some(Utc::now())),
-            ))
+            .set(upsert_excluded_assignments!())
             .get_result(conn)
     }
 
+    /// Upsert (insert or update) a batch of KPI records in a single statement
+    pub fn upsert_batch(
+        new_kpis: &[NewUserKpi],
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<usize> {
+        if new_kpis.is_empty() {
+            return Ok(0);
+        }
+
+        diesel::insert_into(user_kpis::table)
+            .values(new_kpis)
+            .on_conflict((user_kpis::user_address, user_kpis::vault_id))
+            .do_update()
+            .set(upsert_excluded_assignments!())
+            .execute(conn)
+    }
+
     /// Get historical performance data for a specific metric and timeframe
     /// Returns time series data for `all_time_pnl`, `unrealized_pnl`, or `realized_pnl`
     pub fn get_historical_performance(
@@ -1,8 +1,10 @@
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Nullable, Numeric, Text, Timestamptz};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
+use crate::models::user_position::herfindahl_index;
 use crate::schema::user_portfolio_history;
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
@@ -30,6 +32,35 @@ pub struct NewUserPortfolioHistory {
     pub calculated_at: DateTime<Utc>,
 }
 
+/// Holders of a vault on a day, from the daily portfolio snapshots
+#[derive(Debug, Clone, QueryableByName)]
+pub struct HolderCountBucket {
+    #[diesel(sql_type = Timestamptz)]
+    pub bucket: DateTime<Utc>,
+    #[diesel(sql_type = BigInt)]
+    pub holders: i64,
+    #[diesel(sql_type = Numeric)]
+    pub total_shares: Decimal,
+    #[diesel(sql_type = Numeric)]
+    pub sum_of_squares: Decimal,
+}
+
+/// Latest share price of a vault on a day
+#[derive(Debug, Clone, QueryableByName)]
+struct SharePricePoint {
+    #[diesel(sql_type = Timestamptz)]
+    calculated_at: DateTime<Utc>,
+    #[diesel(sql_type = Numeric)]
+    share_price: Decimal,
+}
+
+impl HolderCountBucket {
+    /// Sum of the squared supply shares of the holders, between 0 and 1
+    pub fn herfindahl_index(&self) -> Decimal {
+        herfindahl_index(self.sum_of_squares, self.total_shares)
+    }
+}
+
 impl UserPortfolioHistory {
     /// Create a new portfolio history record
     pub fn create(
@@ -133,6 +164,111 @@ impl UserPortfolioHistory {
             .get_result(conn)
     }
 
+    /// Insert a batch of portfolio history records
+    pub fn insert_batch(
+        records: &[NewUserPortfolioHistory],
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<usize> {
+        if records.is_empty() {
+            return Ok(0);
+        }
+
+        diesel::insert_into(user_portfolio_history::table)
+            .values(records)
+            .execute(conn)
+    }
+
+    /// Get the portfolio time series of several users in a vault in a single query.
+    /// Returns (`user_address`, timestamp, `portfolio_value`) tuples in chronological order
+    pub fn get_vault_time_series_for_users(
+        vault_id: &str,
+        user_addresses: &[String],
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<(String, DateTime<Utc>, Decimal)>> {
+        user_portfolio_history::table
+            .filter(user_portfolio_history::vault_id.eq(vault_id))
+            .filter(user_portfolio_history::user_address.eq_any(user_addresses))
+            .select((
+                user_portfolio_history::user_address,
+                user_portfolio_history::calculated_at,
+                user_portfolio_history::portfolio_value,
+            ))
+            .order(user_portfolio_history::calculated_at.asc())
+            .load(conn)
+    }
+
+    /// Get the portfolio value series of a user across all vaults, summed per day.
+    /// The latest snapshot of each vault within a day is used for that day, and a vault
+    /// without a snapshot on a day counts with its previous value, up to the time its
+    /// position was closed (`closed_positions` holds the vault ids and closing times).
+    pub fn get_user_aggregate_time_series(
+        user_address: &str,
+        since: Option<DateTime<Utc>>,
+        closed_positions: &[(String, DateTime<Utc>)],
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<(DateTime<Utc>, Decimal)>> {
+        let mut query = user_portfolio_history::table
+            .filter(user_portfolio_history::user_address.eq(user_address))
+            .select((
+                user_portfolio_history::vault_id,
+                user_portfolio_history::calculated_at,
+                user_portfolio_history::portfolio_value,
+            ))
+            .order(user_portfolio_history::calculated_at.asc())
+            .into_boxed();
+
+        // Values carried into the period from the last snapshot of each vault before it
+        let mut latest: std::collections::HashMap<String, Decimal> =
+            std::collections::HashMap::new();
+        if let Some(since) = since {
+            query = query.filter(user_portfolio_history::calculated_at.ge(since));
+
+            let carried: Vec<(String, Decimal)> = user_portfolio_history::table
+                .filter(user_portfolio_history::user_address.eq(user_address))
+                .filter(user_portfolio_history::calculated_at.lt(since))
+                .distinct_on(user_portfolio_history::vault_id)
+                .select((
+                    user_portfolio_history::vault_id,
+                    user_portfolio_history::portfolio_value,
+                ))
+                .order((
+                    user_portfolio_history::vault_id,
+                    user_portfolio_history::calculated_at.desc(),
+                ))
+                .load(conn)?;
+            latest.extend(carried);
+        }
+
+        let rows: Vec<(String, DateTime<Utc>, Decimal)> = query.load(conn)?;
+
+        // Keep the latest value per (day, vault)
+        let mut by_day: std::collections::BTreeMap<
+            chrono::NaiveDate,
+            (DateTime<Utc>, std::collections::HashMap<String, Decimal>),
+        > = std::collections::BTreeMap::new();
+        for (vault_id, ts, value) in rows {
+            let entry = by_day
+                .entry(ts.date_naive())
+                .or_insert_with(|| (ts, std::collections::HashMap::new()));
+            entry.0 = ts;
+            entry.1.insert(vault_id, value);
+        }
+
+        // Then sum the latest value of every vault still held on that day
+        Ok(by_day
+            .into_iter()
+            .map(|(day, (ts, values))| {
+                latest.extend(values);
+                latest.retain(|vault_id, _| {
+                    !closed_positions.iter().any(|(closed, closed_at)| {
+                        closed == vault_id && closed_at.date_naive() < day
+                    })
+                });
+                (ts, latest.values().sum())
+            })
+            .collect())
+    }
+
     /// Get distinct share price time series for a vault (one point per day).
     /// Share price is vault-level (same for all users), so we pick one row per day.
     pub fn get_share_price_series(
@@ -166,6 +302,128 @@ impl UserPortfolioHistory {
         Ok(by_day.into_values().collect())
     }
 
+    /// Get a page of the daily share price series of a vault, the points after `after`,
+    /// oldest first. Same points as [`Self::get_share_price_series`].
+    pub fn get_share_price_series_page(
+        vault_id: &str,
+        since: Option<DateTime<Utc>>,
+        after: Option<DateTime<Utc>>,
+        limit: i64,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<(DateTime<Utc>, Decimal)>> {
+        let points: Vec<SharePricePoint> = diesel::sql_query(
+            "SELECT calculated_at, share_price
+             FROM (
+                SELECT DISTINCT ON (date_trunc('day', calculated_at AT TIME ZONE 'UTC'))
+                    calculated_at, share_price
+                FROM user_portfolio_history
+                WHERE vault_id = $1 AND ($2 IS NULL OR calculated_at >= $2)
+                ORDER BY date_trunc('day', calculated_at AT TIME ZONE 'UTC'), calculated_at DESC
+             ) daily
+             WHERE $3 IS NULL OR calculated_at > $3
+             ORDER BY calculated_at
+             LIMIT $4",
+        )
+        .bind::<Text, _>(vault_id)
+        .bind::<Nullable<Timestamptz>, _>(since)
+        .bind::<Nullable<Timestamptz>, _>(after)
+        .bind::<BigInt, _>(limit)
+        .load(conn)?;
+
+        Ok(points
+            .into_iter()
+            .map(|point| (point.calculated_at, point.share_price))
+            .collect())
+    }
+
+    /// Get the latest recorded share price of a vault strictly before a point in time
+    pub fn find_share_price_before(
+        vault_id: &str,
+        before: DateTime<Utc>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Option<Decimal>> {
+        user_portfolio_history::table
+            .filter(user_portfolio_history::vault_id.eq(vault_id))
+            .filter(user_portfolio_history::calculated_at.lt(before))
+            .order(user_portfolio_history::calculated_at.desc())
+            .select(user_portfolio_history::share_price)
+            .first(conn)
+            .optional()
+    }
+
+    /// Get the latest recorded share price of a vault strictly before a point in time,
+    /// along with the time it was recorded at
+    pub fn find_share_price_point_before(
+        vault_id: &str,
+        before: DateTime<Utc>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Option<(DateTime<Utc>, Decimal)>> {
+        user_portfolio_history::table
+            .filter(user_portfolio_history::vault_id.eq(vault_id))
+            .filter(user_portfolio_history::calculated_at.lt(before))
+            .order(user_portfolio_history::calculated_at.desc())
+            .select((
+                user_portfolio_history::calculated_at,
+                user_portfolio_history::share_price,
+            ))
+            .first(conn)
+            .optional()
+    }
+
+    /// Get the share balance of every user of a vault at a point in time, from their latest
+    /// snapshot at or before it
+    pub fn find_share_balances_at(
+        vault_id: &str,
+        at: DateTime<Utc>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<(String, Decimal)>> {
+        user_portfolio_history::table
+            .filter(user_portfolio_history::vault_id.eq(vault_id))
+            .filter(user_portfolio_history::calculated_at.le(at))
+            .distinct_on(user_portfolio_history::user_address)
+            .order((
+                user_portfolio_history::user_address,
+                user_portfolio_history::calculated_at.desc(),
+            ))
+            .select((
+                user_portfolio_history::user_address,
+                user_portfolio_history::share_balance,
+            ))
+            .load(conn)
+    }
+
+    /// Count the holders of a vault per day, oldest first. The latest snapshot of a
+    /// user within a day is used for that day.
+    pub fn holder_count_series(
+        vault_id: &str,
+        since: Option<DateTime<Utc>>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<HolderCountBucket>> {
+        diesel::sql_query(
+            "WITH daily AS (
+                SELECT DISTINCT ON (user_address, date_trunc('day', calculated_at, 'UTC'))
+                    date_trunc('day', calculated_at, 'UTC') AS bucket,
+                    user_address,
+                    share_balance
+                FROM user_portfolio_history
+                WHERE vault_id = $1
+                  AND ($2 IS NULL OR calculated_at >= $2)
+                ORDER BY user_address, date_trunc('day', calculated_at, 'UTC'), calculated_at DESC
+             )
+             SELECT
+                bucket,
+                COUNT(*) FILTER (WHERE share_balance > 0) AS holders,
+                COALESCE(SUM(share_balance), 0) AS total_shares,
+                COALESCE(SUM(share_balance * share_balance), 0) AS sum_of_squares
+             FROM daily
+             GROUP BY bucket
+             ORDER BY bucket",
+        )
+        .bind::<Text, _>(vault_id)
+        .bind::<Nullable<Timestamptz>, _>(since)
+        .load(conn)
+    }
+
     /// Get the latest portfolio history record for a user/vault
     pub fn find_latest_by_user_and_vault(
         user_address: &str,
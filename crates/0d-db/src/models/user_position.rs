@@ -1,9 +1,11 @@
 use chrono::{DateTime, Utc};
 use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Numeric, Text};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 
 use crate::schema::user_positions;
+use crate::types::{HolderSort, SortOrder};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
 #[diesel(table_name = user_positions)]
@@ -40,6 +42,61 @@ pub struct UserPositionUpdate {
     pub updated_at: Option<DateTime<Utc>>,
 }
 
+/// Distribution of the shares of a vault among its holders
+#[derive(Debug, Clone, QueryableByName)]
+pub struct HolderConcentration {
+    #[diesel(sql_type = BigInt)]
+    pub holders: i64,
+    #[diesel(sql_type = Numeric)]
+    pub total_shares: Decimal,
+    /// Sum of the squared share balances, from which the Herfindahl index is derived
+    #[diesel(sql_type = Numeric)]
+    pub sum_of_squares: Decimal,
+    #[diesel(sql_type = Numeric)]
+    pub top_10_shares: Decimal,
+}
+
+impl HolderConcentration {
+    /// Share of the supply held by the 10 largest holders, between 0 and 1
+    pub fn top_10_share(&self) -> Decimal {
+        if self.total_shares.is_zero() {
+            return Decimal::ZERO;
+        }
+        self.top_10_shares / self.total_shares
+    }
+
+    /// Sum of the squared supply shares of the holders, between 0 and 1
+    pub fn herfindahl_index(&self) -> Decimal {
+        herfindahl_index(self.sum_of_squares, self.total_shares)
+    }
+}
+
+/// Herfindahl index of a supply from the sum of the squared holdings, between 0 and 1
+pub fn herfindahl_index(sum_of_squares: Decimal, total_shares: Decimal) -> Decimal {
+    if total_shares.is_zero() {
+        return Decimal::ZERO;
+    }
+    sum_of_squares / (total_shares * total_shares)
+}
+
+/// Last holder of a page of [`UserPosition::find_holders`], the next page starts after it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HolderCursor {
+    pub share_balance: Decimal,
+    pub first_deposit_at: Option<DateTime<Utc>>,
+    pub user_address: String,
+}
+
+impl From<&UserPosition> for HolderCursor {
+    fn from(position: &UserPosition) -> Self {
+        Self {
+            share_balance: position.share_balance,
+            first_deposit_at: position.first_deposit_at,
+            user_address: position.user_address.clone(),
+        }
+    }
+}
+
 impl UserPosition {
     /// Find a specific user's position in a vault
     pub fn find_by_user_and_vault(
@@ -64,6 +121,132 @@ impl UserPosition {
             .load(conn)
     }
 
+    /// Find a page of the active positions of a vault, after the holder of `after`. Ties are
+    /// broken by user address, and holders without first deposit come last.
+    pub fn find_holders(
+        vault_id: &str,
+        sort: HolderSort,
+        order: SortOrder,
+        after: Option<&HolderCursor>,
+        limit: i64,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        use crate::schema::user_positions::dsl::{first_deposit_at, share_balance, user_address};
+
+        let mut query = user_positions::table
+            .filter(user_positions::vault_id.eq(vault_id))
+            .filter(share_balance.gt(Decimal::from(0)))
+            .into_boxed();
+
+        if let Some(after) = after {
+            let address = after.user_address.clone();
+            query = match (sort, order, after.first_deposit_at) {
+                (HolderSort::ShareBalance, SortOrder::Asc, _) => query.filter(
+                    share_balance.gt(after.share_balance).or(share_balance
+                        .eq(after.share_balance)
+                        .and(user_address.gt(address))),
+                ),
+                (HolderSort::ShareBalance, SortOrder::Desc, _) => query.filter(
+                    share_balance.lt(after.share_balance).or(share_balance
+                        .eq(after.share_balance)
+                        .and(user_address.gt(address))),
+                ),
+                (HolderSort::FirstDepositAt, _, None) => {
+                    query.filter(first_deposit_at.is_null().and(user_address.gt(address)))
+                }
+                (HolderSort::FirstDepositAt, SortOrder::Asc, Some(at)) => query.filter(
+                    first_deposit_at
+                        .is_null()
+                        .or(first_deposit_at.gt(at))
+                        .or(first_deposit_at.eq(at).and(user_address.gt(address))),
+                ),
+                (HolderSort::FirstDepositAt, SortOrder::Desc, Some(at)) => query.filter(
+                    first_deposit_at
+                        .is_null()
+                        .or(first_deposit_at.lt(at))
+                        .or(first_deposit_at.eq(at).and(user_address.gt(address))),
+                ),
+            };
+        }
+
+        let query = match (sort, order) {
+            (HolderSort::ShareBalance, SortOrder::Asc) => {
+                query.order((share_balance.asc(), user_address.asc()))
+            }
+            (HolderSort::ShareBalance, SortOrder::Desc) => {
+                query.order((share_balance.desc(), user_address.asc()))
+            }
+            (HolderSort::FirstDepositAt, SortOrder::Asc) => {
+                query.order((first_deposit_at.asc().nulls_last(), user_address.asc()))
+            }
+            (HolderSort::FirstDepositAt, SortOrder::Desc) => {
+                query.order((first_deposit_at.desc().nulls_last(), user_address.asc()))
+            }
+        };
+
+        query.limit(limit).load(conn)
+    }
+
+    /// Compute how concentrated the shares of a vault are among its holders
+    pub fn holder_concentration(
+        vault_id: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<HolderConcentration> {
+        diesel::sql_query(
+            "SELECT
+                COUNT(*) AS holders,
+                COALESCE(SUM(share_balance), 0) AS total_shares,
+                COALESCE(SUM(share_balance * share_balance), 0) AS sum_of_squares,
+                COALESCE((
+                    SELECT SUM(top.share_balance)
+                    FROM (
+                        SELECT share_balance
+                        FROM user_positions
+                        WHERE vault_id = $1 AND share_balance > 0
+                        ORDER BY share_balance DESC
+                        LIMIT 10
+                    ) top
+                ), 0) AS top_10_shares
+             FROM user_positions
+             WHERE vault_id = $1 AND share_balance > 0",
+        )
+        .bind::<Text, _>(vault_id)
+        .get_result(conn)
+    }
+
+    /// Find active positions of a user across all vaults
+    pub fn find_active_by_user(
+        user_address: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        user_positions::table
+            .filter(user_positions::user_address.eq(user_address))
+            .filter(user_positions::share_balance.gt(Decimal::from(0)))
+            .order(user_positions::vault_id.asc())
+            .load(conn)
+    }
+
+    /// Find every position a user has held across all vaults, including closed ones
+    pub fn find_by_user(
+        user_address: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        user_positions::table
+            .filter(user_positions::user_address.eq(user_address))
+            .order(user_positions::vault_id.asc())
+            .load(conn)
+    }
+
+    /// Find the positions of several users across all vaults
+    pub fn find_by_users(
+        user_addresses: &[String],
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        user_positions::table
+            .filter(user_positions::user_address.eq_any(user_addresses))
+            .load(conn)
+    }
+
     /// Create a new position
     pub fn create(
         new_position: &NewUserPosition,
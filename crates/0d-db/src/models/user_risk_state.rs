@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+
+use crate::schema::user_risk_states;
+
+/// Serialized risk-metric accumulators for a user position, used to compute
+/// risk metrics incrementally instead of reloading the full portfolio history
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = user_risk_states)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct UserRiskState {
+    pub id: i32,
+    pub user_address: String,
+    pub vault_id: String,
+    pub state: JsonValue,
+    pub last_point_at: DateTime<Utc>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = user_risk_states)]
+pub struct NewUserRiskState {
+    pub user_address: String,
+    pub vault_id: String,
+    pub state: JsonValue,
+    pub last_point_at: DateTime<Utc>,
+}
+
+impl UserRiskState {
+    /// Find all risk states for a vault
+    pub fn find_by_vault(
+        vault_id: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        user_risk_states::table
+            .filter(user_risk_states::vault_id.eq(vault_id))
+            .load(conn)
+    }
+
+    /// Upsert a batch of risk states
+    pub fn upsert_batch(
+        states: &[NewUserRiskState],
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<usize> {
+        use diesel::pg::upsert::excluded;
+
+        if states.is_empty() {
+            return Ok(0);
+        }
+
+        diesel::insert_into(user_risk_states::table)
+            .values(states)
+            .on_conflict((user_risk_states::user_address, user_risk_states::vault_id))
+            .do_update()
+            .set((
+                user_risk_states::state.eq(excluded(user_risk_states::state)),
+                user_risk_states::last_point_at.eq(excluded(user_risk_states::last_point_at)),
+                user_risk_states::updated_at.eq(Some(Utc::now())),
+            ))
+            .execute(conn)
+    }
+}
@@ -1,5 +1,5 @@
 use chrono::{DateTime, Utc};
-use diesel::sql_types::{BigInt, Nullable, Text};
+use diesel::sql_types::{BigInt, Nullable, Numeric, Text, Timestamptz};
 use diesel::{dsl::exists, prelude::*, select};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
@@ -7,6 +7,7 @@ use serde_json::Value as JsonValue;
 use utoipa::ToSchema;
 
 use crate::schema::user_transactions::{self};
+use crate::types::{BucketInterval, SortOrder};
 
 #[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
 #[diesel(table_name = user_transactions)]
@@ -20,6 +21,8 @@ pub struct UserTransaction {
     pub vault_id: String,
     pub type_: String,
     pub status: String,
+    /// In base asset. A withdraw books the nominal of its redeem request, the assets received
+    /// on claim are recorded on the redeem request.
     pub amount: Decimal,
     pub partner_id: Option<String>,
     pub shares_amount: Option<Decimal>,
@@ -127,6 +130,49 @@ impl TransactionType {
     }
 }
 
+/// Deposits and redemptions of a vault over a time bucket, in its underlying currency.
+/// Redemptions are counted when requested.
+#[derive(Debug, Clone, QueryableByName)]
+pub struct VaultFlowBucket {
+    #[diesel(sql_type = Timestamptz)]
+    pub bucket: DateTime<Utc>,
+    #[diesel(sql_type = Numeric)]
+    pub deposits: Decimal,
+    #[diesel(sql_type = Numeric)]
+    pub redemptions: Decimal,
+    #[diesel(sql_type = BigInt)]
+    pub unique_depositors: i64,
+    /// Depositors whose first deposit in the vault is in the bucket
+    #[diesel(sql_type = BigInt)]
+    pub new_users: i64,
+}
+
+/// Deposits and redemptions of a vault over a whole period
+#[derive(Debug, Clone, QueryableByName)]
+pub struct VaultFlowTotals {
+    #[diesel(sql_type = Numeric)]
+    pub deposits: Decimal,
+    #[diesel(sql_type = Numeric)]
+    pub redemptions: Decimal,
+    #[diesel(sql_type = BigInt)]
+    pub unique_depositors: i64,
+    /// Depositors whose first deposit in the vault is in the period
+    #[diesel(sql_type = BigInt)]
+    pub new_users: i64,
+}
+
+/// Filters of the transaction history of a user. Empty filters match every transaction.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct UserTransactionFilter {
+    pub vault_ids: Vec<String>,
+    pub type_: Option<TransactionType>,
+    pub status: Option<TransactionStatus>,
+    /// Inclusive lower bound of the block timestamp
+    pub from: Option<DateTime<Utc>>,
+    /// Exclusive upper bound of the block timestamp
+    pub to: Option<DateTime<Utc>>,
+}
+
 // Transaction status enum for better type safety
 #[derive(Debug, Clone, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
@@ -167,6 +213,22 @@ impl UserTransaction {
             .optional()
     }
 
+    /// Find the withdraw transaction of a user in a vault recorded by a transaction hash
+    pub fn find_withdraw(
+        vault_id: &str,
+        user_address: &str,
+        tx_hash: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Option<Self>> {
+        user_transactions::table
+            .filter(user_transactions::vault_id.eq(vault_id))
+            .filter(user_transactions::user_address.eq(user_address))
+            .filter(user_transactions::tx_hash.eq(tx_hash))
+            .filter(user_transactions::type_.eq(TransactionType::Withdraw.as_str()))
+            .first(conn)
+            .optional()
+    }
+
     /// Find transactions for a user in a specific vault
     pub fn find_by_user_and_vault(
         user_address: &str,
@@ -193,23 +255,105 @@ impl UserTransaction {
             .load(conn)
     }
 
-    /// Find pending transactions for a user (optionally filtered by vault)
-    pub fn find_pending_by_user(
+    /// Find all transactions of a user across vaults in chronological order
+    pub fn find_by_user_chronological(
         user_address: &str,
-        vault_id: Option<&str>,
         conn: &mut diesel::PgConnection,
     ) -> QueryResult<Vec<Self>> {
-        let mut query = user_transactions::table
+        user_transactions::table
             .filter(user_transactions::user_address.eq(user_address))
-            .filter(user_transactions::status.eq(TransactionStatus::Pending.as_str()))
+            .order((
+                user_transactions::block_timestamp.asc(),
+                user_transactions::id.asc(),
+            ))
+            .load(conn)
+    }
+
+    /// Find all transactions of a vault in chronological order
+    pub fn find_by_vault_chronological(
+        vault_id: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        user_transactions::table
+            .filter(user_transactions::vault_id.eq(vault_id))
+            .order((
+                user_transactions::block_timestamp.asc(),
+                user_transactions::id.asc(),
+            ))
+            .load(conn)
+    }
+
+    /// Find the deposits made through a partner, or through any partner when `partner_id` is
+    /// `None`, oldest first
+    pub fn find_partner_deposits(
+        partner_id: Option<&str>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        let mut query = user_transactions::table
+            .filter(user_transactions::type_.eq(TransactionType::Deposit.as_str()))
+            .filter(user_transactions::partner_id.is_not_null())
             .into_boxed();
 
-        if let Some(vault) = vault_id {
-            query = query.filter(user_transactions::vault_id.eq(vault));
+        if let Some(partner) = partner_id {
+            query = query.filter(user_transactions::partner_id.eq(partner));
         }
 
         query
-            .order(user_transactions::block_timestamp.desc())
+            .order((
+                user_transactions::block_timestamp.asc(),
+                user_transactions::id.asc(),
+            ))
+            .load(conn)
+    }
+
+    /// Find the vaults deposits were made into through a partner
+    pub fn find_partner_vault_ids(
+        partner_id: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<String>> {
+        user_transactions::table
+            .filter(user_transactions::type_.eq(TransactionType::Deposit.as_str()))
+            .filter(user_transactions::partner_id.eq(partner_id))
+            .select(user_transactions::vault_id)
+            .distinct()
+            .order(user_transactions::vault_id.asc())
+            .load(conn)
+    }
+
+    /// Find the partner a position was referred by: the partner of the first deposit the user
+    /// made in the vault through a partner
+    pub fn find_referring_partner(
+        user_address: &str,
+        vault_id: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Option<String>> {
+        user_transactions::table
+            .filter(user_transactions::user_address.eq(user_address))
+            .filter(user_transactions::vault_id.eq(vault_id))
+            .filter(user_transactions::type_.eq(TransactionType::Deposit.as_str()))
+            .filter(user_transactions::partner_id.is_not_null())
+            .order((
+                user_transactions::block_timestamp.asc(),
+                user_transactions::id.asc(),
+            ))
+            .select(user_transactions::partner_id)
+            .first::<Option<String>>(conn)
+            .optional()
+            .map(Option::flatten)
+    }
+
+    /// Find the withdrawals of several users across vaults, oldest first
+    pub fn find_withdrawals_by_users(
+        user_addresses: &[String],
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        user_transactions::table
+            .filter(user_transactions::user_address.eq_any(user_addresses))
+            .filter(user_transactions::type_.eq(TransactionType::Withdraw.as_str()))
+            .order((
+                user_transactions::block_timestamp.asc(),
+                user_transactions::id.asc(),
+            ))
             .load(conn)
     }
 
@@ -253,124 +397,66 @@ impl UserTransaction {
         self.update(&updates, conn)
     }
 
-    /// Calculate total deposits for a user in a specific vault
-    pub fn total_deposits_by_user_and_vault(
+    /// Find the transactions of a user matching a filter, ordered by time then id.
+    /// Uses keyset pagination: only the transactions after the `(block_timestamp, id)`
+    /// position in that order are returned.
+    pub fn find_by_user_filtered(
         user_address: &str,
-        vault_id: &str,
-        conn: &mut diesel::PgConnection,
-    ) -> QueryResult<Option<Decimal>> {
-        use diesel::dsl::sum;
-
-        user_transactions::table
-            .filter(user_transactions::user_address.eq(user_address))
-            .filter(user_transactions::vault_id.eq(vault_id))
-            .filter(user_transactions::type_.eq(TransactionType::Deposit.as_str()))
-            .filter(user_transactions::status.eq(TransactionStatus::Confirmed.as_str()))
-            .select(sum(user_transactions::amount))
-            .first(conn)
-    }
-
-    /// Find transactions for a user in a specific vault with pagination
-    /// Uses ID-based cursor pagination for efficient database queries
-    pub fn find_by_user_and_vault_paginated(
-        user_address: &str,
-        vault_id: &str,
-        transaction_type: Option<&str>,
-        cursor_id: Option<i32>,
+        filter: &UserTransactionFilter,
+        order: SortOrder,
+        after: Option<(DateTime<Utc>, i32)>,
         limit: i64,
         conn: &mut diesel::PgConnection,
     ) -> QueryResult<Vec<Self>> {
-        use diesel::prelude::*;
-
-        match (transaction_type, cursor_id) {
-            (Some(tx_type), Some(cursor)) => {
-                // Both filters applied
-                user_transactions::table
-                    .filter(user_transactions::user_address.eq(user_address))
-                    .filter(user_transactions::vault_id.eq(vault_id))
-                    .filter(user_transactions::type_.eq(tx_type))
-                    .filter(user_transactions::id.lt(cursor))
-                    .order(user_transactions::block_timestamp.desc())
-                    .limit(limit)
-                    .load(conn)
-            }
-            (Some(tx_type), None) => {
-                // Only transaction type filter
-                user_transactions::table
-                    .filter(user_transactions::user_address.eq(user_address))
-                    .filter(user_transactions::vault_id.eq(vault_id))
-                    .filter(user_transactions::type_.eq(tx_type))
-                    .order(user_transactions::block_timestamp.desc())
-                    .limit(limit)
-                    .load(conn)
-            }
-            (None, Some(cursor)) => {
-                // Only cursor filter
-                user_transactions::table
-                    .filter(user_transactions::user_address.eq(user_address))
-                    .filter(user_transactions::vault_id.eq(vault_id))
-                    .filter(user_transactions::id.lt(cursor))
-                    .order(user_transactions::block_timestamp.desc())
-                    .limit(limit)
-                    .load(conn)
-            }
-            (None, None) => {
-                // No filters
-                user_transactions::table
-                    .filter(user_transactions::user_address.eq(user_address))
-                    .filter(user_transactions::vault_id.eq(vault_id))
-                    .order(user_transactions::block_timestamp.desc())
-                    .limit(limit)
-                    .load(conn)
-            }
-        }
-    }
-
-    /// Find redeem transaction by `redeem_id` regardless of status (pending or confirmed).
-    /// Used for idempotent handling of `RedeemClaimed` events.
-    pub fn find_redeem_by_id(
-        user_address: &str,
-        vault_id: &str,
-        redeem_id: &str,
-        conn: &mut diesel::PgConnection,
-    ) -> QueryResult<Self> {
-        use diesel::prelude::*;
-
-        user_transactions::table
+        let mut query = user_transactions::table
             .filter(user_transactions::user_address.eq(user_address))
-            .filter(user_transactions::vault_id.eq(vault_id))
-            .filter(user_transactions::type_.eq(TransactionType::Withdraw.as_str()))
-            .filter(user_transactions::metadata.is_not_null())
-            .load::<Self>(conn)?
-            .into_iter()
-            .find(|tx| {
-                if let Some(metadata) = &tx.metadata
-                    && let Some(id) = metadata.get("redeem_id")
-                    && let Some(id_str) = id.as_str()
-                {
-                    return id_str == redeem_id;
-                }
-                false
-            })
-            .ok_or(diesel::result::Error::NotFound)
-    }
+            .into_boxed();
 
-    /// Update transaction status and amount
-    pub fn update_status_and_amount(
-        id: i32,
-        status: &str,
-        amount: Decimal,
-        conn: &mut diesel::PgConnection,
-    ) -> QueryResult<Self> {
-        use diesel::prelude::*;
+        if !filter.vault_ids.is_empty() {
+            query = query.filter(user_transactions::vault_id.eq_any(&filter.vault_ids));
+        }
+        if let Some(type_) = &filter.type_ {
+            query = query.filter(user_transactions::type_.eq(type_.as_str()));
+        }
+        if let Some(status) = &filter.status {
+            query = query.filter(user_transactions::status.eq(status.as_str()));
+        }
+        if let Some(from) = filter.from {
+            query = query.filter(user_transactions::block_timestamp.ge(from));
+        }
+        if let Some(to) = filter.to {
+            query = query.filter(user_transactions::block_timestamp.lt(to));
+        }
 
-        diesel::update(user_transactions::table.find(id))
-            .set((
-                user_transactions::status.eq(status),
-                user_transactions::amount.eq(amount),
-                user_transactions::updated_at.eq(Utc::now()),
-            ))
-            .get_result(conn)
+        query = match (order, after) {
+            (SortOrder::Asc, Some((t, id))) => query.filter(
+                user_transactions::block_timestamp
+                    .gt(t)
+                    .or(user_transactions::block_timestamp
+                        .eq(t)
+                        .and(user_transactions::id.gt(id))),
+            ),
+            (SortOrder::Desc, Some((t, id))) => query.filter(
+                user_transactions::block_timestamp
+                    .lt(t)
+                    .or(user_transactions::block_timestamp
+                        .eq(t)
+                        .and(user_transactions::id.lt(id))),
+            ),
+            (_, None) => query,
+        };
+        query = match order {
+            SortOrder::Asc => query.order((
+                user_transactions::block_timestamp.asc(),
+                user_transactions::id.asc(),
+            )),
+            SortOrder::Desc => query.order((
+                user_transactions::block_timestamp.desc(),
+                user_transactions::id.desc(),
+            )),
+        };
+
+        query.limit(limit).load(conn)
     }
 
     /// Calculate total deposits from a collection of transactions
@@ -397,50 +483,86 @@ impl UserTransaction {
         transactions.iter().filter_map(|tx| tx.gas_fee).sum()
     }
 
-    /// Calculate average redeem delay in seconds for a user (optionally filtered by vault)
-    /// Returns the average time between redeem request (`created_at`) and claim (`updated_at`)
-    /// Only considers confirmed withdraw transactions
-    pub fn calculate_average_redeem_delay(
-        user_address: &str,
-        vault_id: Option<&str>,
+    /// Aggregate the flows of a vault per UTC day or week, oldest first,
+    /// starting after the bucket `after`. Buckets without any deposit or redemption are
+    /// omitted.
+    pub fn vault_flow_series(
+        vault_id: &str,
+        interval: BucketInterval,
+        since: Option<DateTime<Utc>>,
+        after: Option<DateTime<Utc>>,
+        limit: i64,
         conn: &mut diesel::PgConnection,
-    ) -> QueryResult<Option<i64>> {
-        #[derive(QueryableByName)]
-        struct AvgDelayResult {
-            #[diesel(sql_type = Nullable<BigInt>)]
-            avg_delay_seconds: Option<i64>,
-        }
-
-        let query_str = if vault_id.is_some() {
-            "SELECT CAST(AVG(EXTRACT(EPOCH FROM (updated_at - created_at))) AS BIGINT) as avg_delay_seconds
-             FROM user_transactions
-             WHERE user_address = $1
-               AND vault_id = $2
-               AND type = 'withdraw'
-               AND status = 'confirmed'
-               AND created_at IS NOT NULL
-               AND updated_at IS NOT NULL"
-        } else {
-            "SELECT CAST(AVG(EXTRACT(EPOCH FROM (updated_at - created_at))) AS BIGINT) as avg_delay_seconds
-             FROM user_transactions
-             WHERE user_address = $1
-               AND type = 'withdraw'
-               AND status = 'confirmed'
-               AND created_at IS NOT NULL
-               AND updated_at IS NOT NULL"
-        };
+    ) -> QueryResult<Vec<VaultFlowBucket>> {
+        diesel::sql_query(
+            "WITH first_deposits AS (
+                SELECT user_address, MIN(block_timestamp) AS first_deposit_at
+                FROM user_transactions
+                WHERE vault_id = $1
+                  AND type = 'deposit'
+                  AND status NOT IN ('failed', 'cancelled')
+                GROUP BY user_address
+             )
+             SELECT
+                date_trunc($2, t.block_timestamp, 'UTC') AS bucket,
+                COALESCE(SUM(t.amount) FILTER (WHERE t.type = 'deposit'), 0) AS deposits,
+                COALESCE(SUM(t.amount) FILTER (WHERE t.type = 'withdraw'), 0) AS redemptions,
+                COUNT(DISTINCT t.user_address) FILTER (WHERE t.type = 'deposit') AS unique_depositors,
+                COUNT(DISTINCT t.user_address) FILTER (
+                    WHERE t.type = 'deposit'
+                      AND f.first_deposit_at >= date_trunc($2, t.block_timestamp, 'UTC')
+                ) AS new_users
+             FROM user_transactions t
+             LEFT JOIN first_deposits f ON f.user_address = t.user_address
+             WHERE t.vault_id = $1
+               AND t.type IN ('deposit', 'withdraw')
+               AND t.status NOT IN ('failed', 'cancelled')
+               AND ($3 IS NULL OR t.block_timestamp >= $3)
+               AND ($4 IS NULL OR date_trunc($2, t.block_timestamp, 'UTC') > $4)
+             GROUP BY bucket
+             ORDER BY bucket
+             LIMIT $5",
+        )
+        .bind::<Text, _>(vault_id)
+        .bind::<Text, _>(interval.as_pg_field())
+        .bind::<Nullable<Timestamptz>, _>(since)
+        .bind::<Nullable<Timestamptz>, _>(after)
+        .bind::<BigInt, _>(limit)
+        .load(conn)
+    }
 
-        if let Some(vault) = vault_id {
-            diesel::sql_query(query_str)
-                .bind::<Text, _>(user_address)
-                .bind::<Text, _>(vault)
-                .get_result::<AvgDelayResult>(conn)
-                .map(|result| result.avg_delay_seconds)
-        } else {
-            diesel::sql_query(query_str)
-                .bind::<Text, _>(user_address)
-                .get_result::<AvgDelayResult>(conn)
-                .map(|result| result.avg_delay_seconds)
-        }
+    /// Aggregate the flows of a vault since a point in time, or over its whole history
+    pub fn vault_flow_totals(
+        vault_id: &str,
+        since: Option<DateTime<Utc>>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<VaultFlowTotals> {
+        diesel::sql_query(
+            "WITH first_deposits AS (
+                SELECT user_address, MIN(block_timestamp) AS first_deposit_at
+                FROM user_transactions
+                WHERE vault_id = $1
+                  AND type = 'deposit'
+                  AND status NOT IN ('failed', 'cancelled')
+                GROUP BY user_address
+             )
+             SELECT
+                COALESCE(SUM(t.amount) FILTER (WHERE t.type = 'deposit'), 0) AS deposits,
+                COALESCE(SUM(t.amount) FILTER (WHERE t.type = 'withdraw'), 0) AS redemptions,
+                COUNT(DISTINCT t.user_address) FILTER (WHERE t.type = 'deposit') AS unique_depositors,
+                COUNT(DISTINCT t.user_address) FILTER (
+                    WHERE t.type = 'deposit'
+                      AND ($2 IS NULL OR f.first_deposit_at >= $2)
+                ) AS new_users
+             FROM user_transactions t
+             LEFT JOIN first_deposits f ON f.user_address = t.user_address
+             WHERE t.vault_id = $1
+               AND t.type IN ('deposit', 'withdraw')
+               AND t.status NOT IN ('failed', 'cancelled')
+               AND ($2 IS NULL OR t.block_timestamp >= $2)",
+        )
+        .bind::<Text, _>(vault_id)
+        .bind::<Nullable<Timestamptz>, _>(since)
+        .get_result(conn)
     }
 }
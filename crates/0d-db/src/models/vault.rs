@@ -78,6 +78,19 @@ impl Vault {
         vaults::table.load(conn)
     }
 
+    /// Find the vaults listed after a vault id, by id
+    pub fn find_page(
+        after: Option<&str>,
+        limit: i64,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        let mut query = vaults::table.order(vaults::id.asc()).into_boxed();
+        if let Some(after) = after {
+            query = query.filter(vaults::id.gt(after));
+        }
+        query.limit(limit).load(conn)
+    }
+
     pub fn find_by_chain(chain: &str, conn: &mut diesel::PgConnection) -> QueryResult<Vec<Self>> {
         vaults::table.filter(vaults::chain.eq(chain)).load(conn)
     }
@@ -0,0 +1,81 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::vault_kpis;
+use crate::types::RiskMetric;
+
+/// Risk analytics computed on the vault share price series
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = vault_kpis)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct VaultKpi {
+    pub id: i32,
+    pub vault_id: String,
+    pub max_drawdown_pct: Option<Decimal>,
+    pub sharpe_ratio: Option<Decimal>,
+    pub sortino_ratio: Option<Decimal>,
+    pub volatility_pct: Option<Decimal>,
+    pub var_historical_pct: Option<Decimal>,
+    pub var_parametric_pct: Option<Decimal>,
+    pub cvar_pct: Option<Decimal>,
+    pub calmar_ratio: Option<Decimal>,
+    pub omega_ratio: Option<Decimal>,
+    pub max_drawdown_duration_days: Option<Decimal>,
+    pub recovery_time_days: Option<Decimal>,
+    pub risk_free_rate: Option<Decimal>,
+    pub periods_per_year: Option<Decimal>,
+    pub calculated_at: Option<DateTime<Utc>>,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable, AsChangeset)]
+#[diesel(table_name = vault_kpis)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewVaultKpi {
+    pub vault_id: String,
+    pub max_drawdown_pct: Option<Decimal>,
+    pub sharpe_ratio: Option<Decimal>,
+    pub sortino_ratio: Option<Decimal>,
+    pub volatility_pct: Option<Decimal>,
+    pub var_historical_pct: Option<Decimal>,
+    pub var_parametric_pct: Option<Decimal>,
+    pub cvar_pct: Option<Decimal>,
+    pub calmar_ratio: Option<Decimal>,
+    pub omega_ratio: Option<Decimal>,
+    pub max_drawdown_duration_days: Option<Decimal>,
+    pub recovery_time_days: Option<Decimal>,
+    pub risk_free_rate: Option<Decimal>,
+    pub periods_per_year: Option<Decimal>,
+    pub calculated_at: Option<DateTime<Utc>>,
+}
+
+impl VaultKpi {
+    /// Get the value for a specific risk metric
+    pub const fn get_risk_metric_value(&self, metric: &RiskMetric) -> Option<Decimal> {
+        crate::risk_metric_field!(self, metric)
+    }
+
+    /// Find the KPIs of a vault
+    pub fn find_by_vault(
+        vault_id: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Option<Self>> {
+        vault_kpis::table
+            .filter(vault_kpis::vault_id.eq(vault_id))
+            .first(conn)
+            .optional()
+    }
+
+    /// Upsert (insert or update) the KPI record of a vault
+    pub fn upsert(new_kpi: &NewVaultKpi, conn: &mut diesel::PgConnection) -> QueryResult<Self> {
+        diesel::insert_into(vault_kpis::table)
+            .values(new_kpi)
+            .on_conflict(vault_kpis::vault_id)
+            .do_update()
+            .set((new_kpi, vault_kpis::updated_at.eq(Some(Utc::now()))))
+            .get_result(conn)
+    }
+}
@@ -0,0 +1,217 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value as JsonValue;
+use utoipa::ToSchema;
+
+use crate::models::WebhookSubscription;
+use crate::schema::{webhook_deliveries, webhook_subscriptions};
+
+/// Outbox entry: one event to deliver to one subscription
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = webhook_deliveries)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WebhookDelivery {
+    pub id: i32,
+    pub subscription_id: i32,
+    pub event_type: String,
+    pub payload: JsonValue,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_response_status: Option<i32>,
+    pub last_error: Option<String>,
+    pub delivered_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = webhook_deliveries)]
+pub struct NewWebhookDelivery {
+    pub subscription_id: i32,
+    pub event_type: String,
+    pub payload: JsonValue,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookDeliveryStatus {
+    Pending,
+    Delivered,
+    /// Gave up after too many failed attempts
+    DeadLetter,
+}
+
+impl WebhookDeliveryStatus {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Delivered => "delivered",
+            Self::DeadLetter => "dead_letter",
+        }
+    }
+}
+
+/// Indexed event to fan out to the subscriptions it matches
+#[derive(Debug, Clone)]
+pub struct WebhookEvent {
+    pub event_type: String,
+    pub vault_id: String,
+    pub user_address: String,
+    pub partner_id: Option<String>,
+    pub payload: JsonValue,
+}
+
+impl WebhookDelivery {
+    /// Queue the delivery of an event to every matching subscription.
+    ///
+    /// Meant to be called within the database transaction writing the event, so that
+    /// a committed event is always delivered.
+    pub fn enqueue(event: &WebhookEvent, conn: &mut diesel::PgConnection) -> QueryResult<usize> {
+        let subscriptions = WebhookSubscription::find_matching(
+            &event.event_type,
+            &event.vault_id,
+            &event.user_address,
+            event.partner_id.as_deref(),
+            conn,
+        )?;
+        if subscriptions.is_empty() {
+            return Ok(0);
+        }
+
+        let deliveries: Vec<NewWebhookDelivery> = subscriptions
+            .iter()
+            .map(|subscription| NewWebhookDelivery {
+                subscription_id: subscription.id,
+                event_type: event.event_type.clone(),
+                payload: event.payload.clone(),
+            })
+            .collect();
+
+        diesel::insert_into(webhook_deliveries::table)
+            .values(&deliveries)
+            .execute(conn)
+    }
+
+    /// Take the pending deliveries that are due, oldest first. Their next attempt is pushed
+    /// back by `lease` so that concurrent workers skip them while they are being sent.
+    pub fn claim_due(
+        limit: i64,
+        lease: chrono::Duration,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        let now = Utc::now();
+        conn.transaction(|conn| {
+            let active_subscriptions = webhook_subscriptions::table
+                .filter(webhook_subscriptions::active.eq(true))
+                .select(webhook_subscriptions::id);
+
+            let ids: Vec<i32> = webhook_deliveries::table
+                .filter(webhook_deliveries::status.eq(WebhookDeliveryStatus::Pending.as_str()))
+                .filter(webhook_deliveries::next_attempt_at.le(now))
+                .filter(webhook_deliveries::subscription_id.eq_any(active_subscriptions))
+                .order(webhook_deliveries::next_attempt_at.asc())
+                .limit(limit)
+                .select(webhook_deliveries::id)
+                .for_update()
+                .skip_locked()
+                .load(conn)?;
+
+            if ids.is_empty() {
+                return Ok(Vec::new());
+            }
+
+            diesel::update(webhook_deliveries::table.filter(webhook_deliveries::id.eq_any(&ids)))
+                .set((
+                    webhook_deliveries::next_attempt_at.eq(now + lease),
+                    webhook_deliveries::updated_at.eq(now),
+                ))
+                .get_results(conn)
+        })
+    }
+
+    pub fn mark_delivered(
+        id: i32,
+        response_status: i32,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Self> {
+        let now = Utc::now();
+        diesel::update(webhook_deliveries::table.find(id))
+            .set((
+                webhook_deliveries::status.eq(WebhookDeliveryStatus::Delivered.as_str()),
+                webhook_deliveries::attempts.eq(webhook_deliveries::attempts + 1),
+                webhook_deliveries::last_response_status.eq(response_status),
+                webhook_deliveries::last_error.eq(None::<String>),
+                webhook_deliveries::delivered_at.eq(now),
+                webhook_deliveries::updated_at.eq(now),
+            ))
+            .get_result(conn)
+    }
+
+    /// Record a failed attempt. The delivery is retried at `retry_at`, or moved to the
+    /// dead letter state when there is none.
+    pub fn record_failure(
+        id: i32,
+        retry_at: Option<DateTime<Utc>>,
+        response_status: Option<i32>,
+        error: &str,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Self> {
+        let now = Utc::now();
+        let status = if retry_at.is_some() {
+            WebhookDeliveryStatus::Pending
+        } else {
+            WebhookDeliveryStatus::DeadLetter
+        };
+
+        diesel::update(webhook_deliveries::table.find(id))
+            .set((
+                webhook_deliveries::status.eq(status.as_str()),
+                webhook_deliveries::attempts.eq(webhook_deliveries::attempts + 1),
+                webhook_deliveries::next_attempt_at.eq(retry_at.unwrap_or(now)),
+                webhook_deliveries::last_response_status.eq(response_status),
+                webhook_deliveries::last_error.eq(error),
+                webhook_deliveries::updated_at.eq(now),
+            ))
+            .get_result(conn)
+    }
+
+    /// Send a delivery again from scratch, e.g. once a dead letter endpoint is fixed
+    pub fn requeue(id: i32, conn: &mut diesel::PgConnection) -> QueryResult<Self> {
+        let now = Utc::now();
+        diesel::update(webhook_deliveries::table.find(id))
+            .set((
+                webhook_deliveries::status.eq(WebhookDeliveryStatus::Pending.as_str()),
+                webhook_deliveries::attempts.eq(0),
+                webhook_deliveries::next_attempt_at.eq(now),
+                webhook_deliveries::updated_at.eq(now),
+            ))
+            .get_result(conn)
+    }
+
+    pub fn find_by_id(id: i32, conn: &mut diesel::PgConnection) -> QueryResult<Self> {
+        webhook_deliveries::table.find(id).first(conn)
+    }
+
+    /// Most recent deliveries of a subscription (optionally filtered by status)
+    pub fn find_by_subscription(
+        subscription_id: i32,
+        status: Option<&str>,
+        limit: i64,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        let mut query = webhook_deliveries::table
+            .filter(webhook_deliveries::subscription_id.eq(subscription_id))
+            .into_boxed();
+
+        if let Some(status) = status {
+            query = query.filter(webhook_deliveries::status.eq(status));
+        }
+
+        query
+            .order(webhook_deliveries::id.desc())
+            .limit(limit)
+            .load(conn)
+    }
+}
@@ -0,0 +1,117 @@
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::schema::webhook_subscriptions;
+
+/// Endpoint notified of the indexed events of `event_types`. The vault, partner and user
+/// filters are ignored when unset.
+#[derive(Debug, Clone, Serialize, Deserialize, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = webhook_subscriptions)]
+#[diesel(check_for_backend(diesel::pg::Pg))]
+pub struct WebhookSubscription {
+    pub id: i32,
+    pub url: String,
+    pub secret: String,
+    pub description: Option<String>,
+    pub event_types: Vec<String>,
+    pub vault_id: Option<String>,
+    pub partner_id: Option<String>,
+    pub user_address: Option<String>,
+    pub active: bool,
+    pub created_at: Option<DateTime<Utc>>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Insertable)]
+#[diesel(table_name = webhook_subscriptions)]
+pub struct NewWebhookSubscription {
+    pub url: String,
+    pub secret: String,
+    pub description: Option<String>,
+    pub event_types: Vec<String>,
+    pub vault_id: Option<String>,
+    pub partner_id: Option<String>,
+    pub user_address: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, AsChangeset)]
+#[diesel(table_name = webhook_subscriptions)]
+pub struct WebhookSubscriptionUpdate {
+    pub url: Option<String>,
+    pub description: Option<String>,
+    pub event_types: Option<Vec<String>>,
+    pub active: Option<bool>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl WebhookSubscription {
+    pub fn create(
+        new_subscription: &NewWebhookSubscription,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Self> {
+        diesel::insert_into(webhook_subscriptions::table)
+            .values(new_subscription)
+            .get_result(conn)
+    }
+
+    pub fn find_by_id(id: i32, conn: &mut diesel::PgConnection) -> QueryResult<Self> {
+        webhook_subscriptions::table.find(id).first(conn)
+    }
+
+    pub fn find_by_ids(ids: &[i32], conn: &mut diesel::PgConnection) -> QueryResult<Vec<Self>> {
+        webhook_subscriptions::table
+            .filter(webhook_subscriptions::id.eq_any(ids))
+            .load(conn)
+    }
+
+    pub fn find_all(conn: &mut diesel::PgConnection) -> QueryResult<Vec<Self>> {
+        webhook_subscriptions::table
+            .order(webhook_subscriptions::id.asc())
+            .load(conn)
+    }
+
+    /// Find the active subscriptions interested in an event
+    pub fn find_matching(
+        event_type: &str,
+        vault_id: &str,
+        user_address: &str,
+        partner_id: Option<&str>,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Vec<Self>> {
+        webhook_subscriptions::table
+            .filter(webhook_subscriptions::active.eq(true))
+            .filter(webhook_subscriptions::event_types.contains(vec![event_type]))
+            .filter(
+                webhook_subscriptions::vault_id
+                    .is_null()
+                    .or(webhook_subscriptions::vault_id.eq(vault_id)),
+            )
+            .filter(
+                webhook_subscriptions::user_address
+                    .is_null()
+                    .or(webhook_subscriptions::user_address.eq(user_address)),
+            )
+            .filter(
+                webhook_subscriptions::partner_id
+                    .is_null()
+                    .or(webhook_subscriptions::partner_id.eq(partner_id)),
+            )
+            .load(conn)
+    }
+
+    pub fn update(
+        &self,
+        updates: &WebhookSubscriptionUpdate,
+        conn: &mut diesel::PgConnection,
+    ) -> QueryResult<Self> {
+        diesel::update(webhook_subscriptions::table.find(self.id))
+            .set(updates)
+            .get_result(conn)
+    }
+
+    /// Delete a subscription along with its deliveries
+    pub fn delete(id: i32, conn: &mut diesel::PgConnection) -> QueryResult<usize> {
+        diesel::delete(webhook_subscriptions::table.find(id)).execute(conn)
+    }
+}
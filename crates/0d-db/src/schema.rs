@@ -18,6 +18,19 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    currency_prices (id) {
+        id -> Int4,
+        #[max_length = 10]
+        currency -> Varchar,
+        price -> Numeric,
+        sampled_at -> Timestamptz,
+        #[max_length = 32]
+        source -> Varchar,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     indexer_state (id) {
         id -> Int4,
@@ -34,6 +47,146 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    kpi_runs (id) {
+        id -> Int4,
+        started_at -> Timestamptz,
+        finished_at -> Timestamptz,
+        vaults_processed -> Int4,
+        positions_processed -> Int4,
+        errors -> Int4,
+        fetch_ms -> Int8,
+        compute_ms -> Int8,
+        write_ms -> Int8,
+        total_ms -> Int8,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    partner_payout_batches (id) {
+        id -> Int4,
+        #[max_length = 100]
+        partner_id -> Varchar,
+        #[max_length = 100]
+        payout_address -> Varchar,
+        period_start -> Date,
+        period_end -> Date,
+        accruals_count -> Int4,
+        total_reward_usd -> Numeric,
+        #[max_length = 20]
+        status -> Varchar,
+        #[max_length = 100]
+        tx_hash -> Nullable<Varchar>,
+        paid_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    partner_reward_accruals (id) {
+        id -> Int4,
+        #[max_length = 100]
+        partner_id -> Varchar,
+        #[max_length = 50]
+        vault_id -> Varchar,
+        accrual_date -> Date,
+        referred_positions -> Int4,
+        referred_tvl_usd -> Numeric,
+        yield_usd -> Numeric,
+        management_fees_usd -> Numeric,
+        performance_fees_usd -> Numeric,
+        reward_share_bps -> Int4,
+        reward_usd -> Numeric,
+        payout_batch_id -> Nullable<Int4>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    partners (id) {
+        #[max_length = 100]
+        id -> Varchar,
+        #[max_length = 100]
+        display_name -> Varchar,
+        #[max_length = 100]
+        payout_address -> Varchar,
+        reward_share_bps -> Int4,
+        active_from -> Timestamptz,
+        active_until -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    redeem_requests (id) {
+        id -> Int4,
+        #[max_length = 50]
+        vault_id -> Varchar,
+        #[max_length = 100]
+        redeem_id -> Varchar,
+        #[max_length = 100]
+        user_address -> Varchar,
+        #[max_length = 100]
+        receiver -> Varchar,
+        epoch -> Int8,
+        #[max_length = 20]
+        status -> Varchar,
+        requested_shares -> Numeric,
+        nominal_assets -> Numeric,
+        claimed_assets -> Nullable<Numeric>,
+        #[max_length = 100]
+        request_tx_hash -> Varchar,
+        request_block_number -> Int8,
+        requested_at -> Timestamptz,
+        #[max_length = 100]
+        claim_tx_hash -> Nullable<Varchar>,
+        claim_block_number -> Nullable<Int8>,
+        claimed_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    risk_free_rates (id) {
+        id -> Int4,
+        #[max_length = 20]
+        base_asset -> Varchar,
+        annual_rate -> Numeric,
+        effective_from -> Timestamptz,
+        #[max_length = 100]
+        source -> Nullable<Varchar>,
+        created_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    transaction_events (id) {
+        id -> Int8,
+        transaction_id -> Int4,
+        #[max_length = 20]
+        kind -> Varchar,
+        #[max_length = 50]
+        vault_id -> Varchar,
+        #[max_length = 100]
+        user_address -> Varchar,
+        #[max_length = 100]
+        partner_id -> Nullable<Varchar>,
+        #[max_length = 100]
+        tx_hash -> Varchar,
+        #[max_length = 20]
+        status -> Varchar,
+        amount -> Numeric,
+        shares_amount -> Nullable<Numeric>,
+        block_number -> Int8,
+        block_timestamp -> Timestamptz,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     user_kpis (id) {
         id -> Int4,
@@ -55,6 +208,16 @@ diesel::table! {
         updated_at -> Nullable<Timestamptz>,
         sortino_ratio -> Nullable<Numeric>,
         share_balance -> Nullable<Numeric>,
+        risk_free_rate -> Nullable<Numeric>,
+        periods_per_year -> Nullable<Numeric>,
+        volatility_pct -> Nullable<Numeric>,
+        var_historical_pct -> Nullable<Numeric>,
+        var_parametric_pct -> Nullable<Numeric>,
+        cvar_pct -> Nullable<Numeric>,
+        calmar_ratio -> Nullable<Numeric>,
+        omega_ratio -> Nullable<Numeric>,
+        max_drawdown_duration_days -> Nullable<Numeric>,
+        recovery_time_days -> Nullable<Numeric>,
     }
 }
 
@@ -89,6 +252,20 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    user_risk_states (id) {
+        id -> Int4,
+        #[max_length = 100]
+        user_address -> Varchar,
+        #[max_length = 50]
+        vault_id -> Varchar,
+        state -> Jsonb,
+        last_point_at -> Timestamptz,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     user_transactions (id) {
         id -> Int4,
@@ -128,6 +305,30 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    vault_kpis (id) {
+        id -> Int4,
+        #[max_length = 50]
+        vault_id -> Varchar,
+        max_drawdown_pct -> Nullable<Numeric>,
+        sharpe_ratio -> Nullable<Numeric>,
+        sortino_ratio -> Nullable<Numeric>,
+        volatility_pct -> Nullable<Numeric>,
+        var_historical_pct -> Nullable<Numeric>,
+        var_parametric_pct -> Nullable<Numeric>,
+        cvar_pct -> Nullable<Numeric>,
+        calmar_ratio -> Nullable<Numeric>,
+        omega_ratio -> Nullable<Numeric>,
+        max_drawdown_duration_days -> Nullable<Numeric>,
+        recovery_time_days -> Nullable<Numeric>,
+        risk_free_rate -> Nullable<Numeric>,
+        periods_per_year -> Nullable<Numeric>,
+        calculated_at -> Nullable<Timestamptz>,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::table! {
     vaults (id) {
         #[max_length = 50]
@@ -174,21 +375,86 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> Int4,
+        subscription_id -> Int4,
+        #[max_length = 50]
+        event_type -> Varchar,
+        payload -> Jsonb,
+        #[max_length = 20]
+        status -> Varchar,
+        attempts -> Int4,
+        next_attempt_at -> Timestamptz,
+        last_response_status -> Nullable<Int4>,
+        last_error -> Nullable<Text>,
+        delivered_at -> Nullable<Timestamptz>,
+        created_at -> Timestamptz,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    webhook_subscriptions (id) {
+        id -> Int4,
+        #[max_length = 500]
+        url -> Varchar,
+        #[max_length = 128]
+        secret -> Varchar,
+        #[max_length = 255]
+        description -> Nullable<Varchar>,
+        event_types -> Array<Text>,
+        #[max_length = 50]
+        vault_id -> Nullable<Varchar>,
+        #[max_length = 100]
+        partner_id -> Nullable<Varchar>,
+        #[max_length = 100]
+        user_address -> Nullable<Varchar>,
+        active -> Bool,
+        created_at -> Nullable<Timestamptz>,
+        updated_at -> Nullable<Timestamptz>,
+    }
+}
+
 diesel::joinable!(indexer_state -> vaults (vault_id));
+diesel::joinable!(partner_payout_batches -> partners (partner_id));
+diesel::joinable!(partner_reward_accruals -> partner_payout_batches (payout_batch_id));
+diesel::joinable!(partner_reward_accruals -> partners (partner_id));
+diesel::joinable!(partner_reward_accruals -> vaults (vault_id));
+diesel::joinable!(redeem_requests -> users (user_address));
+diesel::joinable!(redeem_requests -> vaults (vault_id));
+diesel::joinable!(transaction_events -> user_transactions (transaction_id));
+diesel::joinable!(transaction_events -> users (user_address));
+diesel::joinable!(transaction_events -> vaults (vault_id));
 diesel::joinable!(user_kpis -> users (user_address));
 diesel::joinable!(user_kpis -> vaults (vault_id));
 diesel::joinable!(user_positions -> users (user_address));
 diesel::joinable!(user_positions -> vaults (vault_id));
 diesel::joinable!(user_transactions -> users (user_address));
 diesel::joinable!(user_transactions -> vaults (vault_id));
+diesel::joinable!(vault_kpis -> vaults (vault_id));
+diesel::joinable!(webhook_deliveries -> webhook_subscriptions (subscription_id));
+diesel::joinable!(webhook_subscriptions -> vaults (vault_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     api_logs,
+    currency_prices,
     indexer_state,
+    kpi_runs,
+    partner_payout_batches,
+    partner_reward_accruals,
+    partners,
+    redeem_requests,
+    risk_free_rates,
+    transaction_events,
     user_kpis,
     user_portfolio_history,
     user_positions,
+    user_risk_states,
     user_transactions,
     users,
+    vault_kpis,
     vaults,
+    webhook_deliveries,
+    webhook_subscriptions,
 );
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Datelike, Days, NaiveTime, Utc};
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 
@@ -19,6 +20,97 @@ impl PerformanceMetric {
     }
 }
 
+/// Risk metrics that can be selected on the KPI endpoints
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RiskMetric {
+    MaxDrawdown,
+    Sharpe,
+    Sortino,
+    Volatility,
+    VarHistorical,
+    VarParametric,
+    Cvar,
+    Calmar,
+    Omega,
+    MaxDrawdownDuration,
+    RecoveryTime,
+}
+
+impl RiskMetric {
+    pub const ALL: [Self; 11] = [
+        Self::MaxDrawdown,
+        Self::Sharpe,
+        Self::Sortino,
+        Self::Volatility,
+        Self::VarHistorical,
+        Self::VarParametric,
+        Self::Cvar,
+        Self::Calmar,
+        Self::Omega,
+        Self::MaxDrawdownDuration,
+        Self::RecoveryTime,
+    ];
+
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::MaxDrawdown => "max_drawdown",
+            Self::Sharpe => "sharpe",
+            Self::Sortino => "sortino",
+            Self::Volatility => "volatility",
+            Self::VarHistorical => "var_historical",
+            Self::VarParametric => "var_parametric",
+            Self::Cvar => "cvar",
+            Self::Calmar => "calmar",
+            Self::Omega => "omega",
+            Self::MaxDrawdownDuration => "max_drawdown_duration",
+            Self::RecoveryTime => "recovery_time",
+        }
+    }
+}
+
+/// Field storing a risk metric in a struct named after the `user_kpis` columns.
+///
+/// `risk_metric_field!(kpi, metric)` reads the value while `risk_metric_field!(&mut kpi, metric)`
+/// borrows the field.
+#[macro_export]
+macro_rules! risk_metric_field {
+    (@field [$($borrow:tt)*] $kpi:expr, $metric:expr) => {
+        match $metric {
+            $crate::types::RiskMetric::MaxDrawdown => $($borrow)* $kpi.max_drawdown_pct,
+            $crate::types::RiskMetric::Sharpe => $($borrow)* $kpi.sharpe_ratio,
+            $crate::types::RiskMetric::Sortino => $($borrow)* $kpi.sortino_ratio,
+            $crate::types::RiskMetric::Volatility => $($borrow)* $kpi.volatility_pct,
+            $crate::types::RiskMetric::VarHistorical => $($borrow)* $kpi.var_historical_pct,
+            $crate::types::RiskMetric::VarParametric => $($borrow)* $kpi.var_parametric_pct,
+            $crate::types::RiskMetric::Cvar => $($borrow)* $kpi.cvar_pct,
+            $crate::types::RiskMetric::Calmar => $($borrow)* $kpi.calmar_ratio,
+            $crate::types::RiskMetric::Omega => $($borrow)* $kpi.omega_ratio,
+            $crate::types::RiskMetric::MaxDrawdownDuration => {
+                $($borrow)* $kpi.max_drawdown_duration_days
+            }
+            $crate::types::RiskMetric::RecoveryTime => $($borrow)* $kpi.recovery_time_days,
+        }
+    };
+    (&mut $kpi:expr, $metric:expr) => {
+        $crate::risk_metric_field!(@field [&mut] $kpi, $metric)
+    };
+    ($kpi:expr, $metric:expr) => {
+        $crate::risk_metric_field!(@field [] $kpi, $metric)
+    };
+}
+
+impl std::str::FromStr for RiskMetric {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|metric| metric.as_str() == s)
+            .ok_or_else(|| format!("Unknown risk metric: {s}"))
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Timeframe {
@@ -52,6 +144,94 @@ impl Timeframe {
     }
 }
 
+/// Width of the buckets of an aggregated time series
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+pub enum BucketInterval {
+    #[default]
+    #[serde(rename = "1d")]
+    OneDay,
+    #[serde(rename = "1w")]
+    OneWeek,
+}
+
+impl BucketInterval {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::OneDay => "1d",
+            Self::OneWeek => "1w",
+        }
+    }
+
+    pub const fn duration(&self) -> chrono::Duration {
+        match self {
+            Self::OneDay => chrono::Duration::days(1),
+            Self::OneWeek => chrono::Duration::weeks(1),
+        }
+    }
+
+    /// Postgres `date_trunc` field of the bucket
+    pub const fn as_pg_field(&self) -> &'static str {
+        match self {
+            Self::OneDay => "day",
+            Self::OneWeek => "week",
+        }
+    }
+
+    /// Start of the bucket containing `at`. Weeks start on Monday, like `date_trunc`.
+    pub fn bucket_start(&self, at: DateTime<Utc>) -> DateTime<Utc> {
+        let day = at.date_naive();
+        let start = match self {
+            Self::OneDay => day,
+            Self::OneWeek => day - Days::new(u64::from(day.weekday().num_days_from_monday())),
+        };
+        start.and_time(NaiveTime::MIN).and_utc()
+    }
+}
+
+/// Sort key of the holders of a vault
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum HolderSort {
+    #[default]
+    ShareBalance,
+    FirstDepositAt,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum SortOrder {
+    Asc,
+    #[default]
+    Desc,
+}
+
+/// Lot selection method used to match withdrawals against deposits when computing
+/// cost basis and realized gains
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum CostBasisMethod {
+    /// First in, first out: the oldest lots are sold first
+    #[default]
+    Fifo,
+    /// Last in, first out: the most recent lots are sold first
+    Lifo,
+    /// Highest in, first out: the lots with the highest cost per share are sold first
+    Hifo,
+    /// Every share carries the average cost of the position
+    AverageCost,
+}
+
+impl CostBasisMethod {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Fifo => "fifo",
+            Self::Lifo => "lifo",
+            Self::Hifo => "hifo",
+            Self::AverageCost => "average_cost",
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default, Serialize, Deserialize, ToSchema)]
 #[serde(rename_all = "snake_case")]
 pub enum AprBasis {
@@ -8,6 +8,7 @@ use std::time::Duration;
 use task_supervisor::SupervisorBuilder;
 use zerod_db::ZerodPool;
 use zerod_db::models::Vault;
+use zerod_types::EventBus;
 
 use crate::vaults::{starknet::StarknetIndexer, state::VaultState};
 
@@ -15,6 +16,7 @@ pub struct IndexerService {
     db_pool: Pool,
     apibara_api_key: String,
     starknet_provider: FallbackProvider,
+    event_bus: EventBus,
 }
 
 impl IndexerService {
@@ -22,11 +24,13 @@ impl IndexerService {
         db_pool: Pool,
         apibara_api_key: String,
         starknet_provider: FallbackProvider,
+        event_bus: EventBus,
     ) -> Self {
         Self {
             db_pool,
             apibara_api_key,
             starknet_provider,
+            event_bus,
         }
     }
 
@@ -64,6 +68,7 @@ impl IndexerService {
                     proxy_address: vault.proxy_address.and_then(|v| Felt::from_hex(&v).ok()),
                     apibara_api_key: self.apibara_api_key.clone(),
                     starknet_provider: self.starknet_provider.clone(),
+                    event_bus: self.event_bus.clone(),
                     state: VaultState::new(
                         vault.id.clone(),
                         vault.start_block as u64,
@@ -1,6 +1,7 @@
 use deadpool_diesel::postgres::Pool;
 use pragma_common::services::{Service, ServiceRunner};
 use pragma_common::starknet::FallbackProvider;
+use zerod_types::EventBus;
 
 use crate::IndexerService;
 
@@ -8,6 +9,7 @@ pub struct IndexerTask {
     db_pool: Pool,
     apibara_api_key: String,
     starknet_provider: FallbackProvider,
+    event_bus: EventBus,
 }
 
 impl IndexerTask {
@@ -15,11 +17,13 @@ impl IndexerTask {
         db_pool: Pool,
         apibara_api_key: String,
         starknet_provider: FallbackProvider,
+        event_bus: EventBus,
     ) -> Self {
         Self {
             db_pool,
             apibara_api_key,
             starknet_provider,
+            event_bus,
         }
     }
 }
@@ -30,10 +34,15 @@ impl Service for IndexerTask {
         let db_pool = self.db_pool.clone();
         let apibara_api_key = self.apibara_api_key.clone();
         let starknet_provider = self.starknet_provider.clone();
+        let event_bus = self.event_bus.clone();
 
         runner.spawn_loop(move |ctx| async move {
-            let indexer_service =
-                IndexerService::new(db_pool.clone(), apibara_api_key, starknet_provider.clone());
+            let indexer_service = IndexerService::new(
+                db_pool.clone(),
+                apibara_api_key,
+                starknet_provider.clone(),
+                event_bus.clone(),
+            );
             if let Some(result) = ctx.run_until_cancelled(indexer_service.run_forever()).await {
                 result?;
             }
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 
 use chrono::{DateTime, Utc};
+use diesel::Connection;
 use evian::contracts::starknet::vault::StarknetVaultContract;
 use evian::contracts::starknet::vault::data::indexer::events::{
     DepositEvent, RedeemClaimedEvent, RedeemRequestedEvent, VaultAddress, VaultEvent,
@@ -15,12 +16,22 @@ use starknet::core::types::Felt;
 use task_supervisor::{SupervisedTask, TaskError};
 use zerod_db::ZerodPool;
 use zerod_db::models::{
+    indexer_state::IndexerStatus,
+    redeem_request::{NewRedeemRequest, RedeemClaim, RedeemRequest, RedeemRequestStatus},
+    transaction_event::{NewTransactionEventRecord, TransactionEventRecord},
     user::User,
     user_position::{NewUserPosition, UserPosition, UserPositionUpdate},
     user_transaction::{
         NewUserTransaction, TransactionStatus, TransactionType, UserTransaction,
         UserTransactionUpdate,
     },
+    webhook_delivery::{WebhookDelivery, WebhookEvent},
+};
+use zerod_types::{
+    EventBus, IndexerEvent,
+    events::{
+        IndexerStatusEvent, PositionEvent, SharePriceEvent, TransactionEvent, TransactionEventKind,
+    },
 };
 
 use crate::vaults::helpers::felt_to_hex_str;
@@ -33,6 +44,7 @@ pub struct StarknetIndexer {
     pub proxy_address: Option<Felt>,
     pub vault_id: String,
     pub starknet_provider: FallbackProvider,
+    pub event_bus: EventBus,
     pub state: VaultState,
 }
 
@@ -91,6 +103,11 @@ impl SupervisedTask for StarknetIndexer {
                         }
                         OutputEvent::Synced => {
                             self.state.set_indexer_state_synced(&self.vault_id).await?;
+                            self.event_bus.publish(IndexerEvent::IndexerStatus(IndexerStatusEvent {
+                                vault_id: self.vault_id.clone(),
+                                status: IndexerStatus::Synced.as_str().to_string(),
+                                last_processed_block: self.state.current_block.try_into().unwrap_or(i64::MAX),
+                            }));
                             tracing::info!("[Vault {}] 🥳 Vault({}) reached the tip of the chain!", self.vault_id, self.vault_id);
                         }
                         // NOTE: Never happens for now. See later when apibara upgrades?
@@ -232,16 +249,29 @@ impl StarknetIndexer {
             metadata: None,
         };
 
-        self.state
+        let transaction_event = self
+            .state
             .db_pool
             .interact_with_context(
                 format!("create deposit transaction for user: {user_address}"),
-                move |conn| UserTransaction::create(&new_transaction, conn),
+                move |conn| {
+                    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                        let transaction = UserTransaction::create(&new_transaction, conn)?;
+                        let event = record_transaction_event(
+                            TransactionEventKind::Deposit,
+                            &transaction,
+                            conn,
+                        )?;
+                        enqueue_webhooks(&event, conn)?;
+                        Ok(event)
+                    })
+                },
             )
             .await?;
 
         let vault_id = self.vault_id.clone();
-        self.state
+        let position = self
+            .state
             .db_pool
             .interact_with_context(
                 format!("update user position for deposit: user={user_address}, vault={vault_id}"),
@@ -280,6 +310,12 @@ impl StarknetIndexer {
             )
             .await?;
 
+        self.publish_transaction(transaction_event);
+        self.publish_position(&position);
+        if let Some(share_price) = share_price {
+            self.publish_share_price(share_price, block_timestamp);
+        }
+
         Ok(())
     }
     async fn handle_redeem_requested_event(
@@ -333,41 +369,74 @@ impl StarknetIndexer {
             return Ok(());
         }
 
+        // Redeems are attributed to the partner the position was referred by
+        let partner_id = self.referring_partner(&user_address).await?;
+
+        let redeem_id = redeem.id.to_string();
+        let epoch: i64 = redeem
+            .epoch
+            .to_string()
+            .parse()
+            .expect("[StartknetIndexer] 🌯 Redeem epoch too large for i64");
+        let block_number: i64 = block_number
+            .try_into()
+            .expect("[StartknetIndexer] 🌯 Block number too large for i64");
+
         // Create transaction record for withdrawal
         let new_transaction = NewUserTransaction {
-            tx_hash,
-            block_number: block_number
-                .try_into()
-                .expect("[StartknetIndexer] 🌯 Block number too large for i64"),
+            tx_hash: tx_hash.clone(),
+            block_number,
             block_timestamp,
             user_address: user_address.clone(),
             vault_id: self.vault_id.clone(),
             type_: TransactionType::Withdraw.as_str().to_string(),
             status: TransactionStatus::Pending.as_str().to_string(),
             amount: redeem_assets,
-            partner_id: None,
+            partner_id,
             shares_amount: Some(redeem_shares),
             share_price,
             gas_fee: None,
-            metadata: Some(serde_json::json!({
-                "redeem_id": redeem.id.to_string(),
-                "epoch": redeem.epoch.to_string(),
-                "receiver": redeem.receiver.to_string()
-            })),
+            metadata: None,
+        };
+        let new_redeem_request = NewRedeemRequest {
+            vault_id: self.vault_id.clone(),
+            redeem_id,
+            user_address: user_address.clone(),
+            receiver: felt_to_hex_str(redeem.receiver),
+            epoch,
+            requested_shares: redeem_shares,
+            nominal_assets: redeem_assets,
+            request_tx_hash: tx_hash,
+            request_block_number: block_number,
+            requested_at: block_timestamp,
         };
 
-        // First database operation: Create transaction record
-        self.state
+        // First database operation: Create transaction and redeem request records
+        let transaction_event = self
+            .state
             .db_pool
             .interact_with_context(
                 format!("create withdraw transaction for user: {user_address}"),
-                move |conn| UserTransaction::create(&new_transaction, conn),
+                move |conn| {
+                    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                        let transaction = UserTransaction::create(&new_transaction, conn)?;
+                        RedeemRequest::create(&new_redeem_request, conn)?;
+                        let event = record_transaction_event(
+                            TransactionEventKind::RedeemRequested,
+                            &transaction,
+                            conn,
+                        )?;
+                        enqueue_webhooks(&event, conn)?;
+                        Ok(event)
+                    })
+                },
             )
             .await?;
 
         // Second database operation: Update user position
         let vault_id = self.vault_id.clone();
-        self.state
+        let position = self
+            .state
             .db_pool
             .interact_with_context(
                 format!("update user position for redeem: user={user_address}, vault={vault_id}"),
@@ -391,7 +460,7 @@ impl StarknetIndexer {
                                 updated_at: Some(Utc::now()),
                             };
 
-                            position.update(&updates, conn).map(|_| ())
+                            position.update(&updates, conn).map(Some)
                         }
                         Err(diesel::result::Error::NotFound) => {
                             tracing::warn!(
@@ -399,7 +468,7 @@ impl StarknetIndexer {
                                 vault_id,
                                 user_address
                             );
-                            Ok(())
+                            Ok(None)
                         }
                         Err(e) => Err(e),
                     }
@@ -407,6 +476,14 @@ impl StarknetIndexer {
             )
             .await?;
 
+        self.publish_transaction(transaction_event);
+        if let Some(position) = position {
+            self.publish_position(&position);
+        }
+        if let Some(share_price) = share_price {
+            self.publish_share_price(share_price, block_timestamp);
+        }
+
         Ok(())
     }
 
@@ -414,7 +491,7 @@ impl StarknetIndexer {
         &self,
         redeem_claimed: RedeemClaimedEvent,
         tx_hash: String,
-        _block_number: u64,
+        block_number: u64,
         block_timestamp: DateTime<Utc>,
     ) -> Result<(), anyhow::Error> {
         tracing::info!("[StartknetIndexer] ✅ Handling redeem claimed event with hash: {tx_hash}");
@@ -422,41 +499,35 @@ impl StarknetIndexer {
         let user_address = felt_to_hex_str(redeem_claimed.receiver);
         self.ensure_user_exists(user_address.clone()).await?;
 
-        // First, find the original pending redeem transaction by redeem_id
+        // First, find the original redeem request by redeem_id
         let redeem_id = redeem_claimed.id.to_string();
         let vault_id = self.vault_id.clone();
 
-        let transaction = match self
+        let redeem_request = match self
             .state
             .db_pool
             .interact_with_context(
-                format!("find redeem transaction for user: {user_address}"),
+                format!("find redeem request: vault={vault_id}, redeem_id={redeem_id}"),
                 {
-                    let user_addr = user_address.clone();
                     let vault_id_check = vault_id.clone();
                     let redeem_id_check = redeem_id.clone();
                     move |conn| {
-                        UserTransaction::find_redeem_by_id(
-                            &user_addr,
-                            &vault_id_check,
-                            &redeem_id_check,
-                            conn,
-                        )
+                        RedeemRequest::find_by_redeem_id(&vault_id_check, &redeem_id_check, conn)
                     }
                 },
             )
             .await
         {
-            Ok(tx) if tx.status == TransactionStatus::Confirmed.as_str() => {
+            Ok(request) if request.status == RedeemRequestStatus::Claimed.as_str() => {
                 tracing::info!(
-                    "[Vault {}] ⏭️ RedeemClaimed: redeem_id={} already confirmed (tx_id={}), skipping",
+                    "[Vault {}] ⏭️ RedeemClaimed: redeem_id={} already claimed (request_id={}), skipping",
                     vault_id,
                     redeem_id,
-                    tx.id
+                    request.id
                 );
                 return Ok(());
             }
-            Ok(tx) => tx,
+            Ok(request) => request,
             Err(e) if e.is_not_found() => {
                 tracing::warn!(
                     "[Vault {}] ⚠️ RedeemClaimed: no redeem found for user={} redeem_id={} — RedeemRequested was never indexed, skipping",
@@ -468,31 +539,55 @@ impl StarknetIndexer {
             }
             Err(e) => return Err(e.into()),
         };
+        // The assets are claimed by the receiver, but the redeemed shares were the owner's
+        let user_address = redeem_request.user_address.clone();
 
-        // Update the original pending transaction to confirmed status
-        self.state
+        // Record the claim and confirm the original pending transaction
+        let transaction_event = self
+            .state
             .db_pool
             .interact_with_context(
                 format!(
-                    "update transaction status to confirmed: tx_id={}",
-                    transaction.id
+                    "record claim of redeem request: request_id={}",
+                    redeem_request.id
                 ),
                 {
-                    let tx_id = transaction.id;
-                    let new_status = TransactionStatus::Confirmed.as_str().to_string();
-                    let actual_amount = redeem_claimed.assets;
-                    let claim_tx_hash = tx_hash.clone();
+                    let claim = RedeemClaim {
+                        claim_tx_hash: tx_hash.clone(),
+                        claim_block_number: block_number
+                            .try_into()
+                            .expect("[StartknetIndexer] 🌯 Block number too large for i64"),
+                        claimed_at: block_timestamp,
+                        claimed_assets: redeem_claimed.assets,
+                    };
                     move |conn| {
-                        let tx = UserTransaction::update_status_and_amount(
-                            tx_id,
-                            &new_status,
-                            actual_amount,
-                            conn,
-                        )?;
-                        // Store claim tx hash in metadata, preserving the original RedeemRequested tx hash
-                        let mut metadata = tx.metadata.clone().unwrap_or_default();
-                        metadata["claim_tx_hash"] = serde_json::Value::String(claim_tx_hash);
-                        tx.update(&UserTransactionUpdate::new().with_metadata(metadata), conn)
+                        conn.transaction::<_, diesel::result::Error, _>(|conn| {
+                            redeem_request.claim(&claim, conn)?;
+
+                            // The claim is recorded on the redeem request, the withdraw keeps
+                            // the nominal of the request
+                            let tx = UserTransaction::find_withdraw(
+                                &redeem_request.vault_id,
+                                &redeem_request.user_address,
+                                &redeem_request.request_tx_hash,
+                                conn,
+                            )?
+                            .ok_or(diesel::result::Error::NotFound)?
+                            .update_status(TransactionStatus::Confirmed, conn)?;
+
+                            // The claim event carries the assets received
+                            let claimed = UserTransaction {
+                                amount: claim.claimed_assets,
+                                ..tx
+                            };
+                            let event = record_transaction_event(
+                                TransactionEventKind::RedeemClaimed,
+                                &claimed,
+                                conn,
+                            )?;
+                            enqueue_webhooks(&event, conn)?;
+                            Ok(event)
+                        })
                     }
                 },
             )
@@ -503,7 +598,8 @@ impl StarknetIndexer {
         let vault_id_for_position = vault_id.clone();
         let redeem_nominal = redeem_claimed.redeem_request_nominal;
         let block_ts = block_timestamp;
-        self.state
+        let position = self
+            .state
             .db_pool
             .interact_with_context(
                 format!(
@@ -534,7 +630,7 @@ impl StarknetIndexer {
                                 updated_at: Some(Utc::now()),
                             };
 
-                            position.update(&updates, conn).map(|_| ())
+                            position.update(&updates, conn).map(Some)
                         }
                         Err(diesel::result::Error::NotFound) => {
                             tracing::warn!(
@@ -542,7 +638,7 @@ impl StarknetIndexer {
                                 vault_id_for_position,
                                 user_addr_for_position
                             );
-                            Ok(())
+                            Ok(None)
                         }
                         Err(e) => Err(e),
                     }
@@ -550,9 +646,57 @@ impl StarknetIndexer {
             )
             .await?;
 
+        self.publish_transaction(transaction_event);
+        if let Some(position) = position {
+            self.publish_position(&position);
+        }
+
         Ok(())
     }
 
+    /// Publish a committed user transaction to the streaming subscribers
+    fn publish_transaction(&self, event: TransactionEvent) {
+        self.event_bus.publish(IndexerEvent::Transaction(event));
+    }
+
+    /// Publish a committed user position to the streaming subscribers
+    fn publish_position(&self, position: &UserPosition) {
+        self.event_bus
+            .publish(IndexerEvent::Position(PositionEvent {
+                vault_id: position.vault_id.clone(),
+                user_address: position.user_address.clone(),
+                share_balance: position.share_balance.to_string(),
+                cost_basis: position.cost_basis.to_string(),
+                last_activity_at: position.last_activity_at,
+            }));
+    }
+
+    /// Publish the share price implied by a deposit or redeem
+    fn publish_share_price(&self, share_price: Decimal, block_timestamp: DateTime<Utc>) {
+        self.event_bus
+            .publish(IndexerEvent::SharePrice(SharePriceEvent {
+                vault_id: self.vault_id.clone(),
+                share_price: share_price.to_string(),
+                block_timestamp,
+            }));
+    }
+
+    /// Partner the position of a user in this vault was referred by, if any
+    async fn referring_partner(&self, user_address: &str) -> Result<Option<String>, anyhow::Error> {
+        let user_address = user_address.to_string();
+        let vault_id = self.vault_id.clone();
+        let partner_id = self
+            .state
+            .db_pool
+            .interact_with_context(
+                format!("find referring partner of user: {user_address}"),
+                move |conn| UserTransaction::find_referring_partner(&user_address, &vault_id, conn),
+            )
+            .await?;
+
+        Ok(partner_id)
+    }
+
     /// Ensure user exists in database
     async fn ensure_user_exists(&self, user_address: String) -> Result<(), anyhow::Error> {
         self.state
@@ -565,3 +709,47 @@ impl StarknetIndexer {
         Ok(())
     }
 }
+
+/// Log the event of a transaction, within the database transaction writing it
+fn record_transaction_event(
+    kind: TransactionEventKind,
+    transaction: &UserTransaction,
+    conn: &mut diesel::PgConnection,
+) -> diesel::QueryResult<TransactionEvent> {
+    let record =
+        TransactionEventRecord::create(&NewTransactionEventRecord::new(kind, transaction), conn)?;
+    Ok(TransactionEvent {
+        event_id: record.id,
+        id: transaction.id,
+        kind,
+        vault_id: transaction.vault_id.clone(),
+        user_address: transaction.user_address.clone(),
+        partner_id: transaction.partner_id.clone(),
+        tx_hash: transaction.tx_hash.clone(),
+        status: transaction.status.clone(),
+        amount: transaction.amount.to_string(),
+        shares: transaction.shares_amount.map(|s| s.to_string()),
+        block_number: transaction.block_number,
+        block_timestamp: transaction.block_timestamp,
+    })
+}
+
+/// Queue the webhook deliveries of a transaction, within the database transaction writing it
+fn enqueue_webhooks(
+    event: &TransactionEvent,
+    conn: &mut diesel::PgConnection,
+) -> diesel::QueryResult<usize> {
+    let payload = serde_json::to_value(event)
+        .map_err(|e| diesel::result::Error::SerializationError(Box::new(e)))?;
+
+    WebhookDelivery::enqueue(
+        &WebhookEvent {
+            event_type: event.kind.as_str().to_string(),
+            vault_id: event.vault_id.clone(),
+            user_address: event.user_address.clone(),
+            partner_id: event.partner_id.clone(),
+            payload,
+        },
+        conn,
+    )
+}
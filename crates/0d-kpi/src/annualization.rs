@@ -0,0 +1,202 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, dec};
+
+use crate::error::KpiError;
+
+/// Number of seconds in a (365 days) year
+pub const SECONDS_PER_YEAR: Decimal = dec!(31_536_000);
+
+/// Fallback when the sampling frequency cannot be derived from the series
+pub const DEFAULT_PERIODS_PER_YEAR: Decimal = dec!(365);
+
+/// Annualized risk-free rate over time, as the steps `(effective_from, rate)` of a step-wise
+/// series. The first rate also applies before it is effective.
+#[derive(Debug, Clone, Default)]
+pub struct RiskFreeRates {
+    steps: Vec<(DateTime<Utc>, Decimal)>,
+}
+
+impl RiskFreeRates {
+    /// The same rate at all times
+    pub fn constant(rate: Decimal) -> Self {
+        Self {
+            steps: vec![(DateTime::<Utc>::MIN_UTC, rate)],
+        }
+    }
+
+    pub fn from_steps(mut steps: Vec<(DateTime<Utc>, Decimal)>) -> Self {
+        steps.sort_by_key(|(effective_from, _)| *effective_from);
+        Self { steps }
+    }
+
+    /// Rate in effect at a point in time, zero without any step
+    pub fn at(&self, at: DateTime<Utc>) -> Decimal {
+        self.steps
+            .iter()
+            .take_while(|(effective_from, _)| *effective_from <= at)
+            .last()
+            .or_else(|| self.steps.first())
+            .map_or(Decimal::ZERO, |(_, rate)| *rate)
+    }
+
+    /// Rate accrued over `[start, end]`, each rate weighted by the time it was in effect
+    pub fn accrued(&self, start: DateTime<Utc>, end: DateTime<Utc>) -> Decimal {
+        let mut accrued = Decimal::ZERO;
+        for (index, (effective_from, rate)) in self.steps.iter().enumerate() {
+            let from = if index == 0 {
+                start
+            } else {
+                (*effective_from).max(start)
+            };
+            let to = self
+                .steps
+                .get(index + 1)
+                .map_or(end, |(next_from, _)| (*next_from).min(end));
+            let elapsed_secs = (to - from).num_seconds();
+            if elapsed_secs > 0 {
+                accrued += *rate * Decimal::from(elapsed_secs) / SECONDS_PER_YEAR;
+            }
+        }
+        accrued
+    }
+}
+
+/// A single period return along with the time it spans
+#[derive(Debug, Clone, Copy)]
+pub struct PeriodReturn {
+    pub return_rate: Decimal,
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub year_fraction: Decimal,
+}
+
+impl PeriodReturn {
+    /// Return in excess of the risk-free rates accrued over the same period
+    pub fn excess_return(&self, risk_free_rates: &RiskFreeRates) -> Decimal {
+        self.return_rate - risk_free_rates.accrued(self.start, self.end)
+    }
+}
+
+/// Compute simple returns between consecutive points along with their elapsed time.
+///
+/// Tracking the actual period length lets irregularly sampled series be annualized
+/// correctly. Points sharing the same timestamp as their predecessor are skipped.
+// NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
+pub fn period_returns(
+    portfolio_history: &[(DateTime<Utc>, Decimal)],
+) -> Result<Vec<PeriodReturn>, KpiError> {
+    let Some((mut prev_ts, mut prev_value)) = portfolio_history.first().copied() else {
+        return Ok(Vec::new());
+    };
+    if prev_value < Decimal::ZERO {
+        return Err(KpiError::InvalidData(
+            "Portfolio value cannot be negative".to_string(),
+        ));
+    }
+
+    let mut returns = Vec::with_capacity(portfolio_history.len().saturating_sub(1));
+
+    for (ts, value) in portfolio_history.iter().skip(1) {
+        if *value < Decimal::ZERO {
+            return Err(KpiError::InvalidData(
+                "Portfolio value cannot be negative".to_string(),
+            ));
+        }
+
+        let elapsed_secs = (*ts - prev_ts).num_seconds();
+        if elapsed_secs <= 0 {
+            continue;
+        }
+
+        if prev_value == Decimal::ZERO {
+            return Err(KpiError::InvalidData(
+                "Previous portfolio value is zero, cannot calculate return".to_string(),
+            ));
+        }
+
+        returns.push(PeriodReturn {
+            return_rate: (*value - prev_value) / prev_value,
+            start: prev_ts,
+            end: *ts,
+            year_fraction: Decimal::from(elapsed_secs) / SECONDS_PER_YEAR,
+        });
+        prev_ts = *ts;
+        prev_value = *value;
+    }
+
+    Ok(returns)
+}
+
+/// Derive the number of periods per year from the average spacing between points
+pub fn periods_per_year(returns: &[PeriodReturn]) -> Decimal {
+    let total_year_fraction: Decimal = returns.iter().map(|r| r.year_fraction).sum();
+    if returns.is_empty() || total_year_fraction <= Decimal::ZERO {
+        return DEFAULT_PERIODS_PER_YEAR;
+    }
+
+    Decimal::from(returns.len()) / total_year_fraction
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::test_utils::start;
+
+    #[test]
+    fn test_period_returns_with_irregular_spacing() {
+        let start = start();
+        let history = [
+            (start, dec!(100)),
+            (start + Duration::days(1), dec!(110)),
+            (start + Duration::days(1), dec!(120)),
+            (start + Duration::days(4), dec!(99)),
+        ];
+        let returns = period_returns(&history).unwrap();
+
+        assert_eq!(returns.len(), 2);
+        assert_eq!(returns[0].return_rate, dec!(0.1));
+        assert_eq!(returns[0].year_fraction, dec!(86_400) / SECONDS_PER_YEAR);
+        // The point sharing its timestamp with the previous one is skipped
+        assert_eq!(returns[1].return_rate, dec!(-0.1));
+        assert_eq!(returns[1].start, start + Duration::days(1));
+        assert_eq!(returns[1].year_fraction, dec!(259_200) / SECONDS_PER_YEAR);
+    }
+
+    #[test]
+    fn test_periods_per_year_from_average_spacing() {
+        let start = start();
+        // One daily and one three day period: two periods over four days
+        let history = [
+            (start, dec!(100)),
+            (start + Duration::days(1), dec!(101)),
+            (start + Duration::days(4), dec!(102)),
+        ];
+        let returns = period_returns(&history).unwrap();
+
+        assert!((periods_per_year(&returns) - dec!(182.5)).abs() < dec!(0.000001));
+        assert_eq!(periods_per_year(&[]), DEFAULT_PERIODS_PER_YEAR);
+    }
+
+    #[test]
+    fn test_risk_free_rates_accrued_over_steps() {
+        let start = start();
+        let rates = RiskFreeRates::from_steps(vec![
+            (start + Duration::days(10), dec!(0.0365)),
+            (start + Duration::days(2), dec!(0.073)),
+        ]);
+
+        assert_eq!(rates.at(start), dec!(0.073));
+        assert_eq!(rates.at(start + Duration::days(10)), dec!(0.0365));
+        // 8 days at 7.3% then 2 days at 3.65%
+        assert_eq!(
+            rates.accrued(start + Duration::days(2), start + Duration::days(12)),
+            dec!(0.0018)
+        );
+        assert_eq!(
+            RiskFreeRates::constant(dec!(0.0365)).accrued(start, start + Duration::days(10)),
+            dec!(0.001)
+        );
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, MathematicalOps, dec};
+
+use crate::annualization::SECONDS_PER_YEAR;
+use crate::drawdown::calculate_max_drawdown;
+use crate::error::KpiError;
+
+/// Compounded annual growth rate of a series, `None` when it can't be represented
+// NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
+pub fn calculate_annualized_return(
+    portfolio_history: &[(DateTime<Utc>, Decimal)],
+) -> Result<Option<Decimal>, KpiError> {
+    let (Some(first), Some(last)) = (portfolio_history.first(), portfolio_history.last()) else {
+        return Ok(Some(Decimal::ZERO));
+    };
+
+    annualized_return_between(*first, *last)
+}
+
+/// Compounded annual growth rate between two points of a series.
+/// Short periods of high growth compound beyond what a `Decimal` holds, giving `None`.
+pub(crate) fn annualized_return_between(
+    (first_ts, first_value): (DateTime<Utc>, Decimal),
+    (last_ts, last_value): (DateTime<Utc>, Decimal),
+) -> Result<Option<Decimal>, KpiError> {
+    if first_value <= Decimal::ZERO || last_value < Decimal::ZERO {
+        return Err(KpiError::InvalidData(
+            "Portfolio value must be positive to compute an annualized return".to_string(),
+        ));
+    }
+
+    let elapsed_secs = (last_ts - first_ts).num_seconds();
+    if elapsed_secs <= 0 {
+        return Ok(Some(Decimal::ZERO));
+    }
+
+    let years = Decimal::from(elapsed_secs) / SECONDS_PER_YEAR;
+    let growth = last_value / first_value;
+    if growth == Decimal::ZERO {
+        return Ok(Some(-Decimal::ONE));
+    }
+
+    Ok(growth
+        .checked_powd(Decimal::ONE / years)
+        .and_then(|compounded| compounded.checked_sub(Decimal::ONE)))
+}
+
+/// Calmar ratio: annualized return divided by the maximum drawdown.
+/// `None` when the annualized return can't be computed.
+// NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
+pub fn calculate_calmar_ratio(
+    portfolio_history: &[(DateTime<Utc>, Decimal)],
+) -> Result<Option<Decimal>, KpiError> {
+    if portfolio_history.len() < 2 {
+        return Ok(Some(Decimal::ZERO));
+    }
+
+    let max_drawdown = calculate_max_drawdown(portfolio_history)? / dec!(100);
+    if max_drawdown <= Decimal::ZERO {
+        return Ok(Some(Decimal::ZERO)); // No drawdown, Calmar ratio is undefined
+    }
+
+    Ok(calculate_annualized_return(portfolio_history)?.map(|annualized| annualized / max_drawdown))
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    #[test]
+    fn test_calmar_ratio() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let history = [
+            (start, dec!(100)),
+            (start + Duration::days(100), dec!(50)),
+            (start + Duration::days(365), dec!(200)),
+        ];
+
+        // Doubling over a year with a 50% drawdown
+        let calmar = calculate_calmar_ratio(&history).unwrap().unwrap();
+        assert_eq!(calmar.round_dp(6), dec!(2));
+    }
+
+    #[test]
+    fn test_annualized_return_overflow() {
+        let start = Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let history = [
+            (start, dec!(100)),
+            (start + Duration::seconds(1), dec!(50)),
+            (start + Duration::seconds(2), dec!(100_000)),
+        ];
+
+        assert_eq!(calculate_annualized_return(&history).unwrap(), None);
+        assert_eq!(calculate_calmar_ratio(&history).unwrap(), None);
+    }
+}
@@ -30,3 +30,111 @@ pub fn calculate_max_drawdown(
 
     Ok(max_drawdown)
 }
+
+#[derive(Debug, Clone, Default)]
+pub struct DrawdownDurations {
+    /// Longest time spent below a previous peak, including an ongoing drawdown (in days)
+    pub max_drawdown_duration_days: Decimal,
+    /// Time from the trough of the deepest drawdown back to its previous peak (in days),
+    /// `None` if the portfolio has not recovered yet
+    pub recovery_time_days: Option<Decimal>,
+}
+
+fn to_days(duration: chrono::Duration) -> Decimal {
+    Decimal::from(duration.num_seconds()) / dec!(86400)
+}
+
+// NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
+pub fn calculate_drawdown_durations(
+    portfolio_history: &[(DateTime<Utc>, Decimal)],
+) -> Result<DrawdownDurations, KpiError> {
+    let Some((first_ts, first_value)) = portfolio_history.first() else {
+        return Ok(DrawdownDurations::default());
+    };
+
+    let mut peak = (*first_ts, *first_value);
+    let mut trough = peak;
+    let mut in_drawdown = false;
+    let mut current_is_deepest = false;
+    let mut max_depth = Decimal::ZERO;
+    let mut max_duration = chrono::Duration::zero();
+    let mut recovery_time = None;
+
+    for (ts, value) in portfolio_history {
+        if *value < Decimal::ZERO {
+            return Err(KpiError::InvalidData(
+                "Portfolio value cannot be negative".to_string(),
+            ));
+        }
+
+        if *value >= peak.1 {
+            if in_drawdown {
+                max_duration = max_duration.max(*ts - peak.0);
+                if current_is_deepest {
+                    recovery_time = Some(to_days(*ts - trough.0));
+                }
+            }
+            peak = (*ts, *value);
+            in_drawdown = false;
+            current_is_deepest = false;
+            continue;
+        }
+
+        if !in_drawdown || *value < trough.1 {
+            trough = (*ts, *value);
+        }
+        in_drawdown = true;
+
+        if peak.1 > Decimal::ZERO {
+            let depth = (peak.1 - trough.1) / peak.1;
+            if depth > max_depth {
+                max_depth = depth;
+                current_is_deepest = true;
+                recovery_time = None;
+            }
+        }
+    }
+
+    if in_drawdown && let Some((last_ts, _)) = portfolio_history.last() {
+        max_duration = max_duration.max(*last_ts - peak.0);
+    }
+
+    Ok(DrawdownDurations {
+        max_drawdown_duration_days: to_days(max_duration),
+        recovery_time_days: recovery_time,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::at_days;
+
+    #[test]
+    fn test_recovered_drawdown() {
+        let history = at_days(&[(0, dec!(100)), (1, dec!(80)), (4, dec!(100))]);
+
+        assert_eq!(calculate_max_drawdown(&history).unwrap(), dec!(20));
+        let durations = calculate_drawdown_durations(&history).unwrap();
+        assert_eq!(durations.max_drawdown_duration_days, dec!(4));
+        assert_eq!(durations.recovery_time_days, Some(dec!(3)));
+    }
+
+    #[test]
+    fn test_ongoing_deepest_drawdown() {
+        let history = at_days(&[
+            (0, dec!(100)),
+            (1, dec!(80)),
+            (3, dec!(90)),
+            (5, dec!(100)),
+            (6, dec!(70)),
+            (8, dec!(75)),
+        ]);
+
+        assert_eq!(calculate_max_drawdown(&history).unwrap(), dec!(30));
+        let durations = calculate_drawdown_durations(&history).unwrap();
+        assert_eq!(durations.max_drawdown_duration_days, dec!(5));
+        // The deepest drawdown has not recovered yet
+        assert_eq!(durations.recovery_time_days, None);
+    }
+}
@@ -4,4 +4,6 @@ pub enum KpiError {
     CalculationError(String),
     #[error("Invalid data: {0}")]
     InvalidData(String),
+    #[error("Missing value: {0}")]
+    MissingValue(String),
 }
@@ -0,0 +1,407 @@
+use std::collections::VecDeque;
+
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, MathematicalOps, dec};
+use serde::{Deserialize, Serialize};
+
+use crate::RiskMetricsResult;
+use crate::annualization::{DEFAULT_PERIODS_PER_YEAR, RiskFreeRates, SECONDS_PER_YEAR};
+use crate::calmar::annualized_return_between;
+use crate::error::KpiError;
+use crate::var::{
+    DEFAULT_VAR_CONFIDENCE, HISTORICAL_VAR_WINDOW, cvar_of_returns, historical_var_of_returns,
+    parametric_var_of_moments,
+};
+
+/// Running mean and sum of squared deviations of a series (Welford's algorithm)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct Moments {
+    mean: Decimal,
+    m2: Decimal,
+}
+
+impl Moments {
+    /// Add the `count`-th value of the series
+    fn push(&mut self, value: Decimal, count: u64) {
+        let delta = value - self.mean;
+        self.mean += delta / Decimal::from(count);
+        self.m2 += delta * (value - self.mean);
+    }
+
+    /// Population variance of `count` values
+    fn variance(self, count: u64) -> Decimal {
+        (self.m2 / Decimal::from(count)).max(Decimal::ZERO)
+    }
+}
+
+/// Running accumulators for the risk metrics of a portfolio time series.
+///
+/// Points are appended one at a time so that a daily KPI run only has to push the
+/// latest portfolio value instead of reloading and re-processing the full history.
+/// The state has a bounded size: only the returns of the historical value-at-risk window
+/// are kept.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RiskState {
+    first_point: Option<(DateTime<Utc>, Decimal)>,
+    last_point: Option<(DateTime<Utc>, Decimal)>,
+    risk_free_rate: Decimal,
+
+    // Period returns
+    count: u64,
+    sum_year_fraction: Decimal,
+    returns: Moments,
+    recent_returns: VecDeque<Decimal>,
+
+    // Returns in excess of the risk-free rate accrued over each period
+    excess: Moments,
+    sum_downside_squared: Decimal,
+    downside_count: u64,
+    excess_gains: Decimal,
+    excess_losses: Decimal,
+
+    // Drawdown tracking
+    peak: Option<(DateTime<Utc>, Decimal)>,
+    trough: Option<(DateTime<Utc>, Decimal)>,
+    in_drawdown: bool,
+    current_is_deepest: bool,
+    max_drawdown: Decimal,
+    max_drawdown_duration_secs: i64,
+    recovery_time_secs: Option<i64>,
+}
+
+impl RiskState {
+    /// Build a state from a full portfolio history
+    // NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
+    pub fn from_history(
+        portfolio_history: &[(DateTime<Utc>, Decimal)],
+        risk_free_rates: &RiskFreeRates,
+    ) -> Result<Self, KpiError> {
+        let mut state = Self::default();
+        for (ts, value) in portfolio_history {
+            state.push(*ts, *value, risk_free_rates)?;
+        }
+        Ok(state)
+    }
+
+    /// Timestamp of the latest point pushed into the state
+    pub fn last_point_at(&self) -> Option<DateTime<Utc>> {
+        self.last_point.map(|(ts, _)| ts)
+    }
+
+    /// Append a new point to the series.
+    /// The state is keyed on the day of its latest point: points of a day already applied are
+    /// skipped, so that rerunning the daily job doesn't count the same day twice.
+    pub fn push(
+        &mut self,
+        ts: DateTime<Utc>,
+        value: Decimal,
+        risk_free_rates: &RiskFreeRates,
+    ) -> Result<(), KpiError> {
+        if value < Decimal::ZERO {
+            return Err(KpiError::InvalidData(
+                "Portfolio value cannot be negative".to_string(),
+            ));
+        }
+
+        if let Some((last_ts, _)) = self.last_point
+            && ts.date_naive() <= last_ts.date_naive()
+        {
+            return Ok(());
+        }
+
+        self.risk_free_rate = risk_free_rates.at(ts);
+        self.update_drawdown(ts, value);
+
+        let Some((prev_ts, prev_value)) = self.last_point else {
+            self.first_point = Some((ts, value));
+            self.last_point = Some((ts, value));
+            return Ok(());
+        };
+
+        let elapsed_secs = (ts - prev_ts).num_seconds();
+        if prev_value == Decimal::ZERO {
+            return Err(KpiError::InvalidData(
+                "Previous portfolio value is zero, cannot calculate return".to_string(),
+            ));
+        }
+
+        let return_rate = (value - prev_value) / prev_value;
+        let year_fraction = Decimal::from(elapsed_secs) / SECONDS_PER_YEAR;
+        // The risk-free rates are accrued over the actual period to handle irregular sampling
+        let excess = return_rate - risk_free_rates.accrued(prev_ts, ts);
+
+        self.count += 1;
+        self.sum_year_fraction += year_fraction;
+        self.returns.push(return_rate, self.count);
+        if self.recent_returns.len() == HISTORICAL_VAR_WINDOW {
+            self.recent_returns.pop_front();
+        }
+        self.recent_returns.push_back(return_rate);
+
+        self.excess.push(excess, self.count);
+        if excess < Decimal::ZERO {
+            self.sum_downside_squared += excess * excess;
+            self.downside_count += 1;
+            self.excess_losses -= excess;
+        } else {
+            self.excess_gains += excess;
+        }
+
+        self.last_point = Some((ts, value));
+        Ok(())
+    }
+
+    fn update_drawdown(&mut self, ts: DateTime<Utc>, value: Decimal) {
+        let Some(peak) = self.peak else {
+            self.peak = Some((ts, value));
+            return;
+        };
+
+        if value >= peak.1 {
+            if self.in_drawdown {
+                self.max_drawdown_duration_secs = self
+                    .max_drawdown_duration_secs
+                    .max((ts - peak.0).num_seconds());
+                if self.current_is_deepest {
+                    self.recovery_time_secs = self
+                        .trough
+                        .map(|(trough_ts, _)| (ts - trough_ts).num_seconds());
+                }
+            }
+            self.peak = Some((ts, value));
+            self.in_drawdown = false;
+            self.current_is_deepest = false;
+            return;
+        }
+
+        let trough = match self.trough {
+            Some(trough) if self.in_drawdown && trough.1 <= value => trough,
+            _ => (ts, value),
+        };
+        self.trough = Some(trough);
+        self.in_drawdown = true;
+
+        if peak.1 > Decimal::ZERO {
+            let depth = (peak.1 - trough.1) / peak.1;
+            if depth > self.max_drawdown {
+                self.max_drawdown = depth;
+                self.current_is_deepest = true;
+                self.recovery_time_secs = None;
+            }
+        }
+    }
+
+    fn periods_per_year(&self) -> Decimal {
+        if self.count == 0 || self.sum_year_fraction <= Decimal::ZERO {
+            return DEFAULT_PERIODS_PER_YEAR;
+        }
+        Decimal::from(self.count) / self.sum_year_fraction
+    }
+
+    /// Compute the risk metrics from the accumulated state
+    pub fn metrics(&self) -> Result<RiskMetricsResult, KpiError> {
+        let periods_per_year = self.periods_per_year();
+        let (Some(first_point), Some(last_point)) = (self.first_point, self.last_point) else {
+            return Ok(RiskMetricsResult {
+                risk_free_rate: self.risk_free_rate,
+                periods_per_year,
+                ..Default::default()
+            });
+        };
+
+        let count = self.count;
+        let count_dec = Decimal::from(count);
+        let sqrt_periods = periods_per_year.sqrt().ok_or_else(|| {
+            KpiError::CalculationError("Failed to compute annualization factor".to_string())
+        })?;
+        let sqrt = |variance: Decimal, what: &str| {
+            variance
+                .sqrt()
+                .ok_or_else(|| KpiError::CalculationError(format!("Failed to compute {what}")))
+        };
+
+        let (sharpe_ratio, sortino_ratio, var_parametric_pct, volatility_pct) = if count == 0 {
+            Default::default()
+        } else {
+            let mean_excess = self.excess.mean;
+            let annualized_excess = mean_excess * periods_per_year;
+            let excess_variance = self.excess.variance(count);
+            let sharpe = if excess_variance > Decimal::ZERO {
+                annualized_excess / (sqrt(excess_variance, "standard deviation")? * sqrt_periods)
+            } else {
+                Decimal::ZERO
+            };
+
+            let sortino = if self.downside_count == 0 {
+                if mean_excess > Decimal::ZERO {
+                    dec!(100) // Cap at 100%
+                } else {
+                    Decimal::ZERO
+                }
+            } else {
+                let downside_variance =
+                    self.sum_downside_squared / Decimal::from(self.downside_count);
+                if downside_variance > Decimal::ZERO {
+                    annualized_excess
+                        / (sqrt(downside_variance, "downside deviation")? * sqrt_periods)
+                } else {
+                    Decimal::ZERO
+                }
+            };
+
+            let return_variance = self.returns.variance(count);
+            let var_parametric = parametric_var_of_moments(
+                self.returns.mean,
+                return_variance,
+                DEFAULT_VAR_CONFIDENCE,
+            )?;
+
+            // Sample variance (Bessel's correction)
+            let volatility = if count > 1 && return_variance > Decimal::ZERO {
+                let sample_variance = return_variance * count_dec / (count_dec - Decimal::ONE);
+                sqrt(sample_variance, "standard deviation")? * sqrt_periods * dec!(100)
+            } else {
+                Decimal::ZERO
+            };
+
+            (sharpe, sortino, var_parametric, volatility)
+        };
+
+        let calmar_ratio = if self.max_drawdown > Decimal::ZERO && first_point.1 > Decimal::ZERO {
+            annualized_return_between(first_point, last_point)?
+                .map(|annualized| annualized / self.max_drawdown)
+        } else {
+            Some(Decimal::ZERO)
+        };
+
+        let omega_ratio = if self.excess_losses > Decimal::ZERO {
+            self.excess_gains / self.excess_losses
+        } else if self.excess_gains > Decimal::ZERO {
+            dec!(100) // Cap at 100 when there are no losses
+        } else {
+            Decimal::ZERO
+        };
+
+        let mut max_drawdown_duration_secs = self.max_drawdown_duration_secs;
+        if self.in_drawdown
+            && let Some((peak_ts, _)) = self.peak
+        {
+            max_drawdown_duration_secs =
+                max_drawdown_duration_secs.max((last_point.0 - peak_ts).num_seconds());
+        }
+        let secs_to_days = |secs: i64| Decimal::from(secs) / dec!(86400);
+        let recent_returns: Vec<Decimal> = self.recent_returns.iter().copied().collect();
+
+        Ok(RiskMetricsResult {
+            max_drawdown_pct: self.max_drawdown * dec!(100),
+            sharpe_ratio,
+            sortino_ratio,
+            risk_free_rate: self.risk_free_rate,
+            periods_per_year,
+            volatility_pct,
+            var_historical_pct: historical_var_of_returns(&recent_returns, DEFAULT_VAR_CONFIDENCE),
+            var_parametric_pct,
+            cvar_pct: cvar_of_returns(&recent_returns, DEFAULT_VAR_CONFIDENCE),
+            calmar_ratio,
+            omega_ratio,
+            max_drawdown_duration_days: secs_to_days(max_drawdown_duration_secs),
+            recovery_time_days: self.recovery_time_secs.map(secs_to_days),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::calculate_risk_metrics;
+    use crate::test_utils::daily;
+
+    #[test]
+    fn test_matches_full_history_metrics() {
+        let history = daily([
+            dec!(100),
+            dec!(103),
+            dec!(98),
+            dec!(101),
+            dec!(95),
+            dec!(99),
+            dec!(104),
+            dec!(102),
+        ]);
+        let rates = RiskFreeRates::constant(dec!(0.05));
+        let incremental = RiskState::from_history(&history, &rates)
+            .unwrap()
+            .metrics()
+            .unwrap();
+        let batch = calculate_risk_metrics(&history, &rates).unwrap();
+
+        let close = |a: Decimal, b: Decimal| (a - b).abs() < dec!(0.000001);
+        assert!(close(incremental.sharpe_ratio, batch.sharpe_ratio));
+        assert!(close(incremental.volatility_pct, batch.volatility_pct));
+        assert!(close(
+            incremental.var_parametric_pct,
+            batch.var_parametric_pct
+        ));
+        assert_eq!(incremental.var_historical_pct, batch.var_historical_pct);
+        assert_eq!(incremental.cvar_pct, batch.cvar_pct);
+        assert_eq!(incremental.max_drawdown_pct, batch.max_drawdown_pct);
+        assert_eq!(
+            incremental.max_drawdown_duration_days,
+            batch.max_drawdown_duration_days
+        );
+    }
+
+    #[test]
+    fn test_var_window_matches_full_history_metrics() {
+        // Longer than the value-at-risk window, with a crash that falls out of it
+        let values = (0..500).map(|day: u32| {
+            if day == 10 {
+                dec!(50)
+            } else {
+                dec!(100) + Decimal::from((day * 37) % 23)
+            }
+        });
+        let history = daily(values);
+        let rates = RiskFreeRates::default();
+        let incremental = RiskState::from_history(&history, &rates)
+            .unwrap()
+            .metrics()
+            .unwrap();
+        let batch = calculate_risk_metrics(&history, &rates).unwrap();
+
+        assert_eq!(incremental.var_historical_pct, batch.var_historical_pct);
+        assert_eq!(incremental.cvar_pct, batch.cvar_pct);
+        assert!(batch.cvar_pct < dec!(50));
+    }
+
+    #[test]
+    fn test_rerun_on_the_same_day_is_skipped() {
+        let history = daily([dec!(100), dec!(103), dec!(98)]);
+        let rates = RiskFreeRates::default();
+        let mut state = RiskState::from_history(&history, &rates).unwrap();
+        let before = state.metrics().unwrap();
+
+        let (last_ts, _) = history[2];
+        state
+            .push(last_ts + Duration::hours(6), dec!(90), &rates)
+            .unwrap();
+
+        assert_eq!(state.count, 2);
+        assert_eq!(state.metrics().unwrap().sharpe_ratio, before.sharpe_ratio);
+        assert_eq!(
+            state.metrics().unwrap().max_drawdown_pct,
+            before.max_drawdown_pct
+        );
+    }
+
+    #[test]
+    fn test_bounded_var_window() {
+        let values = (0..400).map(|i| if i % 2 == 0 { dec!(100) } else { dec!(101) });
+        let state = RiskState::from_history(&daily(values), &RiskFreeRates::default()).unwrap();
+
+        assert_eq!(state.count, 399);
+        assert_eq!(state.recent_returns.len(), HISTORICAL_VAR_WINDOW);
+    }
+}
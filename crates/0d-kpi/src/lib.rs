@@ -1,35 +1,62 @@
+pub mod annualization;
+pub mod calmar;
 pub mod cost_basis;
 pub mod drawdown;
 pub mod error;
+pub mod incremental;
+pub mod omega;
+pub mod rewards;
 pub mod service;
 pub mod sharpe;
 pub mod sortino;
 pub mod task;
+pub mod tax_lots;
+#[cfg(test)]
+mod test_utils;
+pub mod var;
+pub mod volatility;
 
 use chrono::{DateTime, Utc};
 use rust_decimal::{Decimal, dec};
 
+use crate::annualization::{period_returns, periods_per_year};
+
 use zerod_db::models::{UserPosition, UserTransaction};
+use zerod_db::types::CostBasisMethod;
 
+pub use annualization::{DEFAULT_PERIODS_PER_YEAR, RiskFreeRates, SECONDS_PER_YEAR};
+pub use calmar::{calculate_annualized_return, calculate_calmar_ratio};
 pub use cost_basis::calculate_cost_basis_and_realized_pnl;
-pub use drawdown::calculate_max_drawdown;
+pub use drawdown::{DrawdownDurations, calculate_drawdown_durations, calculate_max_drawdown};
 pub use error::KpiError;
+pub use incremental::RiskState;
+pub use omega::calculate_omega_ratio;
+pub use rewards::{PartnerRewardEngine, PositionAccrual, accrue_position, partner_reward};
 pub use service::KpiService;
 pub use sharpe::calculate_sharpe_ratio;
 pub use sortino::calculate_sortino_ratio;
 pub use task::KpiTask;
+pub use tax_lots::{LONG_TERM_HOLDING_DAYS, LotLedger, RealizedLot, TaxLot};
+pub use var::{
+    DEFAULT_VAR_CONFIDENCE, HISTORICAL_VAR_WINDOW, calculate_cvar, calculate_historical_var,
+    calculate_parametric_var,
+};
+pub use volatility::calculate_annualized_volatility;
 
 #[derive(Debug, Clone, Default)]
 pub struct PnlCalculationResult {
     pub all_time_pnl: Decimal,
     pub unrealized_pnl: Decimal,
     pub realized_pnl: Decimal,
+    /// Cost basis of the shares still held
+    pub cost_basis: Decimal,
 }
 
 pub fn calculate_user_pnl(
     position: &UserPosition,
     transactions: &[UserTransaction],
     current_share_price: Decimal,
+    method: CostBasisMethod,
 ) -> Result<PnlCalculationResult, KpiError> {
     debug_assert!(
         transactions
@@ -38,6 +65,16 @@ pub fn calculate_user_pnl(
         "Transactions must be sorted in chronological order"
     );
 
+    let ledger = LotLedger::from_transactions(transactions, method)?;
+    calculate_ledger_pnl(position, &ledger, current_share_price)
+}
+
+/// Same as [`calculate_user_pnl`], from the lots of the position already replayed
+pub fn calculate_ledger_pnl(
+    position: &UserPosition,
+    ledger: &LotLedger,
+    current_share_price: Decimal,
+) -> Result<PnlCalculationResult, KpiError> {
     if current_share_price <= Decimal::ZERO {
         return Err(KpiError::InvalidData(
             "Current share price cannot be zero or negative".to_string(),
@@ -50,12 +87,19 @@ pub fn calculate_user_pnl(
         ));
     }
 
+    // A closed position only keeps the gains realized on its way out
     if position.share_balance == Decimal::ZERO {
-        return Ok(PnlCalculationResult::default());
+        let realized_pnl = ledger.realized_pnl();
+        return Ok(PnlCalculationResult {
+            all_time_pnl: realized_pnl,
+            realized_pnl,
+            ..Default::default()
+        });
     }
 
-    // Calculate cost basis and realized PnL from transactions
-    let (current_cost_basis, realized_pnl) = calculate_cost_basis_and_realized_pnl(transactions)?;
+    // Cost basis and realized PnL from the replayed lots
+    let current_cost_basis = ledger.cost_basis();
+    let realized_pnl = ledger.realized_pnl();
 
     // Calculate unrealized PnL
     let current_position_value = position.share_balance * current_share_price;
@@ -68,34 +112,69 @@ pub fn calculate_user_pnl(
         all_time_pnl,
         unrealized_pnl,
         realized_pnl,
+        cost_basis: current_cost_basis,
     })
 }
 
+/// Risk-free rate used when no rate is configured for a vault's base asset
+pub const DEFAULT_RISK_FREE_RATE: Decimal = dec!(0.05); // 5% annualized
+
 #[derive(Debug, Clone, Default)]
 pub struct RiskMetricsResult {
     pub max_drawdown_pct: Decimal,
     pub sharpe_ratio: Decimal,
     pub sortino_ratio: Decimal,
+    /// Annualized risk-free rate in effect at the end of the series. The ratios are computed
+    /// against the rate in effect over each period.
+    pub risk_free_rate: Decimal,
+    /// Annualization factor derived from the sampling frequency of the series
+    pub periods_per_year: Decimal,
+    pub volatility_pct: Decimal,
+    pub var_historical_pct: Decimal,
+    pub var_parametric_pct: Decimal,
+    pub cvar_pct: Decimal,
+    /// `None` when the annualized return overflows
+    pub calmar_ratio: Option<Decimal>,
+    pub omega_ratio: Decimal,
+    pub max_drawdown_duration_days: Decimal,
+    pub recovery_time_days: Option<Decimal>,
 }
 
 pub fn calculate_risk_metrics(
     portfolio_history: &[(DateTime<Utc>, Decimal)],
+    risk_free_rates: &RiskFreeRates,
 ) -> Result<RiskMetricsResult, KpiError> {
+    let risk_free_rate = portfolio_history
+        .last()
+        .map_or(Decimal::ZERO, |(ts, _)| risk_free_rates.at(*ts));
     if portfolio_history.len() < 2 {
-        return Ok(RiskMetricsResult::default());
+        return Ok(RiskMetricsResult {
+            risk_free_rate,
+            periods_per_year: DEFAULT_PERIODS_PER_YEAR,
+            ..Default::default()
+        });
     }
 
-    // TODO: Check for risk related values relevant to the vault
-    let risk_free_rate = dec!(0.05); // 5% annualized
-    let daily_risk_free_rate = risk_free_rate / dec!(365);
+    let periods_per_year = periods_per_year(&period_returns(portfolio_history)?);
 
     let max_drawdown = calculate_max_drawdown(portfolio_history)?;
-    let sharpe = calculate_sharpe_ratio(portfolio_history, risk_free_rate)?;
-    let sortino = calculate_sortino_ratio(portfolio_history, daily_risk_free_rate)?;
+    let sharpe = calculate_sharpe_ratio(portfolio_history, risk_free_rates)?;
+    let sortino = calculate_sortino_ratio(portfolio_history, risk_free_rates)?;
+    let durations = calculate_drawdown_durations(portfolio_history)?;
 
     Ok(RiskMetricsResult {
         max_drawdown_pct: max_drawdown,
         sharpe_ratio: sharpe,
         sortino_ratio: sortino,
+        risk_free_rate,
+        periods_per_year,
+        volatility_pct: calculate_annualized_volatility(portfolio_history)?,
+        var_historical_pct: calculate_historical_var(portfolio_history, DEFAULT_VAR_CONFIDENCE)?,
+        var_parametric_pct: calculate_parametric_var(portfolio_history, DEFAULT_VAR_CONFIDENCE)?,
+        cvar_pct: calculate_cvar(portfolio_history, DEFAULT_VAR_CONFIDENCE)?,
+        calmar_ratio: calculate_calmar_ratio(portfolio_history)?,
+        omega_ratio: calculate_omega_ratio(portfolio_history, risk_free_rates)?,
+        max_drawdown_duration_days: durations.max_drawdown_duration_days,
+        recovery_time_days: durations.recovery_time_days,
     })
 }
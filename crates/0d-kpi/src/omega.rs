@@ -0,0 +1,65 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, dec};
+
+use crate::annualization::{RiskFreeRates, period_returns};
+use crate::error::KpiError;
+
+/// Omega ratio: probability-weighted gains over losses relative to a threshold
+// NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
+pub fn calculate_omega_ratio(
+    portfolio_history: &[(DateTime<Utc>, Decimal)],
+    thresholds: &RiskFreeRates, // Annualized, e.g., the risk-free rates
+) -> Result<Decimal, KpiError> {
+    let returns = period_returns(portfolio_history)?;
+    if returns.is_empty() {
+        return Ok(Decimal::ZERO);
+    }
+
+    let mut gains = Decimal::ZERO;
+    let mut losses = Decimal::ZERO;
+
+    for period in &returns {
+        // The threshold is accrued over the actual period length to handle irregular sampling
+        let excess = period.excess_return(thresholds);
+        if excess > Decimal::ZERO {
+            gains += excess;
+        } else {
+            losses -= excess;
+        }
+    }
+
+    if losses == Decimal::ZERO {
+        return if gains > Decimal::ZERO {
+            Ok(dec!(100)) // Cap at 100 when there are no losses
+        } else {
+            Ok(Decimal::ZERO)
+        };
+    }
+
+    Ok(gains / losses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::daily;
+
+    #[test]
+    fn test_omega_ratio() {
+        let history = daily([dec!(100), dec!(110), dec!(99)]);
+        assert_eq!(
+            calculate_omega_ratio(&history, &RiskFreeRates::default()).unwrap(),
+            Decimal::ONE
+        );
+
+        let rising = daily([dec!(100), dec!(110), dec!(120)]);
+        assert_eq!(
+            calculate_omega_ratio(&rising, &RiskFreeRates::default()).unwrap(),
+            dec!(100)
+        );
+        assert_eq!(
+            calculate_omega_ratio(&rising[..1], &RiskFreeRates::default()).unwrap(),
+            Decimal::ZERO
+        );
+    }
+}
@@ -0,0 +1,364 @@
+use std::collections::{BTreeMap, HashMap};
+use std::str::FromStr;
+
+use anyhow::anyhow;
+use chrono::{DateTime, NaiveTime, Utc};
+use deadpool_diesel::postgres::Pool;
+use rust_decimal::{Decimal, dec};
+
+use zerod_db::ZerodPool;
+use zerod_db::models::{
+    NewPartnerRewardAccrual, Partner, PartnerRewardAccrual, ReferredPosition, UserPortfolioHistory,
+    Vault,
+};
+use zerod_quoting::currencies::CURRENCIES_PRICES;
+use zerod_types::Currency;
+
+use crate::annualization::SECONDS_PER_YEAR;
+
+const BPS_DENOMINATOR: Decimal = dec!(10_000);
+
+/// Yield and fees generated by a referred position between two share prices, in USD
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PositionAccrual {
+    pub tvl_usd: Decimal,
+    pub yield_usd: Decimal,
+    pub management_fees_usd: Decimal,
+    pub performance_fees_usd: Decimal,
+}
+
+impl PositionAccrual {
+    pub fn fees_usd(&self) -> Decimal {
+        self.management_fees_usd + self.performance_fees_usd
+    }
+}
+
+/// Compute the yield and fees of the shares held since the previous share price.
+///
+/// Share prices are in the underlying currency of the vault, converted to USD at
+/// `asset_price_usd`. The management fee accrues pro rata of the elapsed time on the current
+/// value and the performance fee is taken on the positive yield of the shares held.
+pub fn accrue_position(
+    share_balance: Decimal,
+    (previous_at, previous_share_price): (DateTime<Utc>, Decimal),
+    (current_at, current_share_price): (DateTime<Utc>, Decimal),
+    asset_price_usd: Decimal,
+    mgmt_fee_bps: i32,
+    perf_fee_bps: i32,
+) -> PositionAccrual {
+    let tvl_usd = share_balance * current_share_price * asset_price_usd;
+    let yield_usd = share_balance * (current_share_price - previous_share_price) * asset_price_usd;
+
+    let elapsed_secs = (current_at - previous_at).num_seconds().max(0);
+    let management_fees_usd = tvl_usd * Decimal::from(mgmt_fee_bps) / BPS_DENOMINATOR
+        * Decimal::from(elapsed_secs)
+        / SECONDS_PER_YEAR;
+    let performance_fees_usd =
+        yield_usd.max(Decimal::ZERO) * Decimal::from(perf_fee_bps) / BPS_DENOMINATOR;
+
+    PositionAccrual {
+        tvl_usd,
+        yield_usd,
+        management_fees_usd,
+        performance_fees_usd,
+    }
+}
+
+/// Share of the fees owed to a partner
+pub fn partner_reward(fees_usd: Decimal, reward_share_bps: i32) -> Decimal {
+    fees_usd * Decimal::from(reward_share_bps) / BPS_DENOMINATOR
+}
+
+/// USD price of the underlying currency of a vault at a point in time
+async fn get_asset_price_usd(base_asset: &str, at: DateTime<Utc>) -> anyhow::Result<Decimal> {
+    let currency = Currency::from_str(base_asset)?;
+    CURRENCIES_PRICES
+        .convert_series(&[(at, Decimal::ONE)], currency, Currency::USD)
+        .await?
+        .first()
+        .copied()
+        .ok_or_else(|| anyhow!("No USD price of {base_asset} at {at}"))
+}
+
+/// Accrues the rewards of the registered partners on the positions they referred.
+///
+/// A position is referred by the first partner its user deposited through into the vault
+/// (see [`ReferredPosition`]), and accrues from the share price recorded before the current
+/// day to the latest one.
+pub struct PartnerRewardEngine {
+    db_pool: Pool,
+}
+
+impl PartnerRewardEngine {
+    pub const fn new(db_pool: Pool) -> Self {
+        Self { db_pool }
+    }
+
+    /// Record today's accruals of every active partner. Meant to run once the share
+    /// prices of the day are recorded. Returns the number of ledger entries written.
+    pub async fn accrue_daily_rewards(&self) -> anyhow::Result<usize> {
+        let now = Utc::now();
+        let accrual_date = now.date_naive();
+        let day_start = accrual_date.and_time(NaiveTime::MIN).and_utc();
+
+        let partners: HashMap<String, Partner> = self
+            .db_pool
+            .interact_with_context("fetch partners".to_string(), Partner::find_all)
+            .await?
+            .into_iter()
+            .filter(|partner| partner.is_active_at(now))
+            .map(|partner| (partner.id.clone(), partner))
+            .collect();
+        if partners.is_empty() {
+            return Ok(0);
+        }
+
+        let referred_positions = self
+            .db_pool
+            .interact_with_context("fetch referred positions".to_string(), |conn| {
+                ReferredPosition::find_by_partner(None, conn)
+            })
+            .await?;
+        let mut by_vault: BTreeMap<String, Vec<ReferredPosition>> = BTreeMap::new();
+        for position in referred_positions {
+            if partners.contains_key(&position.partner_id) {
+                by_vault
+                    .entry(position.vault_id.clone())
+                    .or_default()
+                    .push(position);
+            }
+        }
+        let vaults: HashMap<String, Vault> = self
+            .db_pool
+            .interact_with_context("fetch all vaults".to_string(), Vault::find_all)
+            .await?
+            .into_iter()
+            .map(|vault| (vault.id.clone(), vault))
+            .collect();
+
+        let mut totals: BTreeMap<(String, String), (i32, PositionAccrual)> = BTreeMap::new();
+        for (vault_id, positions) in by_vault {
+            let Some(vault) = vaults.get(&vault_id) else {
+                continue;
+            };
+            let Some((previous, current)) = self
+                .get_share_price_points(&vault_id, now, day_start)
+                .await?
+            else {
+                continue;
+            };
+            let asset_price_usd = match get_asset_price_usd(&vault.base_asset, current.0).await {
+                Ok(price) => price,
+                Err(e) => {
+                    tracing::warn!(
+                        "[PartnerRewardEngine] ⚠️ No USD price of {} for vault {vault_id}, skipping its rewards: {e:#}",
+                        vault.base_asset
+                    );
+                    continue;
+                }
+            };
+            let (previous_balances, current_balances) = self
+                .get_share_balances(&vault_id, previous.0, current.0)
+                .await?;
+
+            for position in positions {
+                // Positions referred during the period start accruing on the next one
+                if position.referred_at > previous.0 {
+                    continue;
+                }
+                // Only the shares held over the whole period accrue
+                let (Some(previous_balance), Some(current_balance)) = (
+                    previous_balances.get(&position.user_address),
+                    current_balances.get(&position.user_address),
+                ) else {
+                    continue;
+                };
+                let share_balance = (*previous_balance).min(*current_balance);
+                if share_balance <= Decimal::ZERO {
+                    continue;
+                }
+
+                let accrual = accrue_position(
+                    share_balance,
+                    previous,
+                    current,
+                    asset_price_usd,
+                    vault.mgmt_fee_bps.unwrap_or_default(),
+                    vault.perf_fee_bps,
+                );
+                let total = totals
+                    .entry((position.partner_id, vault_id.clone()))
+                    .or_default();
+                total.0 += 1;
+                total.1.tvl_usd += accrual.tvl_usd;
+                total.1.yield_usd += accrual.yield_usd;
+                total.1.management_fees_usd += accrual.management_fees_usd;
+                total.1.performance_fees_usd += accrual.performance_fees_usd;
+            }
+        }
+
+        let accruals: Vec<NewPartnerRewardAccrual> = totals
+            .into_iter()
+            .filter_map(|((partner_id, vault_id), (referred_positions, accrual))| {
+                let reward_share_bps = partners.get(&partner_id)?.reward_share_bps;
+                Some(NewPartnerRewardAccrual {
+                    partner_id,
+                    vault_id,
+                    accrual_date,
+                    referred_positions,
+                    referred_tvl_usd: accrual.tvl_usd,
+                    yield_usd: accrual.yield_usd,
+                    management_fees_usd: accrual.management_fees_usd,
+                    performance_fees_usd: accrual.performance_fees_usd,
+                    reward_share_bps,
+                    reward_usd: partner_reward(accrual.fees_usd(), reward_share_bps),
+                })
+            })
+            .collect();
+
+        let written = self
+            .db_pool
+            .interact_with_context(
+                format!("write {} partner reward accruals", accruals.len()),
+                move |conn| PartnerRewardAccrual::insert_batch(&accruals, conn),
+            )
+            .await?;
+
+        Ok(written)
+    }
+
+    /// Get the share balance of every user of a vault at the start and at the end of
+    /// the period, keyed by user
+    async fn get_share_balances(
+        &self,
+        vault_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> anyhow::Result<(HashMap<String, Decimal>, HashMap<String, Decimal>)> {
+        let vault_id = vault_id.to_string();
+        let (start_balances, end_balances) = self
+            .db_pool
+            .interact_with_context(
+                format!("fetch share balances of vault: {vault_id}"),
+                move |conn| {
+                    let start_balances =
+                        UserPortfolioHistory::find_share_balances_at(&vault_id, start, conn)?;
+                    let end_balances =
+                        UserPortfolioHistory::find_share_balances_at(&vault_id, end, conn)?;
+                    Ok::<_, diesel::result::Error>((start_balances, end_balances))
+                },
+            )
+            .await?;
+
+        Ok((
+            start_balances.into_iter().collect(),
+            end_balances.into_iter().collect(),
+        ))
+    }
+
+    /// Get the share prices of a vault recorded before the current day and today,
+    /// or `None` when the vault has no share price for either
+    async fn get_share_price_points(
+        &self,
+        vault_id: &str,
+        now: DateTime<Utc>,
+        day_start: DateTime<Utc>,
+    ) -> anyhow::Result<Option<((DateTime<Utc>, Decimal), (DateTime<Utc>, Decimal))>> {
+        let vault_id_clone = vault_id.to_string();
+        let (previous, current) = self
+            .db_pool
+            .interact_with_context(
+                format!("fetch share prices of vault: {vault_id}"),
+                move |conn| {
+                    let previous = UserPortfolioHistory::find_share_price_point_before(
+                        &vault_id_clone,
+                        day_start,
+                        conn,
+                    )?;
+                    let current = UserPortfolioHistory::find_share_price_point_before(
+                        &vault_id_clone,
+                        now,
+                        conn,
+                    )?;
+                    Ok::<_, diesel::result::Error>((previous, current))
+                },
+            )
+            .await?;
+
+        match (previous, current) {
+            (Some(previous), Some(current)) if current.0 >= day_start => {
+                Ok(Some((previous, current)))
+            }
+            _ => {
+                tracing::warn!(
+                    "[PartnerRewardEngine] ⚠️ No share price of today and before for vault {vault_id}, skipping its rewards"
+                );
+                Ok(None)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+
+    use super::*;
+
+    #[test]
+    fn test_accrue_position_in_usd() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let accrual = accrue_position(
+            dec!(100),
+            (start, dec!(1.0)),
+            (start + Duration::days(365), dec!(1.1)),
+            dec!(2000),
+            200,
+            1_000,
+        );
+
+        assert_eq!(accrual.tvl_usd, dec!(220000));
+        assert_eq!(accrual.yield_usd, dec!(20000));
+        assert_eq!(accrual.management_fees_usd, dec!(4400));
+        assert_eq!(accrual.performance_fees_usd, dec!(2000));
+        assert_eq!(accrual.fees_usd(), dec!(6400));
+    }
+
+    #[test]
+    fn test_accrue_position_without_yield() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let accrual = accrue_position(
+            dec!(100),
+            (start, dec!(1.1)),
+            (start + Duration::days(365), dec!(1.0)),
+            dec!(1),
+            200,
+            1_000,
+        );
+
+        assert_eq!(accrual.yield_usd, dec!(-10));
+        assert_eq!(accrual.management_fees_usd, dec!(2));
+        assert_eq!(accrual.performance_fees_usd, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_accrue_position_with_share_prices_out_of_order() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let accrual = accrue_position(
+            dec!(100),
+            (start, dec!(1.0)),
+            (start - Duration::days(1), dec!(1.0)),
+            dec!(1),
+            200,
+            1_000,
+        );
+
+        assert_eq!(accrual.management_fees_usd, Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_partner_reward() {
+        assert_eq!(partner_reward(dec!(6400), 2_500), dec!(1600));
+        assert_eq!(partner_reward(dec!(6400), 0), Decimal::ZERO);
+    }
+}
@@ -1,16 +1,24 @@
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use deadpool_diesel::postgres::Pool;
+use diesel::Connection;
+use futures::stream::{self, StreamExt};
 use rust_decimal::Decimal;
-use std::time::Duration;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
 
 use zerod_db::ZerodPool;
 use zerod_db::models::{
-    IndexerState, UserKpi, UserKpiUpdate, UserPortfolioHistory, UserPosition, UserTransaction,
-    Vault,
+    IndexerState, KpiRun, NewKpiRun, NewUserKpi, NewUserPortfolioHistory, NewUserRiskState,
+    NewVaultKpi, RiskFreeRate, UserKpi, UserKpiUpdate, UserPortfolioHistory, UserPosition,
+    UserRiskState, UserTransaction, Vault, VaultKpi,
 };
+use zerod_db::types::CostBasisMethod;
 use zerod_master::{JaffarClient, VaultMasterClient, VesuClient};
 
-use crate::{calculate_risk_metrics, calculate_user_pnl};
+use crate::{
+    DEFAULT_RISK_FREE_RATE, PartnerRewardEngine, RiskFreeRates, RiskState, calculate_risk_metrics,
+    calculate_user_pnl,
+};
 
 pub struct KpiService {
     db_pool: Pool,
@@ -19,6 +27,12 @@ pub struct KpiService {
 impl KpiService {
     const CALCULATION_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60); // 24 hours
     const WAIT_INDEXERS_INTERVAL: Duration = Duration::from_secs(30); // 30 seconds
+    const MAX_CONCURRENT_VAULTS: usize = 4;
+    const MAX_CONCURRENT_CHUNKS: usize = 4;
+    const POSITIONS_CHUNK_SIZE: usize = 500;
+    /// Lot selection method of the stored realized gains, kept on the average cost the stored
+    /// history was computed with. The API applies the method requested by the user.
+    const STORED_COST_BASIS_METHOD: CostBasisMethod = CostBasisMethod::AverageCost;
 
     pub const fn new(db_pool: Pool) -> Self {
         Self { db_pool }
@@ -65,106 +79,299 @@ impl KpiService {
 
     pub async fn run_daily_kpi_calculations(&self) -> anyhow::Result<()> {
         tracing::info!("[KpiService] 🧮 Starting daily KPI calculations...");
-        let start_time = Utc::now();
+        let started_at = Utc::now();
+        let run_timer = Instant::now();
 
         let vaults = self.get_active_vaults().await?;
-        let mut total_updates = 0;
-        let mut total_errors = 0;
-
-        for vault in vaults {
-            match self.calculate_vault_daily_kpis(&vault).await {
-                Ok(updates) => total_updates += updates,
+        let vaults_processed = vaults.len();
+
+        let results: Vec<_> = stream::iter(vaults)
+            .map(|vault| async move {
+                let result = self.calculate_vault_daily_kpis(&vault).await;
+                (vault.id, result)
+            })
+            .buffer_unordered(Self::MAX_CONCURRENT_VAULTS)
+            .collect()
+            .await;
+
+        let mut stats = KpiRunStats::default();
+        for (vault_id, result) in results {
+            match result {
+                Ok(vault_stats) => stats.merge(vault_stats),
                 Err(e) => {
                     tracing::error!(
-                        "[KpiService] 🔴 Failed to calculate daily KPIs for vault {}: {}",
-                        vault.id,
-                        e
+                        "[KpiService] 🔴 Failed to calculate daily KPIs for vault {vault_id}: {e}"
                     );
-                    total_errors += 1;
+                    stats.errors += 1;
                 }
             }
         }
 
-        let duration = Utc::now() - start_time;
+        // Partner rewards accrue on the share prices recorded above
+        match PartnerRewardEngine::new(self.db_pool.clone())
+            .accrue_daily_rewards()
+            .await
+        {
+            Ok(accruals) => {
+                tracing::info!("[KpiService] 🤝 Recorded {accruals} partner reward accruals");
+            }
+            Err(e) => {
+                tracing::error!("[KpiService] 🔴 Failed to accrue partner rewards: {e}");
+                stats.errors += 1;
+            }
+        }
+
+        let total = run_timer.elapsed();
         tracing::info!(
-            "[KpiService] 🧮 Daily KPI calculation completed in {}s. Updates: {}, Errors: {}",
-            duration.num_seconds(),
-            total_updates,
-            total_errors
+            "[KpiService] 🧮 Daily KPI calculation completed in {}ms (fetch: {}ms, compute: {}ms, write: {}ms). Vaults: {}, Updates: {}, Errors: {}",
+            total.as_millis(),
+            stats.fetch.as_millis(),
+            stats.compute.as_millis(),
+            stats.write.as_millis(),
+            vaults_processed,
+            stats.positions_processed,
+            stats.errors
         );
 
+        let new_run = stats.into_new_run(started_at, vaults_processed, total);
+        self.db_pool
+            .interact_with_context("record KPI run".to_string(), move |conn| {
+                KpiRun::create(&new_run, conn)
+            })
+            .await?;
+
         Ok(())
     }
 
-    /// Calculate daily KPIs for all users in a specific vault
-    async fn calculate_vault_daily_kpis(&self, vault: &Vault) -> anyhow::Result<usize> {
+    /// Calculate daily KPIs for all users in a specific vault.
+    /// Everything the vault needs is fetched upfront in a handful of queries, then
+    /// positions are processed in chunks that are each written in a single transaction.
+    async fn calculate_vault_daily_kpis(&self, vault: &Vault) -> anyhow::Result<KpiRunStats> {
+        let mut stats = KpiRunStats::default();
+
+        let fetch_timer = Instant::now();
         let current_share_price = Self::fetch_vault_share_price(vault).await?;
+        let risk_free_rates = self.get_risk_free_rates(&vault.base_asset).await?;
         let user_positions = self.get_vault_user_positions(&vault.id).await?;
-
-        let mut updated_count = 0;
-        for position in user_positions {
-            match self
-                .calculate_user_daily_kpis(&position, &vault.id, current_share_price)
-                .await
-            {
-                Ok(()) => updated_count += 1,
+        let transactions_by_user = self.get_vault_transactions_by_user(&vault.id).await?;
+        let mut risk_states = self.get_vault_risk_states(&vault.id).await?;
+        stats.fetch += fetch_timer.elapsed();
+
+        let chunks: Vec<Vec<(UserPosition, Option<RiskState>)>> = user_positions
+            .chunks(Self::POSITIONS_CHUNK_SIZE)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .map(|position| {
+                        let risk_state = risk_states.remove(&position.user_address);
+                        (position.clone(), risk_state)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        let chunk_results: Vec<_> = stream::iter(chunks)
+            .map(|chunk| {
+                self.process_positions_chunk(
+                    &vault.id,
+                    chunk,
+                    &transactions_by_user,
+                    current_share_price,
+                    &risk_free_rates,
+                )
+            })
+            .buffer_unordered(Self::MAX_CONCURRENT_CHUNKS)
+            .collect()
+            .await;
+
+        for result in chunk_results {
+            match result {
+                Ok(chunk_stats) => stats.merge(chunk_stats),
                 Err(e) => {
                     tracing::error!(
-                        "[KpiService] 🔴 Failed to calculate daily KPIs for user {} in vault {}: {:?}",
-                        position.user_address,
+                        "[KpiService] 🔴 Failed to process a batch of positions in vault {}: {:?}",
                         vault.id,
                         e
                     );
+                    stats.errors += 1;
                 }
             }
         }
 
+        // Vault-level risk metrics run on the share price series, which now includes today's point
+        if let Err(e) = self
+            .calculate_vault_risk_kpis(&vault.id, &risk_free_rates)
+            .await
+        {
+            tracing::error!(
+                "[KpiService] 🔴 Failed to calculate risk KPIs for vault {}: {:?}",
+                vault.id,
+                e
+            );
+            stats.errors += 1;
+        }
+
         tracing::info!(
             "[KpiService] 🧮 Completed KPI calculation for vault {}: {} users updated",
             vault.id,
-            updated_count
+            stats.positions_processed
         );
 
-        Ok(updated_count)
+        Ok(stats)
     }
 
-    /// Calculate and store ALL daily KPIs for a specific user in a specific vault
-    async fn calculate_user_daily_kpis(
+    /// Calculate and store the daily KPIs of a chunk of positions.
+    /// Risk metrics are computed by appending today's value to each user's stored
+    /// risk state; users without a state are bootstrapped from their full history.
+    async fn process_positions_chunk(
         &self,
-        position: &UserPosition,
         vault_id: &str,
+        chunk: Vec<(UserPosition, Option<RiskState>)>,
+        transactions_by_user: &HashMap<String, Vec<UserTransaction>>,
         current_share_price: Decimal,
-    ) -> anyhow::Result<()> {
-        // Get user transactions for this vault
-        let transactions = self
-            .get_user_vault_transactions(&position.user_address, vault_id)
+        risk_free_rates: &RiskFreeRates,
+    ) -> anyhow::Result<KpiRunStats> {
+        let mut stats = KpiRunStats::default();
+
+        let missing_users: Vec<String> = chunk
+            .iter()
+            .filter(|(_, state)| state.is_none())
+            .map(|(position, _)| position.user_address.clone())
+            .collect();
+        let mut histories: HashMap<String, Vec<(DateTime<Utc>, Decimal)>> = HashMap::new();
+        if !missing_users.is_empty() {
+            let fetch_timer = Instant::now();
+            let vault_id_clone = vault_id.to_string();
+            let series = self
+                .db_pool
+                .interact_with_context(
+                    format!(
+                        "fetch portfolio history of {} users for vault: {vault_id}",
+                        missing_users.len()
+                    ),
+                    move |conn| {
+                        UserPortfolioHistory::get_vault_time_series_for_users(
+                            &vault_id_clone,
+                            &missing_users,
+                            conn,
+                        )
+                    },
+                )
+                .await?;
+            for (user_address, ts, value) in series {
+                histories.entry(user_address).or_default().push((ts, value));
+            }
+            stats.fetch += fetch_timer.elapsed();
+        }
+
+        let compute_timer = Instant::now();
+        let calculated_at = Utc::now();
+        let mut history_rows = Vec::with_capacity(chunk.len());
+        let mut state_rows = Vec::with_capacity(chunk.len());
+        let mut kpi_rows = Vec::with_capacity(chunk.len());
+
+        for (position, state) in chunk {
+            let transactions = transactions_by_user
+                .get(&position.user_address)
+                .map_or(&[][..], Vec::as_slice);
+
+            match Self::compute_user_daily_kpis(
+                &position,
+                transactions,
+                state,
+                histories.get(&position.user_address),
+                current_share_price,
+                risk_free_rates,
+                calculated_at,
+            ) {
+                Ok((history_row, state_row, kpi_row)) => {
+                    history_rows.push(history_row);
+                    state_rows.push(state_row);
+                    kpi_rows.push(kpi_row);
+                }
+                Err(e) => {
+                    tracing::error!(
+                        "[KpiService] 🔴 Failed to calculate daily KPIs for user {} in vault {}: {:?}",
+                        position.user_address,
+                        vault_id,
+                        e
+                    );
+                    stats.errors += 1;
+                }
+            }
+        }
+        stats.compute += compute_timer.elapsed();
+
+        let write_timer = Instant::now();
+        let updated = self
+            .db_pool
+            .interact_with_context(
+                format!(
+                    "write daily KPIs of {} users for vault: {vault_id}",
+                    kpi_rows.len()
+                ),
+                move |conn| {
+                    conn.transaction(|conn| {
+                        UserPortfolioHistory::insert_batch(&history_rows, conn)?;
+                        UserRiskState::upsert_batch(&state_rows, conn)?;
+                        UserKpi::upsert_batch(&kpi_rows, conn)
+                    })
+                },
+            )
             .await?;
+        stats.write += write_timer.elapsed();
+        stats.positions_processed += updated;
 
+        Ok(stats)
+    }
+
+    /// Compute the daily portfolio snapshot, updated risk state and KPI record of a user
+    fn compute_user_daily_kpis(
+        position: &UserPosition,
+        transactions: &[UserTransaction],
+        state: Option<RiskState>,
+        history: Option<&Vec<(DateTime<Utc>, Decimal)>>,
+        current_share_price: Decimal,
+        risk_free_rates: &RiskFreeRates,
+        calculated_at: DateTime<Utc>,
+    ) -> anyhow::Result<(NewUserPortfolioHistory, NewUserRiskState, NewUserKpi)> {
         // Calculate PnL
-        let pnl_result = calculate_user_pnl(position, &transactions, current_share_price)?;
+        let pnl_result = calculate_user_pnl(
+            position,
+            transactions,
+            current_share_price,
+            Self::STORED_COST_BASIS_METHOD,
+        )?;
 
         // Calculate current portfolio value
         let current_portfolio_value = position.share_balance * current_share_price;
 
-        // Insert daily portfolio history snapshot
-        self.insert_daily_portfolio_history(
-            &position.user_address,
-            vault_id,
-            current_portfolio_value,
-            position.share_balance,
-            current_share_price,
-        )
-        .await?;
-
-        // Get historical portfolio data for risk metrics from portfolio history table
-        let portfolio_history = self
-            .get_user_portfolio_history(&position.user_address, vault_id)
-            .await?;
+        // Append today's value to the risk state, rebuilding it from history if needed
+        let mut state = match state {
+            Some(state) => state,
+            None => {
+                RiskState::from_history(history.map_or(&[][..], Vec::as_slice), risk_free_rates)?
+            }
+        };
+        state.push(calculated_at, current_portfolio_value, risk_free_rates)?;
+        let risk_metrics = state.metrics()?;
+
+        let history_row = NewUserPortfolioHistory {
+            user_address: position.user_address.clone(),
+            vault_id: position.vault_id.clone(),
+            portfolio_value: current_portfolio_value,
+            share_balance: position.share_balance,
+            share_price: current_share_price,
+            calculated_at,
+        };
 
-        // Calculate risk metrics using historical data
-        let risk_metrics = calculate_risk_metrics(&portfolio_history)?;
+        let state_row = NewUserRiskState {
+            user_address: position.user_address.clone(),
+            vault_id: position.vault_id.clone(),
+            state: serde_json::to_value(&state)?,
+            last_point_at: state.last_point_at().unwrap_or(calculated_at),
+        };
 
-        // Create comprehensive KPI update
         let kpi_update = UserKpiUpdate {
             all_time_pnl: Some(pnl_result.all_time_pnl),
             unrealized_pnl: Some(pnl_result.unrealized_pnl),
@@ -172,47 +379,104 @@ impl KpiService {
             max_drawdown_pct: Some(risk_metrics.max_drawdown_pct),
             sharpe_ratio: Some(risk_metrics.sharpe_ratio),
             sortino_ratio: Some(risk_metrics.sortino_ratio),
-            total_deposits: Some(UserTransaction::calculate_total_deposits(&transactions)),
-            total_withdrawals: Some(UserTransaction::calculate_total_withdrawals(&transactions)),
-            total_fees_paid: Some(UserTransaction::calculate_total_fees(&transactions)),
-            calculated_at: Some(Utc::now()),
+            total_deposits: Some(UserTransaction::calculate_total_deposits(transactions)),
+            total_withdrawals: Some(UserTransaction::calculate_total_withdrawals(transactions)),
+            total_fees_paid: Some(UserTransaction::calculate_total_fees(transactions)),
+            calculated_at: Some(calculated_at),
             share_price_used: Some(current_share_price),
             share_balance: Some(position.share_balance), // Store current share balance
-            updated_at: Some(Utc::now()),
+            risk_free_rate: Some(risk_metrics.risk_free_rate),
+            periods_per_year: Some(risk_metrics.periods_per_year),
+            volatility_pct: Some(risk_metrics.volatility_pct),
+            var_historical_pct: Some(risk_metrics.var_historical_pct),
+            var_parametric_pct: Some(risk_metrics.var_parametric_pct),
+            cvar_pct: Some(risk_metrics.cvar_pct),
+            calmar_ratio: risk_metrics.calmar_ratio,
+            omega_ratio: Some(risk_metrics.omega_ratio),
+            max_drawdown_duration_days: Some(risk_metrics.max_drawdown_duration_days),
+            recovery_time_days: risk_metrics.recovery_time_days,
+            updated_at: Some(calculated_at),
         };
+        let kpi_row =
+            NewUserKpi::from_update(&position.user_address, &position.vault_id, &kpi_update);
 
-        // Update current KPI record with latest values
-        self.insert_daily_user_kpis(&position.user_address, vault_id, &kpi_update)
-            .await?;
-
-        Ok(())
+        Ok((history_row, state_row, kpi_row))
     }
 
-    /// Get historical portfolio values for calculating risk metrics
-    /// Returns a time series of (timestamp, `portfolio_value`) tuples
-    async fn get_user_portfolio_history(
+    /// Calculate and store risk KPIs for a vault based on its share price history
+    async fn calculate_vault_risk_kpis(
         &self,
-        user_address: &str,
         vault_id: &str,
-    ) -> anyhow::Result<Vec<(chrono::DateTime<Utc>, Decimal)>> {
-        let user_address_clone = user_address.to_string();
+        risk_free_rates: &RiskFreeRates,
+    ) -> anyhow::Result<()> {
         let vault_id_clone = vault_id.to_string();
-
-        // Get historical portfolio data from portfolio history table
-        let portfolio_history = self
+        let share_price_series = self
             .db_pool
             .interact_with_context(
-                format!("fetch portfolio history for user: {user_address}, vault: {vault_id}"),
+                format!("fetch share price series for vault: {vault_id}"),
                 move |conn| {
-                    UserPortfolioHistory::get_portfolio_time_series(
-                        &user_address_clone,
-                        &vault_id_clone,
-                        conn,
-                    )
+                    UserPortfolioHistory::get_share_price_series(&vault_id_clone, None, conn)
                 },
             )
             .await?;
-        Ok(portfolio_history)
+
+        let risk_metrics = calculate_risk_metrics(&share_price_series, risk_free_rates)?;
+
+        let new_kpi = NewVaultKpi {
+            vault_id: vault_id.to_string(),
+            max_drawdown_pct: Some(risk_metrics.max_drawdown_pct),
+            sharpe_ratio: Some(risk_metrics.sharpe_ratio),
+            sortino_ratio: Some(risk_metrics.sortino_ratio),
+            volatility_pct: Some(risk_metrics.volatility_pct),
+            var_historical_pct: Some(risk_metrics.var_historical_pct),
+            var_parametric_pct: Some(risk_metrics.var_parametric_pct),
+            cvar_pct: Some(risk_metrics.cvar_pct),
+            calmar_ratio: risk_metrics.calmar_ratio,
+            omega_ratio: Some(risk_metrics.omega_ratio),
+            max_drawdown_duration_days: Some(risk_metrics.max_drawdown_duration_days),
+            recovery_time_days: risk_metrics.recovery_time_days,
+            risk_free_rate: Some(risk_metrics.risk_free_rate),
+            periods_per_year: Some(risk_metrics.periods_per_year),
+            calculated_at: Some(Utc::now()),
+        };
+
+        self.db_pool
+            .interact_with_context(
+                format!("upsert vault KPIs for vault: {vault_id}"),
+                move |conn| VaultKpi::upsert(&new_kpi, conn),
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get the annualized risk-free rates of a base asset over time, so that each period is
+    /// measured against the rate in effect over it. Falls back to the default rate when none
+    /// is configured.
+    async fn get_risk_free_rates(&self, base_asset: &str) -> anyhow::Result<RiskFreeRates> {
+        let base_asset_clone = base_asset.to_string();
+
+        let rates = self
+            .db_pool
+            .interact_with_context(
+                format!("fetch risk-free rates for base asset: {base_asset}"),
+                move |conn| RiskFreeRate::find_by_base_asset(&base_asset_clone, conn),
+            )
+            .await?;
+
+        if rates.is_empty() {
+            tracing::warn!(
+                "[KpiService] ⚠️ No risk-free rate configured for {base_asset}, using default {DEFAULT_RISK_FREE_RATE}"
+            );
+            return Ok(RiskFreeRates::constant(DEFAULT_RISK_FREE_RATE));
+        }
+
+        Ok(RiskFreeRates::from_steps(
+            rates
+                .into_iter()
+                .map(|rate| (rate.effective_from, rate.annual_rate))
+                .collect(),
+        ))
     }
 
     /// Get all active vaults
@@ -240,86 +504,61 @@ impl KpiService {
         Ok(positions)
     }
 
-    /// Get user transactions for a specific vault
-    async fn get_user_vault_transactions(
+    /// Get all transactions of a vault, grouped by user in chronological order
+    async fn get_vault_transactions_by_user(
         &self,
-        user_address: &str,
         vault_id: &str,
-    ) -> anyhow::Result<Vec<UserTransaction>> {
-        let user_address = user_address.to_string();
-        let vault_id = vault_id.to_string();
+    ) -> anyhow::Result<HashMap<String, Vec<UserTransaction>>> {
+        let vault_id_clone = vault_id.to_string();
 
         let transactions = self
             .db_pool
             .interact_with_context(
-                format!("fetch transactions for user: {user_address}, vault: {vault_id}"),
-                move |conn| {
-                    UserTransaction::find_by_user_and_vault_chronological(
-                        &user_address,
-                        &vault_id,
-                        conn,
-                    )
-                },
+                format!("fetch transactions for vault: {vault_id}"),
+                move |conn| UserTransaction::find_by_vault_chronological(&vault_id_clone, conn),
             )
             .await?;
 
-        Ok(transactions)
-    }
-
-    /// Insert daily portfolio history snapshot
-    async fn insert_daily_portfolio_history(
-        &self,
-        user_address: &str,
-        vault_id: &str,
-        portfolio_value: Decimal,
-        share_balance: Decimal,
-        share_price: Decimal,
-    ) -> anyhow::Result<()> {
-        let user_address = user_address.to_string();
-        let vault_id = vault_id.to_string();
-        let calculated_at = Utc::now();
-
-        self.db_pool
-            .interact_with_context(
-                format!(
-                    "insert daily portfolio history for user: {user_address}, vault: {vault_id}"
-                ),
-                move |conn| {
-                    UserPortfolioHistory::insert_daily_record(
-                        &user_address,
-                        &vault_id,
-                        portfolio_value,
-                        share_balance,
-                        share_price,
-                        calculated_at,
-                        conn,
-                    )
-                },
-            )
-            .await?;
+        let mut by_user: HashMap<String, Vec<UserTransaction>> = HashMap::new();
+        for transaction in transactions {
+            by_user
+                .entry(transaction.user_address.clone())
+                .or_default()
+                .push(transaction);
+        }
 
-        Ok(())
+        Ok(by_user)
     }
 
-    /// Insert or update daily user KPI record (upserts to handle multiple runs per day)
-    async fn insert_daily_user_kpis(
+    /// Get the stored risk states of a vault, keyed by user address.
+    /// States that fail to deserialize are dropped so they get rebuilt from history.
+    async fn get_vault_risk_states(
         &self,
-        user_address: &str,
         vault_id: &str,
-        kpi_data: &UserKpiUpdate,
-    ) -> anyhow::Result<()> {
-        let user_address = user_address.to_string();
-        let vault_id = vault_id.to_string();
-        let kpi_data = kpi_data.clone();
+    ) -> anyhow::Result<HashMap<String, RiskState>> {
+        let vault_id_clone = vault_id.to_string();
 
-        self.db_pool
+        let states = self
+            .db_pool
             .interact_with_context(
-                format!("upsert user KPIs for user: {user_address}, vault: {vault_id}"),
-                move |conn| UserKpi::upsert(&user_address, &vault_id, &kpi_data, conn),
+                format!("fetch risk states for vault: {vault_id}"),
+                move |conn| UserRiskState::find_by_vault(&vault_id_clone, conn),
             )
             .await?;
 
-        Ok(())
+        Ok(states
+            .into_iter()
+            .filter_map(|row| match serde_json::from_value(row.state) {
+                Ok(state) => Some((row.user_address, state)),
+                Err(e) => {
+                    tracing::warn!(
+                        "[KpiService] ⚠️ Invalid risk state for user {} in vault {vault_id}, rebuilding: {e}",
+                        row.user_address
+                    );
+                    None
+                }
+            })
+            .collect())
     }
 
     /// Determine if a vault is an alternative vault (vaults 2-6)
@@ -347,3 +586,45 @@ impl KpiService {
             .map_err(|e| anyhow::anyhow!("Invalid share price format: {e}"))
     }
 }
+
+/// Counters and phase timings accumulated over a KPI run
+#[derive(Debug, Default)]
+struct KpiRunStats {
+    positions_processed: usize,
+    errors: usize,
+    fetch: Duration,
+    compute: Duration,
+    write: Duration,
+}
+
+impl KpiRunStats {
+    fn merge(&mut self, other: Self) {
+        self.positions_processed += other.positions_processed;
+        self.errors += other.errors;
+        self.fetch += other.fetch;
+        self.compute += other.compute;
+        self.write += other.write;
+    }
+
+    fn into_new_run(
+        self,
+        started_at: DateTime<Utc>,
+        vaults_processed: usize,
+        total: Duration,
+    ) -> NewKpiRun {
+        let count = |n: usize| i32::try_from(n).unwrap_or(i32::MAX);
+        let millis = |d: Duration| i64::try_from(d.as_millis()).unwrap_or(i64::MAX);
+
+        NewKpiRun {
+            started_at,
+            finished_at: Utc::now(),
+            vaults_processed: count(vaults_processed),
+            positions_processed: count(self.positions_processed),
+            errors: count(self.errors),
+            fetch_ms: millis(self.fetch),
+            compute_ms: millis(self.compute),
+            write_ms: millis(self.write),
+            total_ms: millis(total),
+        }
+    }
+}
@@ -1,56 +1,37 @@
 use chrono::{DateTime, Utc};
-use rust_decimal::{Decimal, MathematicalOps, prelude::FromPrimitive};
+use rust_decimal::{Decimal, MathematicalOps};
 
+use crate::annualization::{RiskFreeRates, period_returns, periods_per_year};
 use crate::error::KpiError;
 
 // NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
 pub fn calculate_sharpe_ratio(
     portfolio_history: &[(DateTime<Utc>, Decimal)],
-    risk_free_rate: Decimal,
+    risk_free_rates: &RiskFreeRates,
 ) -> Result<Decimal, KpiError> {
     if portfolio_history.len() < 2 {
         return Ok(Decimal::ZERO);
     }
 
-    let mut prev_value = portfolio_history[0].1;
-    if prev_value < Decimal::ZERO {
-        return Err(KpiError::InvalidData(
-            "Portfolio value cannot be negative".to_string(),
-        ));
+    let returns = period_returns(portfolio_history)?;
+    if returns.is_empty() {
+        return Ok(Decimal::ZERO);
     }
 
-    let mut sum_returns = Decimal::ZERO;
-    let mut sum_squared_returns = Decimal::ZERO;
-    let mut count = 0;
-
-    for (_, value) in portfolio_history.iter().skip(1) {
-        if *value < Decimal::ZERO {
-            return Err(KpiError::InvalidData(
-                "Portfolio value cannot be negative".to_string(),
-            ));
-        }
-        let return_rate = if prev_value == Decimal::ZERO {
-            return Err(KpiError::InvalidData(
-                "Previous portfolio value is zero, cannot calculate return".to_string(),
-            ));
-        } else {
-            (*value - prev_value) / prev_value
-        };
-        sum_returns += return_rate;
-        sum_squared_returns += return_rate * return_rate;
-        count += 1;
-        prev_value = *value;
-    }
+    let mut sum_excess = Decimal::ZERO;
+    let mut sum_squared_excess = Decimal::ZERO;
 
-    if count == 0 {
-        return Ok(Decimal::ZERO);
+    for period in &returns {
+        // The risk-free rates are accrued over the actual period to handle irregular sampling
+        let excess = period.excess_return(risk_free_rates);
+        sum_excess += excess;
+        sum_squared_excess += excess * excess;
     }
 
-    let count_dec = Decimal::from(count);
-    let mean_return = sum_returns / count_dec;
+    let count_dec = Decimal::from(returns.len());
+    let mean_excess = sum_excess / count_dec;
     // Variance = E[X^2] - (E[X])^2
-    let mean_return_squared = mean_return.powu(2);
-    let variance = (sum_squared_returns / count_dec) - mean_return_squared;
+    let variance = (sum_squared_excess / count_dec) - mean_excess.powu(2);
     let std_dev = if variance > Decimal::ZERO {
         variance.sqrt().ok_or_else(|| {
             KpiError::CalculationError("Failed to compute standard deviation".to_string())
@@ -59,12 +40,12 @@ pub fn calculate_sharpe_ratio(
         return Ok(Decimal::ZERO); // No volatility, Sharpe ratio is undefined
     };
 
-    // Assuming daily returns; adjust for actual time deltas if needed
-    let annualized_return = mean_return * Decimal::from(365);
+    let periods = periods_per_year(&returns);
+    let annualized_excess = mean_excess * periods;
     let annualized_std_dev = std_dev
-        * Decimal::from_f64(365.0_f64.sqrt()).ok_or_else(|| {
+        * periods.sqrt().ok_or_else(|| {
             KpiError::CalculationError("Failed to compute annualized std dev".to_string())
         })?;
 
-    Ok((annualized_return - risk_free_rate) / annualized_std_dev)
+    Ok(annualized_excess / annualized_std_dev)
 }
@@ -1,62 +1,42 @@
-use rust_decimal::{Decimal, MathematicalOps, dec, prelude::FromPrimitive};
+use rust_decimal::{Decimal, MathematicalOps, dec};
 
 use chrono::{DateTime, Utc};
 
+use crate::annualization::{RiskFreeRates, period_returns, periods_per_year};
 use crate::error::KpiError;
 
 // NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
 pub fn calculate_sortino_ratio(
     portfolio_history: &[(DateTime<Utc>, Decimal)],
-    target_return: Decimal, // Daily, e.g., risk_free_rate / 365
+    target_rates: &RiskFreeRates, // Annualized, e.g., the risk-free rates
 ) -> Result<Decimal, KpiError> {
     if portfolio_history.len() < 2 {
         return Ok(Decimal::ZERO);
     }
 
-    let mut prev_value = portfolio_history[0].1;
-    if prev_value < Decimal::ZERO {
-        return Err(KpiError::InvalidData(
-            "Portfolio value cannot be negative".to_string(),
-        ));
+    let returns = period_returns(portfolio_history)?;
+    if returns.is_empty() {
+        return Ok(Decimal::ZERO);
     }
 
-    let mut sum_returns = Decimal::ZERO;
+    let mut sum_excess = Decimal::ZERO;
     let mut sum_downside_squared = Decimal::ZERO;
     let mut downside_count = 0;
-    let mut count = 0;
 
-    for (_, value) in portfolio_history.iter().skip(1) {
-        if *value < Decimal::ZERO {
-            return Err(KpiError::InvalidData(
-                "Portfolio value cannot be negative".to_string(),
-            ));
-        }
-        let return_rate = if prev_value == Decimal::ZERO {
-            return Err(KpiError::InvalidData(
-                "Previous portfolio value is zero, cannot calculate return".to_string(),
-            ));
-        } else {
-            (*value - prev_value) / prev_value
-        };
-        sum_returns += return_rate;
-        if return_rate < target_return {
-            let downside = return_rate - target_return;
-            sum_downside_squared += downside * downside;
+    for period in &returns {
+        // The target is accrued over the actual period length to handle irregular sampling
+        let excess = period.excess_return(target_rates);
+        sum_excess += excess;
+        if excess < Decimal::ZERO {
+            sum_downside_squared += excess * excess;
             downside_count += 1;
         }
-        count += 1;
-        prev_value = *value;
-    }
-
-    if count == 0 {
-        return Ok(Decimal::ZERO);
     }
 
-    let count_dec = Decimal::from(count);
-    let mean_return = sum_returns / count_dec;
+    let mean_excess = sum_excess / Decimal::from(returns.len());
 
     if downside_count == 0 {
-        return if mean_return > target_return {
+        return if mean_excess > Decimal::ZERO {
             Ok(dec!(100)) // Cap at 100%
         } else {
             Ok(Decimal::ZERO) // No excess return, Sortino ratio is zero
@@ -73,13 +53,12 @@ pub fn calculate_sortino_ratio(
         return Ok(Decimal::ZERO); // No downside volatility, Sortino ratio is undefined
     };
 
-    // Assuming daily returns; adjust for actual time deltas if needed
-    let annualized_return = mean_return * dec!(365);
-    let annualized_target = target_return * dec!(365);
+    let periods = periods_per_year(&returns);
+    let annualized_excess = mean_excess * periods;
     let annualized_downside_dev = downside_deviation
-        * Decimal::from_f64(365.0_f64.sqrt()).ok_or_else(|| {
+        * periods.sqrt().ok_or_else(|| {
             KpiError::CalculationError("Failed to compute annualized downside dev".to_string())
         })?;
 
-    Ok((annualized_return - annualized_target) / annualized_downside_dev)
+    Ok(annualized_excess / annualized_downside_dev)
 }
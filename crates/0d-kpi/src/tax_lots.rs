@@ -0,0 +1,358 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use zerod_db::models::{TransactionStatus, UserTransaction};
+use zerod_db::types::CostBasisMethod;
+
+use crate::error::KpiError;
+
+/// Holding period (in days) beyond which a disposal is considered long-term
+pub const LONG_TERM_HOLDING_DAYS: i64 = 365;
+
+/// Shares acquired by a single deposit that are still held
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxLot {
+    pub acquired_at: DateTime<Utc>,
+    pub tx_hash: String,
+    pub shares: Decimal,
+    pub cost_basis: Decimal,
+}
+
+impl TaxLot {
+    pub fn cost_per_share(&self) -> Decimal {
+        if self.shares == Decimal::ZERO {
+            return Decimal::ZERO;
+        }
+        self.cost_basis / self.shares
+    }
+}
+
+/// Portion of a lot disposed of by a withdrawal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RealizedLot {
+    pub acquired_at: DateTime<Utc>,
+    pub disposed_at: DateTime<Utc>,
+    pub acquisition_tx_hash: String,
+    pub disposal_tx_hash: String,
+    pub shares: Decimal,
+    pub cost_basis: Decimal,
+    pub proceeds: Decimal,
+    pub realized_gain: Decimal,
+    pub holding_period_days: i64,
+}
+
+impl RealizedLot {
+    pub const fn is_long_term(&self) -> bool {
+        self.holding_period_days > LONG_TERM_HOLDING_DAYS
+    }
+}
+
+/// Tracks individual deposit lots and matches withdrawals against them
+/// according to the selected cost basis method
+#[derive(Debug, Clone)]
+pub struct LotLedger {
+    method: CostBasisMethod,
+    open_lots: Vec<TaxLot>,
+    realized_lots: Vec<RealizedLot>,
+}
+
+impl LotLedger {
+    pub const fn new(method: CostBasisMethod) -> Self {
+        Self {
+            method,
+            open_lots: Vec::new(),
+            realized_lots: Vec::new(),
+        }
+    }
+
+    /// Replay the confirmed deposits and withdrawals of a position
+    /// NOTE: transactions must be pre-sorted in chronological order (oldest first)
+    pub fn from_transactions(
+        transactions: &[UserTransaction],
+        method: CostBasisMethod,
+    ) -> Result<Self, KpiError> {
+        // NOTE: Amount should be normalized
+        Self::replay(transactions, method, |_, tx| Ok(tx.amount))
+    }
+
+    /// Same as `from_transactions`, booking each transaction at the matching entry of
+    /// `amounts` (e.g. its USD value at transaction time) instead of its amount.
+    /// Fails when a confirmed deposit or withdrawal has no value rather than mixing units.
+    pub fn from_valued_transactions(
+        transactions: &[UserTransaction],
+        amounts: &[Option<Decimal>],
+        method: CostBasisMethod,
+    ) -> Result<Self, KpiError> {
+        Self::replay(transactions, method, |index, tx| {
+            amounts.get(index).copied().flatten().ok_or_else(|| {
+                KpiError::MissingValue(format!("transaction {} has no value", tx.tx_hash))
+            })
+        })
+    }
+
+    fn replay(
+        transactions: &[UserTransaction],
+        method: CostBasisMethod,
+        amount_of: impl Fn(usize, &UserTransaction) -> Result<Decimal, KpiError>,
+    ) -> Result<Self, KpiError> {
+        let mut ledger = Self::new(method);
+
+        for (index, tx) in transactions.iter().enumerate() {
+            if tx.status != TransactionStatus::Confirmed.as_str() {
+                continue;
+            }
+
+            match tx.type_.as_str() {
+                "deposit" => {
+                    if let Some(shares) = tx.shares_amount {
+                        let amount = amount_of(index, tx)?;
+                        ledger.deposit(tx.block_timestamp, &tx.tx_hash, shares, amount)?;
+                    }
+                }
+                "withdraw" => {
+                    if let Some(shares) = tx.shares_amount
+                        && ledger.share_balance() > Decimal::ZERO
+                    {
+                        let amount = amount_of(index, tx)?;
+                        ledger.withdraw(tx.block_timestamp, &tx.tx_hash, shares, amount)?;
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        Ok(ledger)
+    }
+
+    /// Open a new lot
+    pub fn deposit(
+        &mut self,
+        acquired_at: DateTime<Utc>,
+        tx_hash: &str,
+        shares: Decimal,
+        amount: Decimal,
+    ) -> Result<(), KpiError> {
+        if shares < Decimal::ZERO {
+            return Err(KpiError::InvalidData(
+                "Shares amount cannot be negative".to_string(),
+            ));
+        }
+
+        self.open_lots.push(TaxLot {
+            acquired_at,
+            tx_hash: tx_hash.to_string(),
+            shares,
+            cost_basis: amount,
+        });
+        Ok(())
+    }
+
+    /// Dispose of shares, realizing gains on the lots selected by the cost basis method
+    pub fn withdraw(
+        &mut self,
+        disposed_at: DateTime<Utc>,
+        tx_hash: &str,
+        shares: Decimal,
+        proceeds: Decimal,
+    ) -> Result<(), KpiError> {
+        if shares < Decimal::ZERO {
+            return Err(KpiError::InvalidData(
+                "Shares amount cannot be negative".to_string(),
+            ));
+        }
+        let share_balance = self.share_balance();
+        if shares > share_balance {
+            return Err(KpiError::InvalidData(
+                "Cannot withdraw more shares than available".to_string(),
+            ));
+        }
+        if shares == Decimal::ZERO {
+            return Ok(());
+        }
+
+        let proceeds_per_share = proceeds / shares;
+
+        if self.method == CostBasisMethod::AverageCost {
+            // Every lot is re-valued at the average cost, then sold pro rata
+            let avg_cost_per_share = self.cost_basis() / share_balance;
+            let fraction = shares / share_balance;
+            for index in 0..self.open_lots.len() {
+                let lot = &mut self.open_lots[index];
+                lot.cost_basis = lot.shares * avg_cost_per_share;
+                let sold = lot.shares * fraction;
+                self.dispose(index, sold, proceeds_per_share, disposed_at, tx_hash);
+            }
+        } else {
+            let mut remaining = shares;
+            for index in self.disposal_order() {
+                if remaining <= Decimal::ZERO {
+                    break;
+                }
+                let sold = remaining.min(self.open_lots[index].shares);
+                self.dispose(index, sold, proceeds_per_share, disposed_at, tx_hash);
+                remaining -= sold;
+            }
+        }
+
+        self.open_lots.retain(|lot| lot.shares > Decimal::ZERO);
+        Ok(())
+    }
+
+    /// Indices of the open lots in the order they should be sold
+    fn disposal_order(&self) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.open_lots.len()).collect();
+        match self.method {
+            CostBasisMethod::Fifo | CostBasisMethod::AverageCost => {}
+            CostBasisMethod::Lifo => order.reverse(),
+            CostBasisMethod::Hifo => order.sort_by(|a, b| {
+                self.open_lots[*b]
+                    .cost_per_share()
+                    .cmp(&self.open_lots[*a].cost_per_share())
+            }),
+        }
+        order
+    }
+
+    fn dispose(
+        &mut self,
+        index: usize,
+        shares: Decimal,
+        proceeds_per_share: Decimal,
+        disposed_at: DateTime<Utc>,
+        tx_hash: &str,
+    ) {
+        if shares <= Decimal::ZERO {
+            return;
+        }
+
+        let lot = &mut self.open_lots[index];
+        // Selling the whole lot releases its full cost to avoid rounding leftovers
+        let cost_basis = if shares >= lot.shares {
+            lot.cost_basis
+        } else {
+            shares * lot.cost_per_share()
+        };
+        let proceeds = shares * proceeds_per_share;
+
+        lot.shares -= shares;
+        lot.cost_basis -= cost_basis;
+
+        self.realized_lots.push(RealizedLot {
+            acquired_at: lot.acquired_at,
+            disposed_at,
+            acquisition_tx_hash: lot.tx_hash.clone(),
+            disposal_tx_hash: tx_hash.to_string(),
+            shares,
+            cost_basis,
+            proceeds,
+            realized_gain: proceeds - cost_basis,
+            holding_period_days: (disposed_at - lot.acquired_at).num_days(),
+        });
+    }
+
+    pub const fn method(&self) -> CostBasisMethod {
+        self.method
+    }
+
+    pub fn open_lots(&self) -> &[TaxLot] {
+        &self.open_lots
+    }
+
+    pub fn realized_lots(&self) -> &[RealizedLot] {
+        &self.realized_lots
+    }
+
+    pub fn into_realized_lots(self) -> Vec<RealizedLot> {
+        self.realized_lots
+    }
+
+    pub fn share_balance(&self) -> Decimal {
+        self.open_lots.iter().map(|lot| lot.shares).sum()
+    }
+
+    /// Cost basis of the shares still held
+    pub fn cost_basis(&self) -> Decimal {
+        self.open_lots.iter().map(|lot| lot.cost_basis).sum()
+    }
+
+    pub fn realized_pnl(&self) -> Decimal {
+        self.realized_lots.iter().map(|lot| lot.realized_gain).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{Duration, TimeZone};
+    use rust_decimal::dec;
+
+    use super::*;
+
+    /// Lots of 10 shares bought at 10, 30 then 20 per share, 10 shares sold for 250
+    fn ledger_after_sale(method: CostBasisMethod) -> LotLedger {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut ledger = LotLedger::new(method);
+        ledger.deposit(start, "0x1", dec!(10), dec!(100)).unwrap();
+        ledger
+            .deposit(start + Duration::days(10), "0x2", dec!(10), dec!(300))
+            .unwrap();
+        ledger
+            .deposit(start + Duration::days(20), "0x3", dec!(10), dec!(200))
+            .unwrap();
+        ledger
+            .withdraw(start + Duration::days(400), "0x4", dec!(10), dec!(250))
+            .unwrap();
+        ledger
+    }
+
+    #[test]
+    fn test_fifo() {
+        let ledger = ledger_after_sale(CostBasisMethod::Fifo);
+        assert_eq!(ledger.realized_pnl(), dec!(150));
+        assert_eq!(ledger.cost_basis(), dec!(500));
+        assert_eq!(ledger.realized_lots()[0].acquisition_tx_hash, "0x1");
+        assert!(ledger.realized_lots()[0].is_long_term());
+    }
+
+    #[test]
+    fn test_lifo() {
+        let ledger = ledger_after_sale(CostBasisMethod::Lifo);
+        assert_eq!(ledger.realized_pnl(), dec!(50));
+        assert_eq!(ledger.cost_basis(), dec!(400));
+        assert_eq!(ledger.realized_lots()[0].acquisition_tx_hash, "0x3");
+        assert_eq!(ledger.realized_lots()[0].holding_period_days, 380);
+    }
+
+    #[test]
+    fn test_hifo() {
+        let ledger = ledger_after_sale(CostBasisMethod::Hifo);
+        assert_eq!(ledger.realized_pnl(), dec!(-50));
+        assert_eq!(ledger.cost_basis(), dec!(300));
+        assert_eq!(ledger.realized_lots()[0].acquisition_tx_hash, "0x2");
+    }
+
+    #[test]
+    fn test_average_cost() {
+        let ledger = ledger_after_sale(CostBasisMethod::AverageCost);
+        // A third of every lot is sold at the average cost of 20 per share
+        assert_eq!(ledger.realized_lots().len(), 3);
+        assert_eq!(ledger.realized_pnl().round_dp(10), dec!(50));
+        assert_eq!(ledger.cost_basis().round_dp(10), dec!(400));
+        assert_eq!(ledger.share_balance().round_dp(10), dec!(20));
+    }
+
+    #[test]
+    fn test_sale_across_lots() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let mut ledger = LotLedger::new(CostBasisMethod::Fifo);
+        ledger.deposit(start, "0x1", dec!(10), dec!(100)).unwrap();
+        ledger.deposit(start, "0x2", dec!(10), dec!(200)).unwrap();
+        ledger.withdraw(start, "0x3", dec!(15), dec!(300)).unwrap();
+
+        assert_eq!(ledger.realized_lots().len(), 2);
+        assert_eq!(ledger.realized_pnl(), dec!(100));
+        assert_eq!(ledger.open_lots().len(), 1);
+        assert_eq!(ledger.cost_basis(), dec!(100));
+        assert!(ledger.withdraw(start, "0x4", dec!(6), dec!(100)).is_err());
+    }
+}
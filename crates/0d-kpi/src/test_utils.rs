@@ -0,0 +1,25 @@
+//! Series fixtures shared by the metric tests
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rust_decimal::Decimal;
+
+/// Timestamp of the first point of the fixture series
+pub(crate) fn start() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap()
+}
+
+/// Series of one value per day
+pub(crate) fn daily(values: impl IntoIterator<Item = Decimal>) -> Vec<(DateTime<Utc>, Decimal)> {
+    (0..)
+        .zip(values)
+        .map(|(day, value)| (start() + Duration::days(day), value))
+        .collect()
+}
+
+/// Series of values at the given number of days after the start
+pub(crate) fn at_days(points: &[(i64, Decimal)]) -> Vec<(DateTime<Utc>, Decimal)> {
+    points
+        .iter()
+        .map(|(day, value)| (start() + Duration::days(*day), *value))
+        .collect()
+}
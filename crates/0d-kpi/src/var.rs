@@ -0,0 +1,168 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, MathematicalOps, dec, prelude::ToPrimitive};
+
+use crate::annualization::period_returns;
+use crate::error::KpiError;
+
+/// Confidence level used for the stored value-at-risk figures
+pub const DEFAULT_VAR_CONFIDENCE: Decimal = dec!(0.95);
+
+/// Number of most recent period returns the historical value-at-risk and expected shortfall
+/// are computed over
+pub const HISTORICAL_VAR_WINDOW: usize = 365;
+
+/// One-sided standard normal quantile for the supported confidence levels
+fn z_score(confidence: Decimal) -> Result<Decimal, KpiError> {
+    match confidence {
+        c if c == dec!(0.90) => Ok(dec!(1.2815516)),
+        c if c == dec!(0.95) => Ok(dec!(1.6448536)),
+        c if c == dec!(0.975) => Ok(dec!(1.9599640)),
+        c if c == dec!(0.99) => Ok(dec!(2.3263479)),
+        c => Err(KpiError::InvalidData(format!(
+            "Unsupported confidence level for parametric VaR: {c}"
+        ))),
+    }
+}
+
+/// Period returns of the series
+fn returns_of(portfolio_history: &[(DateTime<Utc>, Decimal)]) -> Result<Vec<Decimal>, KpiError> {
+    Ok(period_returns(portfolio_history)?
+        .into_iter()
+        .map(|r| r.return_rate)
+        .collect())
+}
+
+/// Most recent period returns of the series, within the historical value-at-risk window
+fn recent_returns_of(
+    portfolio_history: &[(DateTime<Utc>, Decimal)],
+) -> Result<Vec<Decimal>, KpiError> {
+    let mut returns = returns_of(portfolio_history)?;
+    returns.drain(..returns.len().saturating_sub(HISTORICAL_VAR_WINDOW));
+    Ok(returns)
+}
+
+/// Returns in the tail beyond the confidence level (at least one), sorted from worst to best
+fn tail_returns(returns: &[Decimal], confidence: Decimal) -> Vec<Decimal> {
+    let mut sorted = returns.to_vec();
+    sorted.sort_unstable();
+
+    let tail = (Decimal::ONE - confidence) * Decimal::from(sorted.len());
+    let tail_size = tail.floor().to_usize().map_or(1, |n| n.max(1));
+    sorted.truncate(tail_size);
+    sorted
+}
+
+/// Historical value-at-risk of a set of period returns, as a positive loss percentage
+pub(crate) fn historical_var_of_returns(returns: &[Decimal], confidence: Decimal) -> Decimal {
+    tail_returns(returns, confidence)
+        .last()
+        .map_or(Decimal::ZERO, |cutoff| {
+            (-*cutoff).max(Decimal::ZERO) * dec!(100)
+        })
+}
+
+/// Expected shortfall of a set of period returns, as a positive loss percentage
+pub(crate) fn cvar_of_returns(returns: &[Decimal], confidence: Decimal) -> Decimal {
+    let tail = tail_returns(returns, confidence);
+    if tail.is_empty() {
+        return Decimal::ZERO;
+    }
+
+    let mean_tail = tail.iter().sum::<Decimal>() / Decimal::from(tail.len());
+    (-mean_tail).max(Decimal::ZERO) * dec!(100)
+}
+
+/// Parametric value-at-risk from the mean and (population) variance of period returns
+pub(crate) fn parametric_var_of_moments(
+    mean: Decimal,
+    variance: Decimal,
+    confidence: Decimal,
+) -> Result<Decimal, KpiError> {
+    let z = z_score(confidence)?;
+    let std_dev = if variance > Decimal::ZERO {
+        variance.sqrt().ok_or_else(|| {
+            KpiError::CalculationError("Failed to compute standard deviation".to_string())
+        })?
+    } else {
+        Decimal::ZERO
+    };
+
+    Ok((z * std_dev - mean).max(Decimal::ZERO) * dec!(100))
+}
+
+/// Historical Value-at-Risk over the last `HISTORICAL_VAR_WINDOW` returns, expressed as a
+/// positive loss percentage over one sampling period
+// NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
+pub fn calculate_historical_var(
+    portfolio_history: &[(DateTime<Utc>, Decimal)],
+    confidence: Decimal,
+) -> Result<Decimal, KpiError> {
+    Ok(historical_var_of_returns(
+        &recent_returns_of(portfolio_history)?,
+        confidence,
+    ))
+}
+
+/// Parametric (variance-covariance) Value-at-Risk assuming normally distributed returns,
+/// expressed as a positive loss percentage over one sampling period
+// NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
+pub fn calculate_parametric_var(
+    portfolio_history: &[(DateTime<Utc>, Decimal)],
+    confidence: Decimal,
+) -> Result<Decimal, KpiError> {
+    let returns = returns_of(portfolio_history)?;
+    if returns.is_empty() {
+        return Ok(Decimal::ZERO);
+    }
+
+    let count_dec = Decimal::from(returns.len());
+    let mean = returns.iter().sum::<Decimal>() / count_dec;
+    let variance = returns.iter().map(|r| (*r - mean).powu(2)).sum::<Decimal>() / count_dec;
+
+    parametric_var_of_moments(mean, variance, confidence)
+}
+
+/// Conditional Value-at-Risk (expected shortfall): the average loss in the tail beyond
+/// the historical value-at-risk, over the same window, expressed as a positive percentage
+/// over one sampling period
+// NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
+pub fn calculate_cvar(
+    portfolio_history: &[(DateTime<Utc>, Decimal)],
+    confidence: Decimal,
+) -> Result<Decimal, KpiError> {
+    Ok(cvar_of_returns(
+        &recent_returns_of(portfolio_history)?,
+        confidence,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::daily;
+
+    #[test]
+    fn test_historical_var_and_cvar() {
+        let mut returns = vec![dec!(-0.05), dec!(-0.03)];
+        returns.extend(std::iter::repeat_n(dec!(0.01), 18));
+
+        // 5% of 20 returns leaves the worst one in the tail, 10% the two worst
+        assert_eq!(historical_var_of_returns(&returns, dec!(0.95)), dec!(5));
+        assert_eq!(cvar_of_returns(&returns, dec!(0.90)), dec!(4));
+        assert_eq!(historical_var_of_returns(&[], dec!(0.95)), Decimal::ZERO);
+        assert_eq!(
+            historical_var_of_returns(&[dec!(0.01), dec!(0.02)], dec!(0.95)),
+            Decimal::ZERO
+        );
+
+        let var = calculate_historical_var(&daily([dec!(100), dec!(95), dec!(100)]), dec!(0.95));
+        assert_eq!(var.unwrap(), dec!(5));
+    }
+
+    #[test]
+    fn test_parametric_var() {
+        let var = parametric_var_of_moments(Decimal::ZERO, dec!(0.0001), dec!(0.95)).unwrap();
+        assert_eq!(var, dec!(0.16448536) * dec!(10));
+        assert!(parametric_var_of_moments(Decimal::ZERO, dec!(0.0001), dec!(0.8)).is_err());
+    }
+}
@@ -0,0 +1,58 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, MathematicalOps, dec};
+
+use crate::annualization::{period_returns, periods_per_year};
+use crate::error::KpiError;
+
+/// Annualized volatility of period returns, expressed as a percentage
+// NOTE: portfolio_history must be pre-sorted in chronological order (oldest first)
+pub fn calculate_annualized_volatility(
+    portfolio_history: &[(DateTime<Utc>, Decimal)],
+) -> Result<Decimal, KpiError> {
+    let returns = period_returns(portfolio_history)?;
+    if returns.len() < 2 {
+        return Ok(Decimal::ZERO);
+    }
+
+    let count_dec = Decimal::from(returns.len());
+    let mean = returns.iter().map(|r| r.return_rate).sum::<Decimal>() / count_dec;
+    // Sample variance (Bessel's correction)
+    let variance = returns
+        .iter()
+        .map(|r| (r.return_rate - mean).powu(2))
+        .sum::<Decimal>()
+        / (count_dec - Decimal::ONE);
+
+    if variance <= Decimal::ZERO {
+        return Ok(Decimal::ZERO);
+    }
+
+    let std_dev = variance.sqrt().ok_or_else(|| {
+        KpiError::CalculationError("Failed to compute standard deviation".to_string())
+    })?;
+    let annualization = periods_per_year(&returns).sqrt().ok_or_else(|| {
+        KpiError::CalculationError("Failed to compute annualization factor".to_string())
+    })?;
+
+    Ok(std_dev * annualization * dec!(100))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_utils::daily;
+
+    #[test]
+    fn test_annualized_volatility() {
+        // Daily returns of +10% and -10%: sample deviation of sqrt(0.02), annualized over 365 days
+        let volatility =
+            calculate_annualized_volatility(&daily([dec!(100), dec!(110), dec!(99)])).unwrap();
+        assert_eq!(volatility.round_dp(2), dec!(270.19));
+
+        let steady = daily([dec!(100), dec!(110), dec!(121)]);
+        assert_eq!(
+            calculate_annualized_volatility(&steady).unwrap(),
+            Decimal::ZERO
+        );
+    }
+}
@@ -115,6 +115,16 @@ impl VaultMasterClient for JaffarClient {
         Ok(response.into_inner().into())
     }
 
+    async fn get_vault_share_price_series(
+        &self,
+        _timeframe: &str,
+    ) -> Result<TimeseriesResponseDTO, MasterApiError> {
+        // Not implemented in SDK - return error
+        Err(MasterApiError::NotImplemented(
+            "Share price series endpoint not available in Jaffar SDK".to_string(),
+        ))
+    }
+
     async fn get_vault_liquidity(&self) -> Result<LiquidityDTO, MasterApiError> {
         // Not implemented in SDK - return error
         Err(MasterApiError::NotImplemented(
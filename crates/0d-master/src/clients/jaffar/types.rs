@@ -61,6 +61,7 @@ impl From<jaffar_sdk::types::GetStatsResponse> for GetStatsDTO {
             tvl_usd: stats.tvl_as_usd,
             past_month_apr_pct: stats.past_month_apr_pct,
             projected_apr_pct: 16.2,
+            flows: None,
         }
     }
 }
@@ -220,6 +220,15 @@ impl VaultMasterClient for VesuClient {
         .into())
     }
 
+    async fn get_vault_share_price_series(
+        &self,
+        _timeframe: &str,
+    ) -> Result<TimeseriesResponseDTO, MasterApiError> {
+        Err(MasterApiError::AnyhowError(anyhow!(
+            "Share price series not supported by Vesu API"
+        )))
+    }
+
     async fn get_vault_liquidity(&self) -> Result<LiquidityDTO, MasterApiError> {
         Err(MasterApiError::AnyhowError(anyhow!(
             "Liquidity not supported by Vesu API"
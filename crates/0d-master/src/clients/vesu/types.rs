@@ -64,6 +64,7 @@ impl From<&vesu_sdk::types::VaultsControllerGetVaultsResponseItem> for GetStatsD
             tvl_usd,
             past_month_apr_pct: apr_pct,
             projected_apr_pct: 6.0,
+            flows: None,
         }
     }
 }
@@ -48,6 +48,22 @@ pub struct GetStatsDTO {
     pub tvl_usd: String,
     pub past_month_apr_pct: f64,
     pub projected_apr_pct: f64,
+    /// Deposit and redemption activity, computed from the indexed transactions when the
+    /// backend doesn't provide it
+    #[serde(default)]
+    pub flows: Option<FlowStatsDTO>,
+}
+
+/// Deposits and redemptions of a vault over a period, in its underlying currency
+#[derive(Debug, Clone, Deserialize, Serialize, ToSchema)]
+pub struct FlowStatsDTO {
+    pub timeframe: Timeframe,
+    pub deposits: String,
+    pub redemptions: String,
+    pub net_flows: String,
+    pub unique_depositors: i64,
+    pub new_users: i64,
+    pub returning_users: i64,
 }
 
 #[derive(Debug, Deserialize, Serialize, ToSchema)]
@@ -38,6 +38,12 @@ pub trait VaultMasterClient: Send + Sync {
         currency: &str,
     ) -> Result<TimeseriesResponseDTO, MasterApiError>;
 
+    /// Share price of the vault over a timeframe, in USD
+    async fn get_vault_share_price_series(
+        &self,
+        timeframe: &str,
+    ) -> Result<TimeseriesResponseDTO, MasterApiError>;
+
     async fn get_vault_liquidity(&self) -> Result<LiquidityDTO, MasterApiError>;
 
     async fn get_vault_slippage_curve(&self) -> Result<SlippageCurveDTO, MasterApiError>;
@@ -1,51 +1,331 @@
 use std::{
     str::FromStr,
-    sync::{Arc, LazyLock},
+    sync::{Arc, LazyLock, OnceLock},
     time::Duration,
 };
 
 use anyhow::Result;
+use chrono::{DateTime, Utc};
+use dashmap::DashMap;
+use deadpool_diesel::postgres::Pool;
 use moka::future::Cache;
+use pragma_common::starknet::FallbackProvider;
 use rust_decimal::Decimal;
+use serde::Serialize;
+use utoipa::ToSchema;
 
-use crate::pyth::fetch_pyth_price;
+use zerod_db::{ZerodPool, models::CurrencyPrice};
+
+use crate::{pragma::fetch_pragma_price, pyth::fetch_pyth_price, validation::PriceRejection};
 
 pub use zerod_types::Currency;
 
 pub static CURRENCIES_PRICES: LazyLock<Arc<CurrenciesPrices>> =
     LazyLock::new(|| Arc::new(CurrenciesPrices::new()));
 
+/// Maximum age of a last known good price before it is no longer served
+const MAX_STALENESS: chrono::Duration = chrono::Duration::hours(1);
+
+/// Where a quoted price comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum PriceSource {
+    /// Constant price (USD is always 1)
+    Fixed,
+    Pyth,
+    PragmaOracle,
+    /// Historical price from the Pyth benchmarks API
+    PythBenchmarks,
+    /// Last successful quote, served while every live source is down
+    LastKnownGood,
+}
+
+impl PriceSource {
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Fixed => "fixed",
+            Self::Pyth => "pyth",
+            Self::PragmaOracle => "pragma_oracle",
+            Self::PythBenchmarks => "pyth_benchmarks",
+            Self::LastKnownGood => "last_known_good",
+        }
+    }
+}
+
+/// A USD price along with its source and publication time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceQuote {
+    pub price: Decimal,
+    /// Confidence interval around the price, when the source publishes one
+    pub confidence: Option<Decimal>,
+    pub source: PriceSource,
+    pub published_at: DateTime<Utc>,
+}
+
 /// Cached currency prices with 10-second TTL
-#[derive(Debug)]
-pub struct CurrenciesPrices(Cache<Currency, Decimal>);
+pub struct CurrenciesPrices {
+    cache: Cache<Currency, PriceQuote>,
+    last_known_good: DashMap<Currency, PriceQuote>,
+    starknet_provider: OnceLock<FallbackProvider>,
+    db_pool: OnceLock<Pool>,
+}
+
+/// Price samples of a currency over a period, used to convert a whole series at once
+#[derive(Debug, Clone)]
+pub struct PriceHistory {
+    currency: Currency,
+    /// Samples sorted by time
+    samples: Vec<(DateTime<Utc>, Decimal)>,
+}
+
+impl PriceHistory {
+    /// Price at `at`, interpolated between the surrounding samples.
+    /// Points outside of the samples carry the nearest one, `None` if there is no sample.
+    pub fn price_at(&self, at: DateTime<Utc>) -> Option<Decimal> {
+        if self.currency == Currency::USD {
+            return Some(Decimal::ONE);
+        }
+
+        let idx = self
+            .samples
+            .partition_point(|(sampled_at, _)| *sampled_at <= at);
+        let Some(&(before_at, before)) = idx.checked_sub(1).and_then(|i| self.samples.get(i))
+        else {
+            return self.samples.first().map(|(_, price)| *price);
+        };
+        let Some(&(after_at, after)) = self.samples.get(idx) else {
+            return Some(before);
+        };
+
+        let span = (after_at - before_at).num_milliseconds();
+        if span == 0 {
+            return Some(before);
+        }
+        let elapsed = Decimal::from((at - before_at).num_milliseconds());
+        Some(before + (after - before) * elapsed / Decimal::from(span))
+    }
+}
 
 impl CurrenciesPrices {
     pub fn new() -> Self {
         const CACHE_DURATION: Duration = Duration::from_secs(10);
 
-        Self(Cache::builder().time_to_live(CACHE_DURATION).build())
+        Self {
+            cache: Cache::builder().time_to_live(CACHE_DURATION).build(),
+            last_known_good: DashMap::new(),
+            starknet_provider: OnceLock::new(),
+            db_pool: OnceLock::new(),
+        }
+    }
+
+    /// Enable the Pragma oracle fallback. Only the first provider set is kept.
+    pub fn set_starknet_provider(&self, provider: FallbackProvider) {
+        if self.starknet_provider.set(provider).is_err() {
+            tracing::warn!("Starknet provider of the price fallback is already set");
+        }
+    }
+
+    /// Enable the persisted price history. Only the first pool set is kept.
+    pub fn set_db_pool(&self, pool: Pool) {
+        if self.db_pool.set(pool).is_err() {
+            tracing::warn!("Database pool of the price history is already set");
+        }
     }
 
     /// Try to fetch the price of the given Currency. Will be always quoted in USD.
-    /// The primary source is Pyth (with caching), but if it fails for any reason we will instead
-    /// use the Starknet L2 price.
+    /// See `quote`.
     pub async fn of(&self, currency: Currency) -> Result<Decimal> {
+        Ok(self.quote(currency).await?.price)
+    }
+
+    /// Quote the given Currency in USD. Sources are tried in order:
+    /// Pyth Hermes, the Pragma oracle on Starknet, then the last known good price
+    /// as long as it was published less than `MAX_STALENESS` ago.
+    pub async fn quote(&self, currency: Currency) -> Result<PriceQuote> {
         if matches!(currency, Currency::USD) {
-            return Ok(Decimal::ONE);
+            return Ok(PriceQuote {
+                price: Decimal::ONE,
+                confidence: None,
+                source: PriceSource::Fixed,
+                published_at: Utc::now(),
+            });
         }
 
         // Try to get from cache first
-        if let Some(cached_price) = self.0.get(&currency).await {
-            return Ok(cached_price);
+        if let Some(cached_quote) = self.cache.get(&currency).await {
+            return Ok(cached_quote);
+        }
+
+        match self.fetch_live_quote(currency).await {
+            Ok(quote) => {
+                self.publish(currency, quote).await;
+                Ok(quote)
+            }
+            Err(e) => {
+                let last = self.last_known_good.get(&currency).map(|quote| *quote);
+                match last {
+                    Some(quote) if Utc::now() - quote.published_at <= MAX_STALENESS => {
+                        tracing::warn!(
+                            "Serving last known good price of {currency} published at {}: {e:#}",
+                            quote.published_at
+                        );
+                        Ok(PriceQuote {
+                            source: PriceSource::LastKnownGood,
+                            ..quote
+                        })
+                    }
+                    _ => Err(e.context(format!("Failed to fetch price for {currency}"))),
+                }
+            }
+        }
+    }
+
+    /// Make a freshly fetched quote available to `quote` callers, e.g. from the price service
+    pub async fn publish(&self, currency: Currency, quote: PriceQuote) {
+        self.cache.insert(currency, quote).await;
+        self.last_known_good.insert(currency, quote);
+    }
+
+    async fn fetch_live_quote(&self, currency: Currency) -> Result<PriceQuote> {
+        let pyth_error = match fetch_pyth_price(currency).await {
+            Ok(quote) => return Ok(quote),
+            Err(e) => e,
+        };
+
+        let Some(provider) = self.starknet_provider.get() else {
+            return Err(pyth_error);
+        };
+        if pyth_error.is::<PriceRejection>() {
+            tracing::warn!("Falling back to Pragma, {pyth_error}");
+        } else {
+            tracing::warn!(
+                "Pyth price of {currency} unavailable, falling back to Pragma: {pyth_error}"
+            );
         }
 
-        // Not in cache, try to fetch from Pyth
-        if let Ok(pyth_price) = fetch_pyth_price(currency).await {
-            self.0.insert(currency, pyth_price).await;
-            return Ok(pyth_price);
+        // Keep the Pyth error as the source so a rejection can still be told apart
+        fetch_pragma_price(provider, currency)
+            .await
+            .map_err(|e| pyth_error.context(format!("Pragma fallback failed: {e}")))
+    }
+
+    /// Load the price samples of a currency covering `[from, to]`
+    pub async fn history(
+        &self,
+        currency: Currency,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<PriceHistory> {
+        let mut history = PriceHistory {
+            currency,
+            samples: Vec::new(),
+        };
+        let Some(pool) = self.db_pool.get() else {
+            return Ok(history);
+        };
+        if currency == Currency::USD {
+            return Ok(history);
         }
 
-        Err(anyhow::anyhow!("Failed to fetch price for {:?}", currency))
+        let samples = pool
+            .interact_with_context(
+                format!("fetch price history of {currency} between {from} and {to}"),
+                move |conn| {
+                    // The samples around the period cover its first and last points
+                    let mut samples: Vec<CurrencyPrice> =
+                        CurrencyPrice::find_at_or_before(currency.as_ref(), from, conn)?
+                            .into_iter()
+                            .collect();
+                    samples.extend(CurrencyPrice::find_between(
+                        currency.as_ref(),
+                        from,
+                        to,
+                        conn,
+                    )?);
+                    samples.extend(CurrencyPrice::find_after(currency.as_ref(), to, conn)?);
+                    Ok::<_, diesel::result::Error>(samples)
+                },
+            )
+            .await?;
+
+        history.samples = samples
+            .into_iter()
+            .map(|sample| (sample.sampled_at, sample.price))
+            .collect();
+        history.samples.dedup_by_key(|(sampled_at, _)| *sampled_at);
+        Ok(history)
+    }
+
+    /// Convert each `(timestamp, amount)` point at the rate in effect at its own timestamp.
+    /// Rates come from the stored history only, the price service backfills missing days.
+    pub async fn convert_series(
+        &self,
+        points: &[(DateTime<Utc>, Decimal)],
+        from: Currency,
+        to: Currency,
+    ) -> Result<Vec<Decimal>> {
+        if from == to {
+            return Ok(points.iter().map(|(_, amount)| *amount).collect());
+        }
+        let (Some(start), Some(end)) = (
+            points.iter().map(|(t, _)| *t).min(),
+            points.iter().map(|(t, _)| *t).max(),
+        ) else {
+            return Ok(Vec::new());
+        };
+
+        let (from_history, to_history) = tokio::try_join!(
+            self.stored_history(from, start, end),
+            self.stored_history(to, start, end)
+        )?;
+
+        let mut converted = Vec::with_capacity(points.len());
+        for &(at, amount) in points {
+            let (Some(from_price), Some(to_price)) =
+                (from_history.price_at(at), to_history.price_at(at))
+            else {
+                return Err(anyhow::anyhow!("No price of {from} or {to} at {at}"));
+            };
+            if to_price.is_zero() {
+                return Err(anyhow::anyhow!("Price of {to} is zero at {at}"));
+            }
+            converted.push(amount * from_price / to_price);
+        }
+
+        Ok(converted)
+    }
+
+    /// Same as `history`, failing when nothing is stored yet rather than mixing in spot prices
+    async fn stored_history(
+        &self,
+        currency: Currency,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<PriceHistory> {
+        let history = self.history(currency, from, to).await?;
+        if history.samples.is_empty() && currency != Currency::USD {
+            return Err(anyhow::anyhow!("No stored price of {currency}"));
+        }
+        Ok(history)
+    }
+
+    /// Price of `base` expressed in `quote` (e.g. ETH in EUR), crossed through USD
+    pub async fn rate(&self, base: Currency, quote: Currency) -> Result<Decimal> {
+        if base == quote {
+            return Ok(Decimal::ONE);
+        }
+
+        let (base_price, quote_price) = tokio::try_join!(self.of(base), self.of(quote))?;
+        if quote_price.is_zero() {
+            return Err(anyhow::anyhow!("Price of {quote} is zero"));
+        }
+
+        Ok(base_price / quote_price)
+    }
+
+    /// Convert an amount from one currency to another
+    pub async fn convert(&self, amount: Decimal, from: Currency, to: Currency) -> Result<Decimal> {
+        Ok(amount * self.rate(from, to).await?)
     }
 
     /// See `of`.
@@ -57,8 +337,53 @@ impl CurrenciesPrices {
     }
 }
 
+impl std::fmt::Debug for CurrenciesPrices {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CurrenciesPrices")
+            .field("cache", &self.cache)
+            .field("last_known_good", &self.last_known_good)
+            .finish_non_exhaustive()
+    }
+}
+
 impl Default for CurrenciesPrices {
     fn default() -> Self {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use rust_decimal::dec;
+
+    use super::*;
+
+    fn at(hour: u32) -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, hour, 0, 0).unwrap()
+    }
+
+    #[test]
+    fn test_price_history_interpolates_between_samples() {
+        let history = PriceHistory {
+            currency: Currency::ETH,
+            samples: vec![(at(2), dec!(100)), (at(6), dec!(200))],
+        };
+
+        assert_eq!(history.price_at(at(0)), Some(dec!(100)));
+        assert_eq!(history.price_at(at(2)), Some(dec!(100)));
+        assert_eq!(history.price_at(at(3)), Some(dec!(125)));
+        assert_eq!(history.price_at(at(6)), Some(dec!(200)));
+        assert_eq!(history.price_at(at(9)), Some(dec!(200)));
+    }
+
+    #[test]
+    fn test_price_history_without_samples() {
+        let history = PriceHistory {
+            currency: Currency::ETH,
+            samples: Vec::new(),
+        };
+
+        assert_eq!(history.price_at(at(0)), None);
+    }
+}
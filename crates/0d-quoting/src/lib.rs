@@ -1,2 +1,7 @@
 pub mod currencies;
+pub mod pragma;
 pub mod pyth;
+pub mod service;
+pub mod validation;
+
+pub use service::PriceService;
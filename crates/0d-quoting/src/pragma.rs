@@ -0,0 +1,51 @@
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use evian::contracts::starknet::pragma_oracle::{PragmaOracleContract, data::DataType};
+use pragma_common::starknet::FallbackProvider;
+use rust_decimal::Decimal;
+
+use crate::currencies::{Currency, PriceQuote, PriceSource};
+
+/// Oldest Pragma median accepted, in seconds
+const MAX_PRICE_AGE_SECS: i64 = 600;
+
+/// Fetch the median spot price of a currency from the Pragma oracle on Starknet
+pub async fn fetch_pragma_price(
+    provider: &FallbackProvider,
+    currency: Currency,
+) -> anyhow::Result<PriceQuote> {
+    let pair_id = currency
+        .info()
+        .pragma_pair_id
+        .ok_or_else(|| anyhow!("No Pragma pair configured for {currency}"))?;
+
+    let oracle = PragmaOracleContract::new(provider.clone());
+    let response = oracle
+        .get_data_median(DataType::SpotEntry(pair_id.to_string()), None)
+        .await
+        .map_err(|e| anyhow!("Pragma oracle call failed for {pair_id}: {e}"))?;
+    if response.num_sources_aggregated == 0 {
+        return Err(anyhow!("No sources aggregated for {pair_id}"));
+    }
+
+    let price =
+        Decimal::try_from_i128_with_scale(i128::try_from(response.price)?, response.decimals)
+            .map_err(|e| anyhow!("Invalid Pragma price for {pair_id}: {e}"))?;
+    let published_at =
+        DateTime::<Utc>::from_timestamp(i64::try_from(response.last_updated_timestamp)?, 0)
+            .ok_or_else(|| anyhow!("Invalid update timestamp for {pair_id}"))?;
+
+    let age = Utc::now() - published_at;
+    if age.num_seconds() > MAX_PRICE_AGE_SECS {
+        return Err(anyhow!(
+            "Pragma price of {currency} is stale: updated at {published_at}, max age {MAX_PRICE_AGE_SECS}s"
+        ));
+    }
+
+    Ok(PriceQuote {
+        price,
+        confidence: None,
+        source: PriceSource::PragmaOracle,
+        published_at,
+    })
+}
@@ -1,8 +1,14 @@
+use std::sync::LazyLock;
+
 use anyhow::anyhow;
+use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
 use serde::Deserialize;
 
-use crate::currencies::Currency;
+use crate::{
+    currencies::{Currency, PriceQuote, PriceSource},
+    validation::PriceValidation,
+};
 
 // Pyth response structures
 #[derive(Debug, Deserialize)]
@@ -19,43 +25,223 @@ struct PriceFeed {
 #[derive(Debug, Deserialize)]
 struct PriceData {
     price: String,
+    conf: String,
     expo: i32,
+    publish_time: i64,
 }
 
-/// Fetch the current price from Pyth for a given currency
-pub async fn fetch_pyth_price(currency: Currency) -> anyhow::Result<Decimal> {
-    if currency == Currency::USD {
-        return Ok(Decimal::ONE);
+const HERMES_URL: &str = "https://hermes.pyth.network/v2/updates/price/latest";
+const BENCHMARKS_URL: &str = "https://benchmarks.pyth.network/v1/updates/price";
+
+/// Client shared by the one-off Pyth calls and the price service
+pub static PYTH_CLIENT: LazyLock<PythClient> =
+    LazyLock::new(|| PythClient::new(PriceValidation::from_env()));
+
+/// Pyth Hermes and benchmarks client, reusing a single HTTP connection pool
+#[derive(Debug, Clone)]
+pub struct PythClient {
+    http: reqwest::Client,
+    validation: PriceValidation,
+}
+
+impl PythClient {
+    pub fn new(validation: PriceValidation) -> Self {
+        Self {
+            http: reqwest::Client::new(),
+            validation,
+        }
     }
 
-    let feed_id = get_feed_id(currency);
+    /// Fetch the latest prices of several currencies in a single Hermes request.
+    /// Each price is validated on its own, so one bad feed does not fail the batch.
+    /// Rejected prices are reported as a [`PriceRejection`](crate::validation::PriceRejection).
+    pub async fn latest_prices(
+        &self,
+        currencies: &[Currency],
+    ) -> anyhow::Result<Vec<(Currency, anyhow::Result<PriceQuote>)>> {
+        let feeds = feeds_of(currencies);
+        if feeds.is_empty() {
+            return Ok(Vec::new());
+        }
 
-    let url = format!("https://hermes.pyth.network/v2/updates/price/latest?ids[]={feed_id}");
+        let ids = feeds
+            .iter()
+            .map(|(_, feed_id)| format!("ids[]={feed_id}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        let url = format!("{HERMES_URL}?{ids}");
+
+        let pyth_response = self.get(&url).await?;
+        let now = Utc::now();
+
+        Ok(feeds
+            .into_iter()
+            .map(|(currency, feed_id)| {
+                let quote = self.validated_quote(currency, pyth_response.find(feed_id), now);
+                (currency, quote)
+            })
+            .collect())
+    }
+
+    /// Parse and validate the live price feed of a currency
+    fn validated_quote(
+        &self,
+        currency: Currency,
+        feed: Option<&PriceFeed>,
+        now: DateTime<Utc>,
+    ) -> anyhow::Result<PriceQuote> {
+        let feed = feed.ok_or_else(|| anyhow!("No price feed found for {currency}"))?;
+        let quote = parse_price_feed(feed, PriceSource::Pyth)?;
+        self.validation.check(currency, &quote, now)?;
+        Ok(quote)
+    }
 
-    let client = reqwest::Client::new();
-    let response = client.get(&url).send().await?.error_for_status()?;
+    /// Fetch the historical prices of several currencies at a given time from the Pyth
+    /// benchmarks API. Currencies without a feed are skipped.
+    pub async fn prices_at(
+        &self,
+        currencies: &[Currency],
+        at: DateTime<Utc>,
+    ) -> anyhow::Result<Vec<(Currency, PriceQuote)>> {
+        let feeds = feeds_of(currencies);
+        if feeds.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let ids = feeds
+            .iter()
+            .map(|(_, feed_id)| format!("ids={feed_id}"))
+            .collect::<Vec<_>>()
+            .join("&");
+        let url = format!("{BENCHMARKS_URL}/{}?{ids}&parsed=true", at.timestamp());
+
+        let pyth_response = self.get(&url).await?;
+
+        feeds
+            .into_iter()
+            .filter_map(|(currency, feed_id)| {
+                let price_feed = pyth_response.find(feed_id)?;
+                Some(
+                    parse_price_feed(price_feed, PriceSource::PythBenchmarks)
+                        .map(|q| (currency, q)),
+                )
+            })
+            .collect()
+    }
+
+    async fn get(&self, url: &str) -> anyhow::Result<PythResponse> {
+        let response = self.http.get(url).send().await?.error_for_status()?;
+        Ok(response.json().await?)
+    }
+}
+
+impl PythResponse {
+    fn find(&self, feed_id: &str) -> Option<&PriceFeed> {
+        self.parsed
+            .iter()
+            .find(|feed| feed.id.trim_start_matches("0x") == feed_id.trim_start_matches("0x"))
+    }
+}
 
-    let pyth_response: PythResponse = response.json().await?;
+/// Fetch the current price from Pyth for a given currency
+pub async fn fetch_pyth_price(currency: Currency) -> anyhow::Result<PriceQuote> {
+    let (_, quote) = PYTH_CLIENT
+        .latest_prices(&[currency])
+        .await?
+        .into_iter()
+        .next()
+        .ok_or_else(|| anyhow!("No Pyth feed configured for {currency}"))?;
+    quote
+}
 
-    let price_feed = pyth_response
-        .parsed
+fn feeds_of(currencies: &[Currency]) -> Vec<(Currency, &'static str)> {
+    currencies
         .iter()
-        .find(|feed| feed.id.replace("0x", "") == feed_id.replace("0x", ""))
-        .ok_or_else(|| anyhow!("No price feed found for {:?}", currency))?;
+        .filter_map(|&currency| Some((currency, currency.info().pyth_feed_id?)))
+        .collect()
+}
 
-    let price_int: i64 = price_feed.price.price.parse()?;
+fn parse_price_feed(price_feed: &PriceFeed, source: PriceSource) -> anyhow::Result<PriceQuote> {
     let expo = price_feed.price.expo;
 
-    let divisor = 10_i64.pow(expo.unsigned_abs());
-    let price = Decimal::from(price_int) / Decimal::from(divisor);
+    let price_int: i64 = price_feed.price.price.parse()?;
+    let price = scale_by_exponent(price_int, expo)?;
+    let conf_int: u64 = price_feed.price.conf.parse()?;
+    let confidence = scale_by_exponent(i64::try_from(conf_int)?, expo)?;
+
+    let published_at = DateTime::<Utc>::from_timestamp(price_feed.price.publish_time, 0)
+        .ok_or_else(|| anyhow!("Invalid publish time for feed {}", price_feed.id))?;
 
-    Ok(price)
+    Ok(PriceQuote {
+        price,
+        confidence: Some(confidence),
+        source,
+        published_at,
+    })
 }
 
-/// Get the Pyth feed ID for a given currency (all against USD)
-fn get_feed_id(currency: Currency) -> &'static str {
-    match currency {
-        Currency::USDC => "0xeaa020c61cc479712813461ce153894a96a6c00b21ed0cfc2798d1f9a9e9c94a",
-        Currency::USD => unreachable!("Already handled"),
+/// Scale a Pyth integer by `10^expo`, failing instead of overflowing
+fn scale_by_exponent(value: i64, expo: i32) -> anyhow::Result<Decimal> {
+    if expo <= 0 {
+        return Decimal::try_new(value, expo.unsigned_abs())
+            .map_err(|e| anyhow!("Invalid Pyth exponent {expo}: {e}"));
+    }
+
+    10_i64
+        .checked_pow(expo.unsigned_abs())
+        .and_then(|multiplier| Decimal::from(value).checked_mul(Decimal::from(multiplier)))
+        .ok_or_else(|| anyhow!("Pyth value {value} overflows with exponent {expo}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::validation::PriceRejection;
+
+    use super::*;
+
+    fn feed(publish_time: i64) -> PriceFeed {
+        PriceFeed {
+            id: "0xfeed".to_string(),
+            price: PriceData {
+                price: "300000000000".to_string(),
+                conf: "150000000".to_string(),
+                expo: -8,
+                publish_time,
+            },
+        }
+    }
+
+    #[test]
+    fn test_parse_price_feed() {
+        let quote = parse_price_feed(&feed(1_735_732_800), PriceSource::Pyth).unwrap();
+
+        assert_eq!(quote.price, Decimal::from(3000));
+        assert_eq!(quote.confidence, Some(Decimal::new(15, 1)));
+    }
+
+    #[test]
+    fn test_scale_by_exponent() {
+        assert_eq!(scale_by_exponent(12, 2).unwrap(), Decimal::from(1200));
+        assert!(scale_by_exponent(1, 19).is_err());
+        assert!(scale_by_exponent(1, -29).is_err());
+    }
+
+    #[test]
+    fn test_rejection_survives_the_error_chain() {
+        let client = PythClient::new(PriceValidation::default());
+        let now = DateTime::<Utc>::from_timestamp(1_735_732_800, 0).unwrap();
+
+        let error = client
+            .validated_quote(Currency::ETH, Some(&feed(1_735_732_800 - 3600)), now)
+            .unwrap_err()
+            .context("Failed to fetch price for ETH");
+
+        assert!(error.is::<PriceRejection>());
+        assert!(
+            !client
+                .validated_quote(Currency::ETH, None, now)
+                .unwrap_err()
+                .is::<PriceRejection>()
+        );
     }
 }
@@ -0,0 +1,204 @@
+use std::{collections::HashSet, time::Duration};
+
+use chrono::{DateTime, Days, Utc};
+use deadpool_diesel::postgres::Pool;
+use pragma_common::services::{Service, ServiceRunner};
+use tokio::time::Instant;
+use zerod_db::{
+    ZerodPool,
+    models::{CurrencyPrice, NewCurrencyPrice},
+};
+
+use crate::{
+    currencies::{CURRENCIES_PRICES, Currency, PriceQuote},
+    pyth::{PYTH_CLIENT, PythClient},
+    validation::PriceRejection,
+};
+
+/// Keeps `CURRENCIES_PRICES` warm and records the price history of every supported currency.
+///
+/// All the Pyth feeds are polled in a single Hermes request; valid prices are published to
+/// the shared cache and persisted at a regular interval. Missing daily prices are backfilled
+/// from the Pyth benchmarks API on startup and then once a day, so conversions never have to
+/// fetch them on demand.
+pub struct PriceService {
+    db_pool: Pool,
+    pyth: PythClient,
+}
+
+impl PriceService {
+    const POLL_INTERVAL: Duration = Duration::from_secs(10);
+    const SAMPLE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+    const BACKFILL_DAYS: u64 = 365;
+    const BACKFILL_INTERVAL: Duration = Duration::from_secs(24 * 60 * 60);
+    /// Delay between two benchmarks requests to stay under the API rate limit
+    const BACKFILL_REQUEST_DELAY: Duration = Duration::from_millis(500);
+
+    pub fn new(db_pool: Pool) -> Self {
+        Self {
+            db_pool,
+            pyth: PYTH_CLIENT.clone(),
+        }
+    }
+
+    pub async fn run_forever(&self) -> anyhow::Result<()> {
+        let mut last_backfilled_at: Option<Instant> = None;
+        let mut last_sampled_at: Option<Instant> = None;
+        loop {
+            if last_backfilled_at.is_none_or(|at| at.elapsed() >= Self::BACKFILL_INTERVAL) {
+                if let Err(e) = self.backfill().await {
+                    tracing::error!("[PriceService] 🔴 Error while backfilling prices: {e}");
+                }
+                last_backfilled_at = Some(Instant::now());
+            }
+
+            match self.poll().await {
+                Ok(quotes) => {
+                    let sample_due =
+                        last_sampled_at.is_none_or(|at| at.elapsed() >= Self::SAMPLE_INTERVAL);
+                    if sample_due && !quotes.is_empty() {
+                        match self.persist(quotes).await {
+                            Ok(()) => last_sampled_at = Some(Instant::now()),
+                            Err(e) => {
+                                tracing::error!(
+                                    "[PriceService] 🔴 Error while storing prices: {e}"
+                                );
+                            }
+                        }
+                    }
+                }
+                Err(e) => tracing::error!("[PriceService] 🔴 Error while polling prices: {e}"),
+            }
+
+            tokio::time::sleep(Self::POLL_INTERVAL).await;
+        }
+    }
+
+    fn quoted_currencies() -> Vec<Currency> {
+        Currency::all()
+            .filter(|currency| *currency != Currency::USD)
+            .collect()
+    }
+
+    /// Fetch every feed at once and publish the prices that passed validation
+    async fn poll(&self) -> anyhow::Result<Vec<(Currency, PriceQuote)>> {
+        let mut quotes = Vec::new();
+        for (currency, quote) in self.pyth.latest_prices(&Self::quoted_currencies()).await? {
+            match quote {
+                Ok(quote) => {
+                    CURRENCIES_PRICES.publish(currency, quote).await;
+                    quotes.push((currency, quote));
+                }
+                // The on-demand fallback chain takes over for this currency
+                Err(e) if e.is::<PriceRejection>() => tracing::warn!("[PriceService] {e}"),
+                Err(e) => tracing::warn!("[PriceService] No price for {currency}: {e}"),
+            }
+        }
+        Ok(quotes)
+    }
+
+    async fn persist(&self, quotes: Vec<(Currency, PriceQuote)>) -> anyhow::Result<()> {
+        let samples: Vec<NewCurrencyPrice> = quotes
+            .into_iter()
+            .map(|(currency, quote)| NewCurrencyPrice {
+                currency: currency.to_string(),
+                price: quote.price,
+                sampled_at: quote.published_at,
+                source: quote.source.as_str().to_string(),
+            })
+            .collect();
+
+        self.db_pool
+            .interact_with_context("insert currency prices".to_string(), move |conn| {
+                CurrencyPrice::insert_batch(&samples, conn)
+            })
+            .await?;
+
+        Ok(())
+    }
+
+    /// Fill the daily prices (at midnight UTC) missing over the last `BACKFILL_DAYS`
+    async fn backfill(&self) -> anyhow::Result<()> {
+        let today = Utc::now().date_naive();
+        let since = (today - Days::new(Self::BACKFILL_DAYS)).and_time(chrono::NaiveTime::MIN);
+        let since = DateTime::<Utc>::from_naive_utc_and_offset(since, Utc);
+
+        let currencies = Self::quoted_currencies();
+        let mut known_timestamps: Vec<HashSet<DateTime<Utc>>> =
+            Vec::with_capacity(currencies.len());
+        for &currency in &currencies {
+            let timestamps = self
+                .db_pool
+                .interact_with_context(
+                    format!("fetch sampled timestamps of {currency}"),
+                    move |conn| CurrencyPrice::find_sampled_since(currency.as_ref(), since, conn),
+                )
+                .await?;
+            known_timestamps.push(timestamps.into_iter().collect());
+        }
+
+        let mut backfilled = 0;
+        let mut day = since;
+        while day.date_naive() < today {
+            let missing: Vec<Currency> = currencies
+                .iter()
+                .zip(&known_timestamps)
+                .filter(|(_, timestamps)| !timestamps.contains(&day))
+                .map(|(currency, _)| *currency)
+                .collect();
+
+            if !missing.is_empty() {
+                match self.pyth.prices_at(&missing, day).await {
+                    Ok(quotes) => {
+                        let samples: Vec<NewCurrencyPrice> = quotes
+                            .into_iter()
+                            .map(|(currency, quote)| NewCurrencyPrice {
+                                currency: currency.to_string(),
+                                price: quote.price,
+                                sampled_at: day,
+                                source: quote.source.as_str().to_string(),
+                            })
+                            .collect();
+                        backfilled += self
+                            .db_pool
+                            .interact_with_context(
+                                format!("insert currency prices of {day}"),
+                                move |conn| CurrencyPrice::insert_batch(&samples, conn),
+                            )
+                            .await?;
+                    }
+                    Err(e) => tracing::warn!("[PriceService] Could not backfill {day}: {e}"),
+                }
+                tokio::time::sleep(Self::BACKFILL_REQUEST_DELAY).await;
+            }
+
+            day += chrono::Duration::days(1);
+        }
+
+        tracing::info!("[PriceService] ✅ Backfilled {backfilled} daily prices");
+        Ok(())
+    }
+}
+
+#[async_trait::async_trait]
+impl Service for PriceService {
+    async fn start<'a>(&mut self, mut runner: ServiceRunner<'a>) -> anyhow::Result<()> {
+        let db_pool = self.db_pool.clone();
+        let pyth = self.pyth.clone();
+
+        runner.spawn_loop(move |ctx| async move {
+            let service = Self {
+                db_pool: db_pool.clone(),
+                pyth: pyth.clone(),
+            };
+
+            if let Some(result) = ctx.run_until_cancelled(service.run_forever()).await {
+                result?;
+            }
+
+            anyhow::Ok(())
+        });
+
+        Ok(())
+    }
+}
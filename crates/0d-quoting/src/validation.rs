@@ -0,0 +1,170 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::{Decimal, dec};
+
+use crate::currencies::{Currency, PriceQuote};
+
+const ENV_MAX_PRICE_AGE_SECS: &str = "PYTH_MAX_PRICE_AGE_SECS";
+const ENV_MAX_CONFIDENCE_RATIO: &str = "PYTH_MAX_CONFIDENCE_RATIO";
+
+/// A live price that was received but must not be used
+#[derive(Debug, thiserror::Error)]
+pub enum PriceRejection {
+    #[error(
+        "Pyth price of {currency} is stale: published at {published_at}, max age {max_age_secs}s"
+    )]
+    Stale {
+        currency: Currency,
+        published_at: DateTime<Utc>,
+        max_age_secs: i64,
+    },
+    #[error(
+        "Pyth price of {currency} is too uncertain: conf {confidence} for {price}, max ratio {max_ratio}"
+    )]
+    Uncertain {
+        currency: Currency,
+        price: Decimal,
+        confidence: Decimal,
+        max_ratio: Decimal,
+    },
+    #[error("Pyth price of {currency} is not positive: {price}")]
+    NotPositive { currency: Currency, price: Decimal },
+}
+
+/// Thresholds a live price must satisfy before being used
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PriceValidation {
+    /// Oldest publish time accepted
+    pub max_age: chrono::Duration,
+    /// Highest confidence interval accepted, relative to the price
+    pub max_confidence_ratio: Decimal,
+}
+
+impl Default for PriceValidation {
+    fn default() -> Self {
+        Self {
+            max_age: chrono::Duration::seconds(60),
+            max_confidence_ratio: dec!(0.01),
+        }
+    }
+}
+
+impl PriceValidation {
+    /// Read the thresholds from `PYTH_MAX_PRICE_AGE_SECS` and `PYTH_MAX_CONFIDENCE_RATIO`,
+    /// falling back to the defaults for missing or invalid values
+    pub fn from_env() -> Self {
+        let mut validation = Self::default();
+
+        if let Ok(value) = std::env::var(ENV_MAX_PRICE_AGE_SECS) {
+            match value.parse::<i64>() {
+                Ok(secs) if secs > 0 => validation.max_age = chrono::Duration::seconds(secs),
+                _ => tracing::warn!("Ignoring invalid {ENV_MAX_PRICE_AGE_SECS}: {value}"),
+            }
+        }
+        if let Ok(value) = std::env::var(ENV_MAX_CONFIDENCE_RATIO) {
+            match value.parse::<Decimal>() {
+                Ok(ratio) if ratio > Decimal::ZERO => validation.max_confidence_ratio = ratio,
+                _ => tracing::warn!("Ignoring invalid {ENV_MAX_CONFIDENCE_RATIO}: {value}"),
+            }
+        }
+
+        validation
+    }
+
+    /// Reject prices that are too old or too uncertain to be used
+    pub fn check(
+        &self,
+        currency: Currency,
+        quote: &PriceQuote,
+        now: DateTime<Utc>,
+    ) -> Result<(), PriceRejection> {
+        if now - quote.published_at > self.max_age {
+            return Err(PriceRejection::Stale {
+                currency,
+                published_at: quote.published_at,
+                max_age_secs: self.max_age.num_seconds(),
+            });
+        }
+        if quote.price <= Decimal::ZERO {
+            return Err(PriceRejection::NotPositive {
+                currency,
+                price: quote.price,
+            });
+        }
+        if let Some(confidence) = quote.confidence
+            && confidence / quote.price > self.max_confidence_ratio
+        {
+            return Err(PriceRejection::Uncertain {
+                currency,
+                price: quote.price,
+                confidence,
+                max_ratio: self.max_confidence_ratio,
+            });
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+
+    use super::*;
+    use crate::currencies::PriceSource;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 1, 1, 12, 0, 0).unwrap()
+    }
+
+    fn quote(price: Decimal, confidence: Option<Decimal>, age_secs: i64) -> PriceQuote {
+        PriceQuote {
+            price,
+            confidence,
+            source: PriceSource::Pyth,
+            published_at: now() - chrono::Duration::seconds(age_secs),
+        }
+    }
+
+    #[test]
+    fn test_accepts_fresh_and_certain_price() {
+        let validation = PriceValidation::default();
+
+        assert!(
+            validation
+                .check(Currency::ETH, &quote(dec!(3000), Some(dec!(3)), 60), now())
+                .is_ok()
+        );
+        assert!(
+            validation
+                .check(Currency::ETH, &quote(dec!(3000), None, 0), now())
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_rejects_stale_price() {
+        let result =
+            PriceValidation::default().check(Currency::ETH, &quote(dec!(3000), None, 61), now());
+
+        assert!(matches!(result, Err(PriceRejection::Stale { .. })));
+    }
+
+    #[test]
+    fn test_rejects_non_positive_price() {
+        let result =
+            PriceValidation::default().check(Currency::ETH, &quote(Decimal::ZERO, None, 0), now());
+
+        assert!(matches!(result, Err(PriceRejection::NotPositive { .. })));
+    }
+
+    #[test]
+    fn test_rejects_uncertain_price() {
+        let result = PriceValidation::default().check(
+            Currency::ETH,
+            &quote(dec!(3000), Some(dec!(31)), 0),
+            now(),
+        );
+
+        assert!(matches!(result, Err(PriceRejection::Uncertain { .. })));
+    }
+}
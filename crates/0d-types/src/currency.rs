@@ -2,25 +2,150 @@ use serde::{Deserialize, Serialize};
 use strum::{AsRefStr, Display, EnumString};
 use utoipa::ToSchema;
 
-#[derive(
-    Debug,
-    Clone,
-    Copy,
-    Default,
-    Serialize,
-    Deserialize,
-    ToSchema,
-    Hash,
-    Eq,
-    PartialEq,
-    Display,
-    AsRefStr,
-    EnumString,
-)]
-#[strum(ascii_case_insensitive)]
-#[serde(rename_all = "UPPERCASE")]
-pub enum Currency {
+/// Static metadata of a supported currency
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CurrencyInfo {
+    pub currency: Currency,
+    pub ticker: &'static str,
+    pub decimals: u32,
+    /// Pyth price feed quoted against USD (`None` for USD itself)
+    pub pyth_feed_id: Option<&'static str>,
+    /// Spot pair of the Pragma oracle on Starknet, used when Pyth is unavailable
+    pub pragma_pair_id: Option<&'static str>,
+    /// Token contract on Starknet mainnet (`None` for fiat currencies)
+    pub starknet_address: Option<&'static str>,
+}
+
+/// Declare the `Currency` enum along with its `CURRENCY_REGISTRY` entries,
+/// so that supporting a new currency only takes a new row in the table below
+macro_rules! currency_registry {
+    ($(
+        $(#[$attr:meta])*
+        $currency:ident {
+            ticker: $ticker:literal,
+            decimals: $decimals:literal,
+            pyth_feed_id: $pyth_feed_id:expr,
+            pragma_pair_id: $pragma_pair_id:expr,
+            starknet_address: $starknet_address:expr $(,)?
+        }
+    ),* $(,)?) => {
+        #[derive(
+            Debug,
+            Clone,
+            Copy,
+            Default,
+            Serialize,
+            Deserialize,
+            ToSchema,
+            Hash,
+            Eq,
+            PartialEq,
+            Display,
+            AsRefStr,
+            EnumString,
+        )]
+        #[strum(ascii_case_insensitive)]
+        #[serde(rename_all = "UPPERCASE")]
+        pub enum Currency {
+            $($(#[$attr])* $currency,)*
+        }
+
+        /// Every currency the API can quote, with the data needed to price it,
+        /// in the order of the `Currency` variants
+        pub const CURRENCY_REGISTRY: &[CurrencyInfo] = &[
+            $(CurrencyInfo {
+                currency: Currency::$currency,
+                ticker: $ticker,
+                decimals: $decimals,
+                pyth_feed_id: $pyth_feed_id,
+                pragma_pair_id: $pragma_pair_id,
+                starknet_address: $starknet_address,
+            },)*
+        ];
+    };
+}
+
+currency_registry! {
     #[default]
-    USD,
-    USDC,
+    USD {
+        ticker: "USD",
+        decimals: 2,
+        pyth_feed_id: None,
+        pragma_pair_id: None,
+        starknet_address: None,
+    },
+    USDC {
+        ticker: "USDC",
+        decimals: 6,
+        pyth_feed_id: Some("0xeaa020c61cc479712813461ce153894a96a6c00b21ed0cfc2798d1f9a9e9c94a"),
+        pragma_pair_id: Some("USDC/USD"),
+        starknet_address: Some(
+            "0x053c91253bc9682c04929ca02ed00b3e423f6710d2ee7e0d5ebb06f3ecf368a8",
+        ),
+    },
+    EUR {
+        ticker: "EUR",
+        decimals: 2,
+        pyth_feed_id: Some("0xa995d00bb36a63cef7fd2c287dc105fc8f3d93779f062f09551b0af3e81ec30b"),
+        pragma_pair_id: None,
+        starknet_address: None,
+    },
+    ETH {
+        ticker: "ETH",
+        decimals: 18,
+        pyth_feed_id: Some("0xff61491a931112ddf1bd8147cd1b641375f79f5825126d665480874634fd0ace"),
+        pragma_pair_id: Some("ETH/USD"),
+        starknet_address: Some(
+            "0x049d36570d4e46f48e99674bd3fcc84644ddd6b96f7c741b1562b82f9e004dc7",
+        ),
+    },
+    STRK {
+        ticker: "STRK",
+        decimals: 18,
+        pyth_feed_id: Some("0x6a182399ff70ccf3e06024898942028204125a819e519a335ffa4579e66cd870"),
+        pragma_pair_id: Some("STRK/USD"),
+        starknet_address: Some(
+            "0x04718f5a0fc34cc1af16a1cdee98ffb20c31f5cd61d6ab07201858f4287c938d",
+        ),
+    },
+    #[serde(alias = "wBTC")]
+    WBTC {
+        ticker: "wBTC",
+        decimals: 8,
+        pyth_feed_id: Some("0xc9d8b075a5c69303365ae23633d4e085199bf5c520a3b90fed1322a0342ffc33"),
+        pragma_pair_id: Some("WBTC/USD"),
+        starknet_address: Some(
+            "0x03fe2b97c1fd336e750087d68b9b867997fd64a2661ff3ca5a7c771641e8e7ac",
+        ),
+    },
+}
+
+impl Currency {
+    /// Registry entry of the currency
+    pub const fn info(self) -> &'static CurrencyInfo {
+        &CURRENCY_REGISTRY[self as usize]
+    }
+
+    /// All supported currencies
+    pub fn all() -> impl Iterator<Item = Self> {
+        CURRENCY_REGISTRY.iter().map(|info| info.currency)
+    }
+
+    /// Find the currency of a Starknet token contract (leading zeros are ignored)
+    pub fn from_starknet_address(address: &str) -> Option<Self> {
+        let normalize = |addr: &str| {
+            addr.trim_start_matches("0x")
+                .trim_start_matches('0')
+                .to_lowercase()
+        };
+        let address = normalize(address);
+
+        CURRENCY_REGISTRY
+            .iter()
+            .find(|info| {
+                info.starknet_address
+                    .is_some_and(|a| normalize(a) == address)
+            })
+            .map(|info| info.currency)
+    }
 }
@@ -0,0 +1,141 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast;
+use utoipa::ToSchema;
+
+/// Number of events a slow subscriber can fall behind before missing some
+const EVENT_BUS_CAPACITY: usize = 1024;
+
+/// On-chain vault activity, emitted by the indexer once it has been committed to the database
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum IndexerEvent {
+    /// A user transaction was created or updated
+    Transaction(TransactionEvent),
+    /// The position of a user changed
+    Position(PositionEvent),
+    /// A new share price was observed
+    SharePrice(SharePriceEvent),
+    /// The indexer of a vault changed status (e.g. reached the tip of the chain)
+    IndexerStatus(IndexerStatusEvent),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionEventKind {
+    Deposit,
+    RedeemRequested,
+    RedeemClaimed,
+}
+
+impl TransactionEventKind {
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            Self::Deposit => "deposit",
+            Self::RedeemRequested => "redeem_requested",
+            Self::RedeemClaimed => "redeem_claimed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct TransactionEvent {
+    /// Position in the log of transaction events, increasing with every event
+    pub event_id: i64,
+    /// Id of the row in `user_transactions`
+    pub id: i32,
+    pub kind: TransactionEventKind,
+    pub vault_id: String,
+    pub user_address: String,
+    /// Partner the deposit was made through
+    pub partner_id: Option<String>,
+    pub tx_hash: String,
+    pub status: String,
+    pub amount: String,
+    pub shares: Option<String>,
+    pub block_number: i64,
+    pub block_timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct PositionEvent {
+    pub vault_id: String,
+    pub user_address: String,
+    pub share_balance: String,
+    pub cost_basis: String,
+    pub last_activity_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct SharePriceEvent {
+    pub vault_id: String,
+    pub share_price: String,
+    pub block_timestamp: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ToSchema)]
+pub struct IndexerStatusEvent {
+    pub vault_id: String,
+    pub status: String,
+    pub last_processed_block: i64,
+}
+
+impl IndexerEvent {
+    pub fn vault_id(&self) -> &str {
+        match self {
+            Self::Transaction(e) => &e.vault_id,
+            Self::Position(e) => &e.vault_id,
+            Self::SharePrice(e) => &e.vault_id,
+            Self::IndexerStatus(e) => &e.vault_id,
+        }
+    }
+
+    pub fn user_address(&self) -> Option<&str> {
+        match self {
+            Self::Transaction(e) => Some(&e.user_address),
+            Self::Position(e) => Some(&e.user_address),
+            Self::SharePrice(_) | Self::IndexerStatus(_) => None,
+        }
+    }
+
+    /// Streaming topics the event is published on, e.g. `vault:{id}:transactions`
+    pub fn topics(&self) -> Vec<String> {
+        let vault_id = self.vault_id();
+        match self {
+            Self::Transaction(e) => vec![
+                format!("vault:{vault_id}:transactions"),
+                format!("user:{}:transactions", e.user_address),
+            ],
+            Self::Position(e) => vec![format!("user:{}:positions", e.user_address)],
+            Self::SharePrice(_) => vec![format!("vault:{vault_id}:share_price")],
+            Self::IndexerStatus(_) => vec![format!("vault:{vault_id}:status")],
+        }
+    }
+}
+
+/// In-process fan-out of the indexer events to the streaming endpoints
+#[derive(Debug, Clone)]
+pub struct EventBus(broadcast::Sender<IndexerEvent>);
+
+impl EventBus {
+    pub fn new() -> Self {
+        let (sender, _) = broadcast::channel(EVENT_BUS_CAPACITY);
+        Self(sender)
+    }
+
+    /// Publish an event to the current subscribers. Events published while nobody
+    /// listens are dropped.
+    pub fn publish(&self, event: IndexerEvent) {
+        let _ = self.0.send(event);
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<IndexerEvent> {
+        self.0.subscribe()
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,3 +1,5 @@
 mod currency;
+pub mod events;
 
-pub use currency::Currency;
+pub use currency::{CURRENCY_REGISTRY, Currency, CurrencyInfo};
+pub use events::{EventBus, IndexerEvent};
@@ -0,0 +1,4 @@
+pub mod service;
+pub mod signature;
+
+pub use service::WebhookService;
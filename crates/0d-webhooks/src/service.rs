@@ -0,0 +1,218 @@
+use std::{collections::HashMap, time::Duration};
+
+use chrono::Utc;
+use deadpool_diesel::postgres::Pool;
+use futures::StreamExt;
+use pragma_common::services::{Service, ServiceRunner};
+use reqwest::header::CONTENT_TYPE;
+use zerod_db::{
+    ZerodPool,
+    models::{WebhookDelivery, WebhookSubscription},
+};
+
+use crate::signature::{
+    DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER, TIMESTAMP_HEADER, sign_payload,
+};
+
+/// Delivers the webhook outbox to the subscribed endpoints.
+///
+/// Payloads are signed with the secret of their subscription. Failed deliveries are retried
+/// with an exponential backoff, then moved to the dead letter state.
+pub struct WebhookService {
+    db_pool: Pool,
+    http: reqwest::Client,
+}
+
+impl WebhookService {
+    const POLL_INTERVAL: Duration = Duration::from_secs(2);
+    const BATCH_SIZE: i64 = 50;
+    const MAX_CONCURRENT_DELIVERIES: usize = 8;
+    const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+    /// How long a claimed delivery is hidden from other workers. Must exceed the request timeout.
+    const CLAIM_LEASE: Duration = Duration::from_secs(60);
+    const MAX_ATTEMPTS: i32 = 10;
+    const BASE_RETRY_DELAY: Duration = Duration::from_secs(30);
+    const MAX_RETRY_DELAY: Duration = Duration::from_secs(6 * 60 * 60);
+
+    pub fn new(db_pool: Pool) -> Self {
+        let http = reqwest::Client::builder()
+            .timeout(Self::REQUEST_TIMEOUT)
+            .build()
+            .expect("Could not build the webhook HTTP client");
+        Self { db_pool, http }
+    }
+
+    pub async fn run_forever(&self) -> anyhow::Result<()> {
+        loop {
+            let claimed = match self.deliver_due().await {
+                Ok(claimed) => claimed,
+                Err(e) => {
+                    tracing::error!("[WebhookService] 🔴 Error while delivering webhooks: {e}");
+                    0
+                }
+            };
+
+            // Keep draining while the outbox has a backlog
+            if claimed < Self::BATCH_SIZE as usize {
+                tokio::time::sleep(Self::POLL_INTERVAL).await;
+            }
+        }
+    }
+
+    /// Send a batch of due deliveries, returning how many were claimed
+    async fn deliver_due(&self) -> anyhow::Result<usize> {
+        let lease = chrono::Duration::from_std(Self::CLAIM_LEASE)?;
+        let deliveries = self
+            .db_pool
+            .interact_with_context("claim due webhook deliveries".to_string(), move |conn| {
+                WebhookDelivery::claim_due(Self::BATCH_SIZE, lease, conn)
+            })
+            .await?;
+        if deliveries.is_empty() {
+            return Ok(0);
+        }
+
+        let mut subscription_ids: Vec<i32> = deliveries.iter().map(|d| d.subscription_id).collect();
+        subscription_ids.sort_unstable();
+        subscription_ids.dedup();
+        let subscriptions: HashMap<i32, WebhookSubscription> = self
+            .db_pool
+            .interact_with_context("fetch webhook subscriptions".to_string(), move |conn| {
+                WebhookSubscription::find_by_ids(&subscription_ids, conn)
+            })
+            .await?
+            .into_iter()
+            .map(|subscription| (subscription.id, subscription))
+            .collect();
+
+        let claimed = deliveries.len();
+        futures::stream::iter(deliveries)
+            .for_each_concurrent(Self::MAX_CONCURRENT_DELIVERIES, |delivery| {
+                let subscription = subscriptions.get(&delivery.subscription_id);
+                async move {
+                    // The subscription was deleted in between, along with its deliveries
+                    let Some(subscription) = subscription else {
+                        return;
+                    };
+                    let delivery_id = delivery.id;
+                    if let Err(e) = self.deliver(delivery, subscription).await {
+                        tracing::error!(
+                            "[WebhookService] 🔴 Could not record delivery {delivery_id}: {e}"
+                        );
+                    }
+                }
+            })
+            .await;
+
+        Ok(claimed)
+    }
+
+    async fn deliver(
+        &self,
+        delivery: WebhookDelivery,
+        subscription: &WebhookSubscription,
+    ) -> anyhow::Result<()> {
+        let body = serde_json::to_vec(&serde_json::json!({
+            "id": delivery.id,
+            "type": delivery.event_type,
+            "created_at": delivery.created_at,
+            "data": delivery.payload,
+        }))?;
+        let timestamp = Utc::now().timestamp();
+
+        let response = self
+            .http
+            .post(&subscription.url)
+            .header(CONTENT_TYPE, "application/json")
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .header(EVENT_HEADER, &delivery.event_type)
+            .header(TIMESTAMP_HEADER, timestamp.to_string())
+            .header(
+                SIGNATURE_HEADER,
+                sign_payload(&subscription.secret, timestamp, &body),
+            )
+            .body(body)
+            .send()
+            .await;
+
+        let (response_status, error) = match response {
+            Ok(response) if response.status().is_success() => {
+                let status = i32::from(response.status().as_u16());
+                self.db_pool
+                    .interact_with_context(
+                        format!("mark webhook delivery {} as delivered", delivery.id),
+                        move |conn| WebhookDelivery::mark_delivered(delivery.id, status, conn),
+                    )
+                    .await?;
+                return Ok(());
+            }
+            Ok(response) => (
+                Some(i32::from(response.status().as_u16())),
+                format!("Endpoint responded with {}", response.status()),
+            ),
+            Err(e) => (None, e.to_string()),
+        };
+
+        let attempts = delivery.attempts + 1;
+        let retry_at = if attempts < Self::MAX_ATTEMPTS {
+            Some(Utc::now() + chrono::Duration::from_std(Self::retry_delay(attempts))?)
+        } else {
+            tracing::warn!(
+                "[WebhookService] ☠️ Delivery {} to subscription {} dead lettered after {attempts} attempts: {error}",
+                delivery.id,
+                subscription.id
+            );
+            None
+        };
+
+        self.db_pool
+            .interact_with_context(
+                format!("record failed webhook delivery {}", delivery.id),
+                move |conn| {
+                    WebhookDelivery::record_failure(
+                        delivery.id,
+                        retry_at,
+                        response_status,
+                        &error,
+                        conn,
+                    )
+                },
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Delay before the next attempt, doubling after every failure
+    fn retry_delay(attempts: i32) -> Duration {
+        let exponent = u32::try_from(attempts.saturating_sub(1))
+            .unwrap_or(0)
+            .min(16);
+        Self::BASE_RETRY_DELAY
+            .saturating_mul(2_u32.pow(exponent))
+            .min(Self::MAX_RETRY_DELAY)
+    }
+}
+
+#[async_trait::async_trait]
+impl Service for WebhookService {
+    async fn start<'a>(&mut self, mut runner: ServiceRunner<'a>) -> anyhow::Result<()> {
+        let db_pool = self.db_pool.clone();
+        let http = self.http.clone();
+
+        runner.spawn_loop(move |ctx| async move {
+            let service = Self {
+                db_pool: db_pool.clone(),
+                http: http.clone(),
+            };
+
+            if let Some(result) = ctx.run_until_cancelled(service.run_forever()).await {
+                result?;
+            }
+
+            anyhow::Ok(())
+        });
+
+        Ok(())
+    }
+}
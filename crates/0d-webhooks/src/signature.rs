@@ -0,0 +1,44 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+/// Id of the delivery, stable across retries so that receivers can deduplicate
+pub const DELIVERY_HEADER: &str = "X-0D-Delivery";
+pub const EVENT_HEADER: &str = "X-0D-Event";
+/// Unix timestamp of the attempt, part of the signed content
+pub const TIMESTAMP_HEADER: &str = "X-0D-Timestamp";
+/// `sha256=<hex HMAC-SHA256 of "{timestamp}.{body}">`, keyed with the subscription secret
+pub const SIGNATURE_HEADER: &str = "X-0D-Signature";
+
+const SECRET_PREFIX: &str = "whsec_";
+
+/// Sign a payload the way receivers are expected to verify it
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Generate a random signing secret for a new subscription
+pub fn generate_secret() -> String {
+    format!(
+        "{SECRET_PREFIX}{}{}",
+        uuid::Uuid::new_v4().simple(),
+        uuid::Uuid::new_v4().simple()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sign_payload() {
+        assert_eq!(
+            sign_payload("whsec_test", 1_700_000_000, br#"{"id":1}"#),
+            "sha256=2f441ba4b3b2d50d28a9ab9d9fd8880376ecd1eb5d0435401553f5d8d0a5dcf8"
+        );
+    }
+}